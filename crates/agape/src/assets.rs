@@ -1,11 +1,17 @@
+use crate::widgets::Svg;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use tracing::info;
 
-// TODO keep assets loaded
 pub struct AssetManager {
     base: PathBuf,
+    /// The svgs that have been parsed, widgets are rebuilt every frame
+    /// so they're kept here instead.
+    svgs: RefCell<HashMap<PathBuf, Svg>>,
 }
 
 impl AssetManager {
@@ -14,6 +20,7 @@ impl AssetManager {
         info!("Initialised asset directory: {:?}", full_path);
         AssetManager {
             base: base.as_ref().to_path_buf(),
+            svgs: RefCell::default(),
         }
     }
 
@@ -25,6 +32,26 @@ impl AssetManager {
             Err(err) => Err(err.into()),
         }
     }
+
+    /// Get the svg at the `path`, the svg is only read and parsed the
+    /// first time.
+    pub fn svg(&self, path: impl AsRef<Path>) -> crate::Result<Option<Svg>> {
+        let path = path.as_ref();
+        if let Some(svg) = self.svgs.borrow().get(path) {
+            return Ok(Some(svg.clone()));
+        }
+
+        let Some(mut file) = self.get(path)? else {
+            return Ok(None);
+        };
+        let mut bytes = vec![];
+        file.read_to_end(&mut bytes)?;
+        let svg = Svg::bytes(&bytes)?;
+        self.svgs
+            .borrow_mut()
+            .insert(path.to_path_buf(), svg.clone());
+        Ok(Some(svg))
+    }
 }
 
 #[cfg(test)]
@@ -42,6 +69,22 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn svgs_are_parsed_once() -> crate::Result<()> {
+        let dir = TempDir::new().unwrap();
+        let data = "<svg width='20' height='10' xmlns='http://www.w3.org/2000/svg'></svg>";
+        fs::write(dir.path().join("icon.svg"), data)?;
+        let assets = AssetManager::new(dir.path());
+        assert!(assets.svg("icon.svg")?.is_some());
+
+        // The cached svg is returned even after the file is removed
+        fs::remove_file(dir.path().join("icon.svg"))?;
+        assert!(assets.svg("icon.svg")?.is_some());
+        assert_eq!(assets.svgs.borrow().len(), 1);
+        assert!(assets.svg("does-not-exist.svg")?.is_none());
+        Ok(())
+    }
+
     #[test]
     fn asset_not_found() -> crate::Result<()> {
         let dir = TempDir::new().unwrap();
@@ -28,6 +28,7 @@ pub mod error;
 mod macros;
 pub mod message;
 pub mod resources;
+mod screenshot;
 pub mod state;
 pub mod style;
 pub mod widgets;
@@ -39,6 +40,7 @@ pub use agape_renderer as renderer;
 pub use build::build;
 pub use error::{Error, Result};
 pub use message::{Message, MessageQueue};
+pub use screenshot::render_to_image;
 use std::path::Path;
 use winit::window::WindowAttributes;
 
@@ -88,6 +90,21 @@ where
         self
     }
 
+    /// Outline the widgets that have layout errors, such as children
+    /// overflowing their parent.
+    pub fn debug_layout(mut self) -> Self {
+        self.state.debug_layout(true);
+        self
+    }
+
+    /// Set the [`Direction`](layout::Direction) of the app, use
+    /// [`Direction::RightToLeft`](layout::Direction::RightToLeft) for languages
+    /// such as Arabic and Hebrew.
+    pub fn direction(mut self, direction: layout::Direction) -> Self {
+        self.state.direction(direction);
+        self
+    }
+
     /// Load a font file, must be either a `.ttf` or `.otf` file.
     pub fn load_font(mut self, path: impl AsRef<Path>) -> Result<Self> {
         self.state.renderer_mut().load_font_file(path)?;
@@ -106,7 +123,18 @@ where
         let renderer = self.state.renderer();
         let pixels = self.pixels.as_mut().unwrap();
 
-        pixels.frame_mut().copy_from_slice(renderer.pixmap().data());
+        // Only copy the parts of the pixmap that changed
+        let pixmap = renderer.pixmap();
+        let frame = pixels.frame_mut();
+        let stride = pixmap.width() as usize * 4;
+        for region in renderer.damage().regions() {
+            let [left, right] = region.x.map(|x| x as usize * 4);
+            let [top, bottom] = region.y.map(|y| y as usize);
+            for row in top..bottom {
+                let range = row * stride + left..row * stride + right;
+                frame[range.clone()].copy_from_slice(&pixmap.data()[range]);
+            }
+        }
         pixels.render().unwrap();
     }
 
@@ -4,7 +4,14 @@ macro_rules! impl_style {
     () => {
         /// Set the background [`Color`] of a [`Widget`].
         pub fn background_color(mut self, color: impl $crate::IntoColor<$crate::Rgba>) -> Self {
-            self.style.background_color = color.into_color();
+            self.style.background = $crate::Fill::Color(color.into_color());
+            self
+        }
+
+        /// Set the background [`Fill`], which can be a color or a
+        /// [`Gradient`](agape_core::Gradient).
+        pub fn background(mut self, fill: impl Into<$crate::Fill>) -> Self {
+            self.style.background = fill.into();
             self
         }
 
@@ -20,14 +27,28 @@ macro_rules! impl_style {
             self
         }
 
+        /// Add a [`BoxShadow`](agape_core::BoxShadow), widgets can have more
+        /// than one shadow.
+        pub fn shadow(mut self, shadow: agape_core::BoxShadow) -> Self {
+            self.style.shadows.push(shadow);
+            self
+        }
+
         /// Add a border.
         pub fn border(mut self, border: agape_core::Border) -> Self {
             self.style.border = Some(border);
             self
         }
 
-        /// Set the corner radius.
-        pub fn corner_radius(mut self, radius: u32) -> Self {
+        /// Set the [`BorderStyle`](agape_core::BorderStyle) of the border.
+        pub fn border_style(mut self, style: agape_core::BorderStyle) -> Self {
+            self.style.border_style(style);
+            self
+        }
+
+        /// Set the corner radius, use a [`CornerRadius`](agape_core::CornerRadius)
+        /// to round each corner differently.
+        pub fn corner_radius(mut self, radius: impl Into<$crate::CornerRadius>) -> Self {
             self.style.corner_radius(radius);
             self
         }
@@ -67,6 +88,114 @@ macro_rules! impl_style {
             self
         }
 
+        pub fn margin(mut self, margin: $crate::layout::Margin) -> Self {
+            self.style.margin(margin);
+            self
+        }
+
+        pub fn margin_all(mut self, value: f32) -> Self {
+            self.style.margin_all(value);
+            self
+        }
+
+        pub fn margin_symmetric(mut self, vertical: f32, horizontal: f32) -> Self {
+            self.style.margin_symmetric(vertical, horizontal);
+            self
+        }
+
+        pub fn margin_top(mut self, value: f32) -> Self {
+            self.style.margin_top(value);
+            self
+        }
+
+        pub fn margin_bottom(mut self, value: f32) -> Self {
+            self.style.margin_bottom(value);
+            self
+        }
+
+        pub fn margin_left(mut self, value: f32) -> Self {
+            self.style.margin_left(value);
+            self
+        }
+
+        pub fn margin_right(mut self, value: f32) -> Self {
+            self.style.margin_right(value);
+            self
+        }
+
+        /// Override the parent's cross axis alignment for this [`Widget`].
+        pub fn align_self(mut self, alignment: $crate::layout::AxisAlignment) -> Self {
+            self.style.align_self(alignment);
+            self
+        }
+
+        /// Set the share of the parent's remaining space this [`Widget`] grows by.
+        pub fn flex_grow(mut self, factor: u8) -> Self {
+            self.style.flex_grow(factor);
+            self
+        }
+
+        /// Set how much this [`Widget`] shrinks when its parent overflows.
+        pub fn flex_shrink(mut self, factor: f32) -> Self {
+            self.style.flex_shrink(factor);
+            self
+        }
+
+        /// Keep the `width / height` of the [`Widget`] at `ratio`.
+        pub fn aspect_ratio(mut self, ratio: f32) -> Self {
+            self.style.aspect_ratio(ratio);
+            self
+        }
+
+        /// Set the direction that the content of the [`Widget`] flows in,
+        /// children use the same direction unless they set their own.
+        pub fn direction(mut self, direction: $crate::layout::Direction) -> Self {
+            self.style.direction(direction);
+            self
+        }
+
+        /// Set how children that don't fit inside the [`Widget`] are shown, use
+        /// [`Overflow::Hidden`] to clip them to its rounded bounds. A [`VStack`]
+        /// with [`Overflow::Scroll`] clips and scrolls its children.
+        ///
+        /// [`Overflow::Hidden`]: $crate::layout::Overflow::Hidden
+        /// [`Overflow::Scroll`]: $crate::layout::Overflow::Scroll
+        /// [`VStack`]: $crate::widgets::VStack
+        pub fn overflow(mut self, overflow: $crate::layout::Overflow) -> Self {
+            self.style.overflow(overflow);
+            self
+        }
+
+        /// Set the opacity of the [`Widget`] and its children, which fade out
+        /// together as a group.
+        pub fn opacity(mut self, opacity: f32) -> Self {
+            self.style.opacity(opacity);
+            self
+        }
+
+        /// Set how the [`Widget`] and its children are blended with what's
+        /// behind them.
+        pub fn blend_mode(mut self, blend_mode: $crate::renderer::BlendMode) -> Self {
+            self.style.blend_mode(blend_mode);
+            self
+        }
+
+        /// Transform the [`Widget`] and its children with an
+        /// [`Affine`](agape_core::Affine) transform, which is applied around
+        /// the middle of the widget by default. Clicks and hovers follow the
+        /// transform.
+        pub fn transform(mut self, transform: agape_core::Affine) -> Self {
+            self.style.transform(transform);
+            self
+        }
+
+        /// Set the point that the transform is applied around, relative to
+        /// the size of the [`Widget`] so `(0.0, 0.0)` is the top left corner.
+        pub fn transform_origin(mut self, x: f32, y: f32) -> Self {
+            self.style.transform_origin(x, y);
+            self
+        }
+
         /// Make the [`Widget`] fit it's children.
         pub fn fit(mut self) -> Self {
             self.style.fit();
@@ -1,5 +1,4 @@
-use agape_core::Position;
-use agape_layout::Layout;
+use agape_core::{Bounds, Position};
 
 /// The current cursor position.
 #[derive(Debug, Default, Copy, Clone, PartialOrd, PartialEq)]
@@ -28,21 +27,19 @@ impl CursorPosition {
         self.previous
     }
 
-    /// Returns `true` if the cursor just hovered the [`Widget`].
-    pub fn mouse_entered(&self, layout: &dyn Layout) -> bool {
-        let bounds = layout.bounds();
+    /// Returns `true` if the cursor just entered the `bounds`.
+    pub fn mouse_entered(&self, bounds: Bounds) -> bool {
         !bounds.within(&self.previous) && bounds.within(&self.current)
     }
 
-    /// Returns `true` if the cursor just left the [`Widget`].
-    pub fn mouse_left(&self, layout: &dyn Layout) -> bool {
-        let bounds = layout.bounds();
+    /// Returns `true` if the cursor just left the `bounds`.
+    pub fn mouse_left(&self, bounds: Bounds) -> bool {
         bounds.within(&self.previous) && !bounds.within(&self.current)
     }
 
-    /// Returns `true` if the cursor is over the [`Widget`].
-    pub fn is_hovered(&self, layout: &dyn Layout) -> bool {
-        layout.bounds().within(&self.current)
+    /// Returns `true` if the cursor is within the `bounds`.
+    pub fn is_hovered(&self, bounds: Bounds) -> bool {
+        bounds.within(&self.current)
     }
 }
 
@@ -50,7 +47,7 @@ impl CursorPosition {
 mod test {
     use super::*;
     use agape_core::Size;
-    use agape_layout::{EmptyLayout, IntrinsicSize, solve_layout};
+    use agape_layout::{EmptyLayout, IntrinsicSize, Layout, solve_layout};
 
     #[test]
     fn just_hovered() {
@@ -60,7 +57,7 @@ mod test {
         let mut cursor_pos = CursorPosition::new();
         cursor_pos.current = Position::unit(10.0);
         cursor_pos.previous = Position::unit(300.0);
-        assert!(cursor_pos.mouse_entered(&layout));
+        assert!(cursor_pos.mouse_entered(layout.bounds()));
     }
 
     #[test]
@@ -71,7 +68,7 @@ mod test {
         let mut cursor_pos = CursorPosition::new();
         cursor_pos.previous = Position::unit(10.0);
         cursor_pos.current = Position::unit(300.0);
-        assert!(cursor_pos.mouse_left(&layout));
+        assert!(cursor_pos.mouse_left(layout.bounds()));
     }
 
     #[test]
@@ -82,7 +79,7 @@ mod test {
         let mut cursor_pos = CursorPosition::new();
         cursor_pos.current = Position::unit(10.0);
         cursor_pos.previous = Position::unit(40.0);
-        assert!(!cursor_pos.mouse_entered(&layout));
+        assert!(!cursor_pos.mouse_entered(layout.bounds()));
     }
 
     #[test]
@@ -93,6 +90,6 @@ mod test {
         let mut cursor_pos = CursorPosition::new();
         cursor_pos.current = Position::unit(350.0);
         cursor_pos.previous = Position::unit(400.0);
-        assert!(!cursor_pos.mouse_entered(&layout));
+        assert!(!cursor_pos.mouse_entered(layout.bounds()));
     }
 }
@@ -0,0 +1,67 @@
+use crate::widgets::Widget;
+use agape_core::{Color, Size};
+use agape_layout::solve_layout;
+use agape_renderer::Renderer;
+use agape_renderer::rect::Rect;
+use image::RgbaImage;
+
+/// Render a `widget` offscreen into an image with the `size`, the same way
+/// it would be drawn in a window. This doesn't need a window so it can be
+/// used in tests and for generating docs.
+///
+/// Widgets that load their assets from the asset directory, like `Svg`s
+/// opened by path, need to be rendered through a [`State`](crate::state::State)
+/// and [`State::screenshot`](crate::state::State::screenshot) instead.
+///
+/// # Example
+/// ```no_run
+/// use agape::{Color, Size, render_to_image};
+/// use agape::widgets::Rect;
+///
+/// let rect = Rect::new().fixed(50.0, 50.0).background_color(Color::BLACK);
+/// let image = render_to_image(&rect, Size::new(100.0, 100.0));
+/// image.save("rect.png").unwrap();
+/// ```
+pub fn render_to_image(widget: &dyn Widget, size: Size) -> RgbaImage {
+    let width = size.width.ceil().max(1.0) as u32;
+    let height = size.height.ceil().max(1.0) as u32;
+
+    let mut renderer = Renderer::new();
+    renderer.resize(width, height);
+    let mut layout = widget.layout(&mut renderer);
+    let layout_tree = solve_layout(layout.as_mut(), size);
+
+    let background = Rect::new()
+        .size(width as f32, height as f32)
+        .color(Color::WHITE);
+    renderer.draw_rect(background);
+    widget.render(&mut renderer, &layout_tree);
+    renderer.to_image()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hstack;
+    use crate::widgets::{HStack, Rect};
+
+    #[test]
+    fn render_widget_offscreen() {
+        let widget = hstack![
+            Rect::new().fixed(20.0, 20.0).background_color(Color::BLACK),
+            Rect::new().fixed(20.0, 20.0).background_color(Color::RED)
+        ];
+        let image = render_to_image(&widget, Size::new(60.0, 30.0));
+
+        assert_eq!(image.dimensions(), (60, 30));
+        assert_eq!(image.get_pixel(10, 10).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(30, 10).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(50, 25).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn empty_size_renders_a_pixel() {
+        let image = render_to_image(&HStack::new(), Size::default());
+        assert_eq!(image.dimensions(), (1, 1));
+    }
+}
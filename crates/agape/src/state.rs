@@ -3,13 +3,17 @@ use crate::assets::AssetManager;
 use crate::message::MouseButtonDown;
 use crate::resources::CursorPosition;
 use crate::widgets::{View, Widget};
-use agape_core::{Position, Size};
-use agape_layout::{Layout, solve_layout};
+use agape_core::{Border, Color, GlobalId, Position, Size};
+use agape_layout::{Direction, Layout, LayoutError, LayoutSnapshot, LayoutTree, solve_layout};
 use agape_renderer::Renderer;
+use agape_renderer::rect::Rect;
+use image::RgbaImage;
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
+use tracing::warn;
 use winit::event::{ElementState, KeyEvent};
 use winit::keyboard::NamedKey;
 
@@ -21,6 +25,8 @@ pub struct State<T> {
     cursor_position: CursorPosition,
     message_queue: MessageQueue,
     layout: Box<dyn Layout>,
+    /// The solved layout, indexed by widget id.
+    layout_tree: LayoutTree,
     widget: Box<dyn Widget>,
     asset_manager: AssetManager,
     window_size: Size,
@@ -28,6 +34,15 @@ pub struct State<T> {
     context: Context,
     view: Box<dyn View<Widget = T>>,
     state_map: StateMap,
+    /// The errors logged last frame, so that the same errors aren't
+    /// logged every frame.
+    reported_errors: Vec<String>,
+    debug_layout: bool,
+    direction: Direction,
+    /// The hash of everything the layout was built from, see [`Widget::hash_layout`].
+    layout_key: Option<u64>,
+    /// The ids of the widgets that the layout was built from, in traversal order.
+    widget_ids: Vec<GlobalId>,
 }
 
 impl<T> State<T>
@@ -46,13 +61,41 @@ where
             window_size: Size::default(),
             widget: Box::new(widget),
             layout,
+            layout_tree: LayoutTree::default(),
             context,
             view: Box::new(root),
             renderer,
             state_map: StateMap::default(),
+            reported_errors: Vec::new(),
+            debug_layout: false,
+            direction: Direction::default(),
+            layout_key: None,
+            widget_ids: Vec::new(),
         }
     }
 
+    /// Highlight the widgets with layout errors.
+    pub fn debug_layout(&mut self, enabled: bool) {
+        self.debug_layout = enabled;
+    }
+
+    /// Set the [`Direction`] of the whole app, widgets can still
+    /// set their own direction.
+    pub fn direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    /// Get the errors from the last time the layout was solved.
+    pub fn layout_errors(&self) -> &[LayoutError] {
+        self.layout_tree.errors()
+    }
+
+    /// Take a [`LayoutSnapshot`] of the last solved layout, useful for
+    /// attaching to bug reports.
+    pub fn layout_snapshot(&self) -> LayoutSnapshot {
+        LayoutSnapshot::new(self.layout.as_ref(), self.layout_tree.errors())
+    }
+
     pub fn asset_dir(&mut self, path: impl AsRef<Path>) {
         self.asset_manager = AssetManager::new(path);
     }
@@ -74,17 +117,32 @@ where
             widget.tick(&mut self.message_queue);
             widget.get_assets(&self.asset_manager);
         });
-        let mut layout = self.widget.layout(&mut self.renderer);
-        solve_layout(layout.as_mut(), self.window_size);
-        self.layout = layout;
+        let layout_key = self.layout_key();
+        if layout_key.is_some() && layout_key == self.layout_key {
+            // Nothing the layout depends on changed, so the widgets take the
+            // ids of the last frame's widgets and the layout is kept as is
+            self.reuse_widget_ids();
+        } else {
+            // Reuse the parts of the last layout that haven't changed
+            let mut layout = self.widget.layout(&mut self.renderer);
+            layout.reuse(self.layout.as_ref());
+            layout.inherit_direction(self.direction);
+            self.layout_tree = solve_layout(layout.as_mut(), self.window_size);
+            self.layout = layout;
+            self.layout_key = layout_key;
+            self.widget_ids = widget_ids(self.widget.as_mut());
+        }
+        self.apply_transforms();
+        self.report_layout_errors();
         self.check_hovered();
         self.check_clicked();
 
         let mut index = 0;
-        self.widget.state(index, &mut self.state_map);
+        let layout_tree = &self.layout_tree;
+        self.widget.state(index, &mut self.state_map, layout_tree);
         self.widget.traverse(&mut |widget| {
             index += 1;
-            widget.state(index, &mut self.state_map);
+            widget.state(index, &mut self.state_map, layout_tree);
         });
 
         // Views have to be updated after all the widgets
@@ -94,9 +152,100 @@ where
         self.message_queue.clear();
     }
 
+    /// Draw the widgets, only the parts of the window that changed since
+    /// the last frame are drawn again. The widgets are walked twice, once
+    /// to record the damage and once to draw it.
     pub fn render(&mut self) {
-        self.renderer.pixmap_mut().fill(tiny_skia::Color::WHITE);
-        self.widget.render(&mut self.renderer, self.layout.as_ref());
+        let widget = &self.widget;
+        let layout_tree = &self.layout_tree;
+        let debug_layout = self.debug_layout;
+        let draw = |renderer: &mut Renderer| {
+            widget.render(renderer, layout_tree);
+            if debug_layout {
+                highlight_layout_errors(renderer, layout_tree);
+            }
+        };
+
+        if self.renderer.record_damage(draw).is_empty() {
+            return;
+        }
+
+        self.renderer.push_damage_clip();
+        let size = self.renderer.size();
+        let background = Rect::new()
+            .size(size.width, size.height)
+            .color(Color::WHITE);
+        self.renderer.draw_rect(background);
+        draw(&mut self.renderer);
+        self.renderer.pop_clip();
+    }
+
+    /// Hash everything the layout of the widget tree is built from, `None` if
+    /// a widget can't be hashed.
+    fn layout_key(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        self.window_size.width.to_bits().hash(&mut hasher);
+        self.window_size.height.to_bits().hash(&mut hasher);
+        self.direction.hash(&mut hasher);
+        for widget in self.widget.iter() {
+            widget.name().hash(&mut hasher);
+            widget.children().len().hash(&mut hasher);
+            if !widget.hash_layout(&mut hasher) {
+                return None;
+            }
+        }
+        Some(hasher.finish())
+    }
+
+    /// Give the widgets the ids of the widgets in the same place last frame,
+    /// which are the ids the layout was built with.
+    fn reuse_widget_ids(&mut self) {
+        let mut ids = self.widget_ids.iter().copied();
+        if let Some(id) = ids.next() {
+            self.widget.set_id(id);
+        }
+        self.widget.traverse(&mut |widget| {
+            if let Some(id) = ids.next() {
+                widget.set_id(id);
+            }
+        });
+    }
+
+    /// Log the layout errors that weren't there last frame.
+    fn report_layout_errors(&mut self) {
+        // Widget ids change every time the view is built, so the widgets
+        // are identified by their name and position in the tree instead.
+        let errors = self.layout_tree.errors();
+        let mut reported = Vec::with_capacity(errors.len());
+        if errors.is_empty() {
+            self.reported_errors = reported;
+            return;
+        }
+
+        let widgets: HashMap<GlobalId, (usize, &'static str)> = self
+            .widget
+            .iter()
+            .enumerate()
+            .map(|(index, widget)| (widget.id(), (index, widget.name())))
+            .collect();
+        for error in errors {
+            let id = error.id();
+            let Some(&(index, name)) = widgets.get(&id) else {
+                continue;
+            };
+
+            let problem = match error {
+                LayoutError::OutOfBounds { .. } => "out of its parent's bounds".to_owned(),
+                LayoutError::Overflow { axis, .. } => format!("children overflow the {axis}"),
+            };
+            let key = format!("{name}#{index}: {problem}");
+            if !self.reported_errors.contains(&key) {
+                warn!(widget = name, index, %id, "Layout error: {problem}");
+            }
+            reported.push(key);
+        }
+
+        self.reported_errors = reported;
     }
 
     /// Get a reference to the [`Renderer`].
@@ -104,6 +253,19 @@ where
         &self.renderer
     }
 
+    /// Capture the last rendered frame as an image, call [`State::render`]
+    /// first to draw the current frame.
+    pub fn screenshot(&self) -> RgbaImage {
+        self.renderer.to_image()
+    }
+
+    /// Save the last rendered frame as a png file, useful for attaching to
+    /// bug reports.
+    pub fn save_screenshot(&self, path: impl AsRef<Path>) -> crate::Result<()> {
+        self.renderer.save_png(path)?;
+        Ok(())
+    }
+
     /// Get a mutable reference to the [`Renderer`].
     pub fn renderer_mut(&mut self) -> &mut Renderer {
         &mut self.renderer
@@ -122,32 +284,44 @@ where
         self.cursor_position.update(position);
     }
 
+    /// Set the transforms of the widgets on the layout tree, so that widgets
+    /// are hit-tested where they are drawn.
+    fn apply_transforms(&mut self) {
+        let tree = &mut self.layout_tree;
+        for widget in self.widget.iter() {
+            let Some(node) = tree.get(widget.id()).copied() else {
+                continue;
+            };
+            // The tree may be from an earlier frame, so identity transforms
+            // are set as well
+            tree.set_transform(widget.id(), widget.transform(&node));
+        }
+    }
+
     pub fn check_hovered(&mut self) {
         let widget = &mut self.widget;
-        let layout = &self.layout;
-        if let Some(l) = layout.get(widget.id())
-            && self.cursor_position.mouse_entered(l)
-        {
+        let layout = &self.layout_tree;
+        // Widgets are only hit where they aren't clipped by a parent
+        let current = self.cursor_position.current();
+        let previous = self.cursor_position.previous();
+        let entered = |id| !layout.contains(id, previous) && layout.contains(id, current);
+        let left = |id| layout.contains(id, previous) && !layout.contains(id, current);
+
+        if entered(widget.id()) {
             widget.hover(&mut self.message_queue);
             widget.mouse_entered(&mut self.message_queue);
         }
 
-        if let Some(l) = layout.get(widget.id())
-            && self.cursor_position.mouse_left(l)
-        {
+        if left(widget.id()) {
             widget.hover(&mut self.message_queue);
             widget.mouse_left(&mut self.message_queue);
         }
         widget.traverse(&mut |widget| {
-            if let Some(l) = layout.get(widget.id())
-                && self.cursor_position.mouse_entered(l)
-            {
+            if entered(widget.id()) {
                 widget.mouse_entered(&mut self.message_queue);
             }
 
-            if let Some(l) = layout.get(widget.id())
-                && self.cursor_position.mouse_left(l)
-            {
+            if left(widget.id()) {
                 widget.mouse_left(&mut self.message_queue);
             }
         });
@@ -158,16 +332,13 @@ where
             return;
         }
         let widget = self.widget.as_mut();
-        let layout = self.layout.as_ref();
-        if let Some(l) = layout.get(widget.id())
-            && self.cursor_position.is_hovered(l)
-        {
+        let layout = &self.layout_tree;
+        let position = self.cursor_position.current();
+        if layout.contains(widget.id(), position) {
             widget.click(&mut self.message_queue);
         }
         widget.traverse(&mut |widget| {
-            if let Some(l) = layout.get(widget.id())
-                && self.cursor_position.is_hovered(l)
-            {
+            if layout.contains(widget.id(), position) {
                 widget.click(&mut self.message_queue);
             }
         });
@@ -187,6 +358,23 @@ where
     }
 }
 
+/// Draw an outline around the widgets with layout errors.
+fn highlight_layout_errors(renderer: &mut Renderer, layout_tree: &LayoutTree) {
+    for error in layout_tree.errors() {
+        let Some(layout) = layout_tree.get(error.id()) else {
+            continue;
+        };
+        let size = layout.size();
+        let position = layout.position();
+        let mut rect = Rect::new()
+            .size(size.width, size.height)
+            .position(position.x, position.y)
+            .color(Color::TRANSPARENT);
+        rect.border = Some(Border::all(2.0, Color::RED));
+        renderer.draw_rect(rect);
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct StateMap {
     map: HashMap<usize, Box<dyn Any>>,
@@ -304,3 +492,466 @@ impl CharacterInput {
         None
     }
 }
+
+/// Get the ids of the `widget` and its children, in traversal order.
+fn widget_ids(widget: &mut dyn Widget) -> Vec<GlobalId> {
+    let mut ids = vec![widget.id()];
+    widget.traverse(&mut |widget| ids.push(widget.id()));
+    ids
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::widgets::VStack;
+    use crate::widgets::{Button, HStack, Rect, Text};
+    use crate::{hstack, vstack};
+    use agape_layout::MeasureLayout;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Overflow;
+
+    impl View for Overflow {
+        type Widget = HStack;
+
+        fn view(&self, _: &mut Context) -> Self::Widget {
+            hstack![Rect::new().fixed(80.0, 50.0), Rect::new().fixed(80.0, 50.0)].fill()
+        }
+    }
+
+    #[test]
+    fn layout_errors() {
+        let mut state = State::new(Overflow);
+        state.resize(Size::new(100.0, 100.0));
+        state.update();
+
+        assert_eq!(state.layout_errors().len(), 1);
+        assert_eq!(
+            state.reported_errors,
+            ["HStack#0: children overflow the MainAxis"]
+        );
+    }
+
+    struct Label;
+
+    impl View for Label {
+        type Widget = HStack;
+
+        fn view(&self, _: &mut Context) -> Self::Widget {
+            hstack![Text::new("Hello world"), Rect::new().fixed(80.0, 50.0)].fill()
+        }
+    }
+
+    #[test]
+    fn reuse_unchanged_layout() {
+        let mut state = State::new(Label);
+        state.resize(Size::new(400.0, 400.0));
+        state.update();
+        let sizes: Vec<Size> = state.layout.iter().map(|layout| layout.size()).collect();
+        state.update();
+
+        assert!(!state.layout.is_dirty());
+        let reused: Vec<Size> = state.layout.iter().map(|layout| layout.size()).collect();
+        assert_eq!(reused, sizes);
+    }
+
+    /// A widget that counts the number of times it's measured.
+    struct Measured {
+        id: GlobalId,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Widget for Measured {
+        fn id(&self) -> GlobalId {
+            self.id
+        }
+
+        fn set_id(&mut self, id: GlobalId) {
+            self.id = id;
+        }
+
+        fn hash_layout(&self, _: &mut dyn Hasher) -> bool {
+            true
+        }
+
+        fn layout(&self, _: &mut Renderer) -> Box<dyn Layout> {
+            let calls = self.calls.clone();
+            let mut layout = MeasureLayout::new(move |_| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                Size::new(40.0, 20.0)
+            });
+            layout.id = self.id;
+            Box::new(layout)
+        }
+
+        fn render(&self, _: &mut Renderer, _: &LayoutTree) {}
+
+        fn children(&self) -> Vec<&dyn Widget> {
+            vec![]
+        }
+
+        fn traverse(&mut self, _: &mut dyn FnMut(&mut dyn Widget)) {}
+    }
+
+    struct MeasuredView(Arc<AtomicUsize>);
+
+    impl View for MeasuredView {
+        type Widget = HStack;
+
+        fn view(&self, _: &mut Context) -> Self::Widget {
+            let measured = Measured {
+                id: GlobalId::new(),
+                calls: self.0.clone(),
+            };
+            hstack![Text::new("Hello world"), measured].fill()
+        }
+    }
+
+    #[test]
+    fn unchanged_frames_are_not_measured() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut state = State::new(MeasuredView(calls.clone()));
+        state.resize(Size::new(400.0, 400.0));
+        state.update();
+        let measured = calls.load(Ordering::Relaxed);
+        let shaped = state.renderer.text_cache().shaped_stats();
+        let layout_tree = state.layout_tree.clone();
+
+        state.update();
+        state.update();
+
+        assert!(measured > 0);
+        assert_eq!(calls.load(Ordering::Relaxed), measured);
+        // The text isn't shaped again either
+        assert_eq!(state.renderer.text_cache().shaped_stats(), shaped);
+        assert_eq!(state.layout_tree, layout_tree);
+        assert_eq!(
+            state.layout_tree.get(state.widget.id()).unwrap().id(),
+            state.widget.id()
+        );
+
+        state.resize(Size::new(300.0, 400.0));
+        state.update();
+        assert!(calls.load(Ordering::Relaxed) > measured);
+    }
+
+    struct Scrolling;
+
+    impl View for Scrolling {
+        type Widget = VStack;
+
+        fn view(&self, _: &mut Context) -> Self::Widget {
+            vstack![Rect::new().fixed(100.0, 100.0).background_color(Color::BLACK); 6]
+                .fixed(100.0, 200.0)
+                .overflow(agape_layout::Overflow::Scroll)
+        }
+    }
+
+    fn first_child_y(state: &State<VStack>) -> f32 {
+        let id = state.widget.children()[0].id();
+        state.layout_tree.get(id).unwrap().position().y
+    }
+
+    #[test]
+    fn scroll_vstack_within_content() {
+        let mut state = State::new(Scrolling);
+        state.resize(Size::unit(400.0));
+        state.update();
+        assert!(state.layout_tree.get(state.widget.id()).unwrap().clips());
+
+        // The 600 tall content scrolls at most 400 inside the 200 tall vstack
+        state.messages_mut().add(Scroll(-1000.0));
+        state.update();
+        assert_eq!(first_child_y(&state), -400.0);
+
+        state.messages_mut().add(Scroll(100.0));
+        state.update();
+        assert_eq!(first_child_y(&state), -300.0);
+
+        state.messages_mut().add(Scroll(1000.0));
+        state.update();
+        assert_eq!(first_child_y(&state), 0.0);
+    }
+
+    #[test]
+    fn scrolled_children_are_clipped() {
+        let mut state = State::new(Scrolling);
+        state.resize(Size::unit(400.0));
+        state.messages_mut().add(Scroll(-150.0));
+        state.update();
+        state.render();
+
+        let pixmap = state.renderer().pixmap();
+        assert_eq!(pixmap.pixel(50, 150).unwrap().red(), 0);
+        assert_eq!(pixmap.pixel(50, 250).unwrap().red(), 255);
+        assert!(state.layout_errors().is_empty());
+    }
+
+    #[test]
+    fn visible_vstack_does_not_scroll() {
+        struct Visible;
+
+        impl View for Visible {
+            type Widget = VStack;
+
+            fn view(&self, _: &mut Context) -> Self::Widget {
+                vstack![Rect::new().fixed(100.0, 100.0); 6].fixed(100.0, 200.0)
+            }
+        }
+
+        let mut state = State::new(Visible);
+        state.resize(Size::unit(400.0));
+        state.messages_mut().add(Scroll(-100.0));
+        state.update();
+        assert_eq!(first_child_y(&state), 0.0);
+    }
+
+    #[test]
+    fn snapshot_current_layout() {
+        let mut state = State::new(Label);
+        state.resize(Size::new(400.0, 400.0));
+        state.update();
+
+        let snapshot = state.layout_snapshot().to_string();
+        assert_eq!(snapshot.lines().count(), state.layout.iter().count());
+        assert!(snapshot.contains("  MeasureLayout #1"));
+    }
+
+    struct Clipped(agape_layout::Overflow);
+
+    #[derive(Debug)]
+    struct Clicked;
+
+    impl View for Clipped {
+        type Widget = HStack;
+
+        fn view(&self, _: &mut Context) -> Self::Widget {
+            let hidden = Button::new(Rect::new().fixed(80.0, 50.0).background_color(Color::BLACK))
+                .on_click(|messages| messages.add(Clicked));
+            hstack![Rect::new().fixed(80.0, 50.0), hidden]
+                .fixed(100.0, 50.0)
+                .overflow(self.0)
+        }
+    }
+
+    #[test]
+    fn clipped_widgets_are_not_clicked() {
+        let mut state = State::new(Clipped(agape_layout::Overflow::Hidden));
+        state.resize(Size::new(200.0, 100.0));
+        state.update();
+        assert!(state.layout_errors().is_empty());
+
+        state.update_cursor_position(Position::new(130.0, 25.0));
+        state.messages_mut().add(MouseButtonDown);
+        state.check_clicked();
+        assert!(!state.messages_mut().has::<Clicked>());
+
+        state.update_cursor_position(Position::new(90.0, 25.0));
+        state.check_clicked();
+        assert!(state.messages_mut().has::<Clicked>());
+    }
+
+    #[test]
+    fn visible_overflow_is_clicked() {
+        let mut state = State::new(Clipped(agape_layout::Overflow::Visible));
+        state.resize(Size::new(200.0, 100.0));
+        state.update();
+
+        state.update_cursor_position(Position::new(130.0, 25.0));
+        state.messages_mut().add(MouseButtonDown);
+        state.check_clicked();
+        assert!(state.messages_mut().has::<Clicked>());
+    }
+
+    #[test]
+    fn clipped_widgets_are_not_drawn() {
+        let mut state = State::new(Clipped(agape_layout::Overflow::Hidden));
+        state.resize(Size::new(200.0, 100.0));
+        state.update();
+        state.render();
+
+        let pixmap = state.renderer().pixmap();
+        assert_eq!(pixmap.pixel(90, 25).unwrap().red(), 0);
+        assert_eq!(pixmap.pixel(130, 25).unwrap().red(), 255);
+    }
+
+    #[test]
+    fn right_to_left() {
+        let mut state = State::new(Label);
+        state.direction(Direction::RightToLeft);
+        state.resize(Size::new(400.0, 400.0));
+        state.update();
+
+        let text = &state.layout.children()[0];
+        let rect = &state.layout.children()[1];
+        assert_eq!(state.layout.direction(), Direction::RightToLeft);
+        assert_eq!(text.position().x + text.size().width, 400.0);
+        assert_eq!(rect.position().x + rect.size().width, text.position().x);
+    }
+
+    #[test]
+    fn layout_errors_after_update() {
+        let mut state = State::new(Overflow);
+        state.resize(Size::new(100.0, 100.0));
+        state.update();
+        state.update();
+
+        assert_eq!(state.layout_errors().len(), 1);
+    }
+
+    #[test]
+    fn highlight_layout_errors() {
+        let mut state = State::new(Overflow);
+        state.resize(Size::new(100.0, 100.0));
+        state.update();
+        state.debug_layout(true);
+        state.render();
+
+        let pixel = state.renderer().pixmap().pixel(0, 99).unwrap();
+        assert_eq!((pixel.red(), pixel.green(), pixel.blue()), (255, 0, 0));
+    }
+
+    /// Two rects, the color of the second one can be changed.
+    struct Swatches(Arc<Mutex<u8>>);
+
+    impl View for Swatches {
+        type Widget = HStack;
+
+        fn view(&self, _: &mut Context) -> Self::Widget {
+            let color = *self.0.lock().unwrap();
+            hstack![
+                Rect::new().fixed(50.0, 50.0).background_color(0),
+                Rect::new().fixed(50.0, 50.0).background_color(color)
+            ]
+        }
+    }
+
+    #[test]
+    fn only_damaged_regions_are_drawn() {
+        let color = Arc::new(Mutex::new(0));
+        let mut state = State::new(Swatches(color.clone()));
+        state.resize(Size::new(200.0, 100.0));
+        state.update();
+        state.render();
+        assert_eq!(state.renderer().damage().regions().len(), 1);
+
+        // Nothing changed, so nothing is drawn
+        state.update();
+        state.render();
+        assert!(state.renderer().damage().is_empty());
+
+        // Mark the first rect to check that it isn't drawn again
+        let marker = tiny_skia::PremultipliedColorU8::from_rgba(0, 0, 255, 255).unwrap();
+        state.renderer_mut().pixmap_mut().pixels_mut()[0] = marker;
+        *color.lock().unwrap() = 255;
+        state.update();
+        state.render();
+
+        let damage = state.renderer().damage().bounds().unwrap();
+        assert_eq!(damage.x, [49.0, 101.0]);
+        let pixmap = state.renderer().pixmap();
+        assert_eq!(pixmap.pixel(0, 0).unwrap(), marker);
+        assert_eq!(pixmap.pixel(75, 25).unwrap().red(), 255);
+    }
+
+    struct Rotated;
+
+    impl View for Rotated {
+        type Widget = Button<Rect>;
+
+        fn view(&self, _: &mut Context) -> Self::Widget {
+            Button::new(
+                Rect::new()
+                    .fixed(100.0, 20.0)
+                    .background_color(Color::BLACK),
+            )
+            .transform(agape_core::Affine::rotate(90.0))
+            .on_click(|messages| messages.add(Clicked))
+        }
+    }
+
+    #[test]
+    fn transformed_widgets_are_clicked_where_drawn() {
+        let mut state = State::new(Rotated);
+        state.resize(Size::new(200.0, 100.0));
+        state.update();
+        state.render();
+
+        // The button is rotated around its middle, so it's tall instead of wide
+        let pixmap = state.renderer().pixmap();
+        assert_eq!(pixmap.pixel(50, 40).unwrap().red(), 0);
+        assert_eq!(pixmap.pixel(90, 10).unwrap().red(), 255);
+
+        state.update_cursor_position(Position::new(90.0, 10.0));
+        state.messages_mut().add(MouseButtonDown);
+        state.check_clicked();
+        assert!(!state.messages_mut().has::<Clicked>());
+
+        state.update_cursor_position(Position::new(50.0, 40.0));
+        state.check_clicked();
+        assert!(state.messages_mut().has::<Clicked>());
+    }
+
+    #[test]
+    fn screenshot_without_a_window() {
+        let mut state = State::new(Overflow);
+        state.resize(Size::new(100.0, 60.0));
+        state.update();
+        state.render();
+
+        let image = state.screenshot();
+        assert_eq!(image.dimensions(), (100, 60));
+        assert_eq!(image.get_pixel(50, 55).0, [255, 255, 255, 255]);
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("screenshot.png");
+        state.save_screenshot(&path).unwrap();
+        let saved = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(saved, image);
+    }
+
+    /// An image that is loaded again every time the view is built.
+    struct Picture;
+
+    impl View for Picture {
+        type Widget = crate::widgets::Image;
+
+        fn view(&self, _: &mut Context) -> Self::Widget {
+            let buf = image::RgbaImage::from_pixel(4, 4, image::Rgba([0, 0, 0, 255]));
+            let data = Arc::new(image::DynamicImage::ImageRgba8(buf));
+            crate::widgets::Image::from_arc(data).fixed(50.0, 50.0)
+        }
+    }
+
+    #[test]
+    fn images_are_decoded_once_between_frames() {
+        let mut state = State::new(Picture);
+        state.resize(Size::new(100.0, 100.0));
+        for _ in 0..2 {
+            state.update();
+            state.renderer_mut().invalidate();
+            state.render();
+        }
+
+        let stats = state.renderer().image_cache().stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(state.renderer().pixmap().pixel(25, 25).unwrap().red(), 0);
+    }
+
+    #[test]
+    fn display_list_of_a_frame() {
+        let mut state = State::new(Overflow);
+        state.resize(Size::new(200.0, 100.0));
+        state.update();
+        state.renderer_mut().record_display_list(true);
+        state.render();
+
+        let list = state.renderer_mut().take_display_list().unwrap();
+        let sizes: Vec<Size> = list.rects().map(|rect| rect.size).collect();
+        // The window background, the hstack and its two rects
+        assert_eq!(sizes[..2], [Size::new(200.0, 100.0); 2]);
+        assert_eq!(sizes[2..], [Size::new(80.0, 50.0); 2]);
+        assert_eq!(list.rects().nth(3).unwrap().position.x, 80.0);
+    }
+}
@@ -1,13 +1,66 @@
-use agape_core::{Border, Color, IntoColor, Rgba};
-use agape_layout::{BoxSizing, IntrinsicSize, Padding};
+use agape_core::{
+    Affine, Border, BorderStyle, Bounds, BoxShadow, CornerRadius, Fill, IntoColor, Position, Rgba,
+};
+use agape_layout::{
+    AxisAlignment, BoxSizing, Direction, IntrinsicSize, LayoutParams, Margin, Overflow, Padding,
+};
+use agape_renderer::BlendMode;
+use std::hash::{Hash, Hasher};
 
-#[derive(Default, Debug, PartialOrd, PartialEq, Clone)]
+#[derive(Debug, PartialOrd, PartialEq, Clone)]
 pub struct BoxStyle {
     pub intrinsic_size: IntrinsicSize,
-    pub background_color: Color<Rgba>,
+    /// The color or gradient behind the widget.
+    pub background: Fill,
     pub border: Option<Border>,
-    pub corner_radius: u32,
+    /// The shadows, the first shadow is drawn on top.
+    pub shadows: Vec<BoxShadow>,
+    pub corner_radius: CornerRadius,
     pub padding: Padding,
+    pub margin: Margin,
+    /// Overrides the parent's cross axis alignment.
+    pub align_self: Option<AxisAlignment>,
+    pub flex_grow: u8,
+    pub flex_shrink: f32,
+    /// The `width / height` ratio to keep.
+    pub aspect_ratio: Option<f32>,
+    /// The direction of the content, `None` to use the parent's direction.
+    pub direction: Option<Direction>,
+    /// How children that don't fit are shown.
+    pub overflow: Overflow,
+    /// The opacity of the widget and its children, from `0.0` to `1.0`.
+    pub opacity: f32,
+    /// How the widget and its children are blended with what's behind them.
+    pub blend_mode: BlendMode,
+    /// The transform of the widget and its children.
+    pub transform: Affine,
+    /// The point that the transform is applied around, relative to the size
+    /// of the widget so `(0.5, 0.5)` is the middle.
+    pub transform_origin: Position,
+}
+
+impl Default for BoxStyle {
+    fn default() -> Self {
+        Self {
+            intrinsic_size: IntrinsicSize::default(),
+            background: Fill::default(),
+            border: None,
+            shadows: Vec::new(),
+            corner_radius: CornerRadius::default(),
+            padding: Padding::default(),
+            margin: Margin::default(),
+            align_self: None,
+            flex_grow: 0,
+            flex_shrink: 0.0,
+            aspect_ratio: None,
+            direction: None,
+            overflow: Overflow::default(),
+            opacity: 1.0,
+            blend_mode: BlendMode::SourceOver,
+            transform: Affine::identity(),
+            transform_origin: Position::unit(0.5),
+        }
+    }
 }
 
 impl BoxStyle {
@@ -16,9 +69,10 @@ impl BoxStyle {
         Self::default()
     }
 
-    /// Set the corner radius
-    pub fn corner_radius(&mut self, radius: u32) {
-        self.corner_radius = radius;
+    /// Set the corner radius, use a [`CornerRadius`] to round each corner
+    /// differently.
+    pub fn corner_radius(&mut self, radius: impl Into<CornerRadius>) {
+        self.corner_radius = radius.into();
     }
 
     /// Set the intrinsic width.
@@ -67,7 +121,8 @@ impl BoxStyle {
         self.intrinsic_height(BoxSizing::Flex(1));
     }
 
-    /// Set the intrinsic width [`BoxSizing::Flex`].
+    /// Set the intrinsic width [`BoxSizing::Flex`]. If there's an aspect ratio
+    /// a fixed height follows the ratio instead.
     ///
     /// # Example
     /// ```
@@ -81,9 +136,11 @@ impl BoxStyle {
     /// ```
     pub fn fill_width(&mut self) {
         self.intrinsic_width(BoxSizing::Flex(1));
+        self.follow_ratio();
     }
 
-    /// Set the intrinsic height [`BoxSizing::Flex`].
+    /// Set the intrinsic height [`BoxSizing::Flex`]. If there's an aspect ratio
+    /// a fixed width follows the ratio instead.
     ///
     /// # Example
     /// ```
@@ -97,6 +154,22 @@ impl BoxStyle {
     /// ```
     pub fn fill_height(&mut self) {
         self.intrinsic_height(BoxSizing::Flex(1));
+        self.follow_ratio();
+    }
+
+    /// When there's an aspect ratio and only one axis flexes, let the other
+    /// axis follow the ratio instead of keeping a fixed size. This way an
+    /// image that fills the width keeps its ratio instead of its height.
+    fn follow_ratio(&mut self) {
+        if self.aspect_ratio.is_none() {
+            return;
+        }
+        let IntrinsicSize { width, height } = &mut self.intrinsic_size;
+        match (*width, *height) {
+            (BoxSizing::Flex(_), BoxSizing::Fixed(_)) => *height = BoxSizing::Shrink,
+            (BoxSizing::Fixed(_), BoxSizing::Flex(_)) => *width = BoxSizing::Shrink,
+            _ => {}
+        }
     }
 
     /// Set the intrinsic width to [`BoxSizing::Shrink`].
@@ -176,6 +249,199 @@ impl BoxStyle {
         self.padding = Padding::all(value);
     }
 
+    pub fn margin(&mut self, margin: Margin) {
+        self.margin = margin;
+    }
+
+    pub fn margin_left(&mut self, value: f32) {
+        self.margin.left = value;
+    }
+
+    pub fn margin_right(&mut self, value: f32) {
+        self.margin.right = value;
+    }
+
+    pub fn margin_top(&mut self, value: f32) {
+        self.margin.top = value;
+    }
+
+    pub fn margin_bottom(&mut self, value: f32) {
+        self.margin.bottom = value;
+    }
+
+    pub fn margin_symmetric(&mut self, vertical: f32, horizontal: f32) {
+        self.margin = Margin::symmetric(vertical, horizontal);
+    }
+
+    pub fn margin_all(&mut self, value: f32) {
+        self.margin = Margin::all(value);
+    }
+
+    /// Override the parent's cross axis alignment.
+    pub fn align_self(&mut self, alignment: AxisAlignment) {
+        self.align_self = Some(alignment);
+    }
+
+    /// Set the share of the remaining space to grow by.
+    pub fn flex_grow(&mut self, factor: u8) {
+        self.flex_grow = factor;
+    }
+
+    /// Set how much to shrink by when the parent overflows.
+    pub fn flex_shrink(&mut self, factor: f32) {
+        self.flex_shrink = factor;
+    }
+
+    /// Keep the `width / height` at `ratio`.
+    ///
+    /// # Example
+    /// ```
+    /// use agape::style::BoxStyle;
+    ///
+    /// let mut style = BoxStyle::new();
+    /// style.aspect_ratio(16.0 / 9.0);
+    ///
+    /// assert_eq!(style.aspect_ratio,Some(16.0 / 9.0));
+    /// ```
+    pub fn aspect_ratio(&mut self, ratio: f32) {
+        self.aspect_ratio = Some(ratio);
+    }
+
+    /// Set the [`Direction`] that the content flows in.
+    pub fn direction(&mut self, direction: Direction) {
+        self.direction = Some(direction);
+    }
+
+    /// Set the [`Overflow`], which decides if the children are clipped.
+    pub fn overflow(&mut self, overflow: Overflow) {
+        self.overflow = overflow;
+    }
+
+    /// Set the opacity of the widget and its children, which is clamped
+    /// between `0.0` and `1.0`.
+    ///
+    /// # Example
+    /// ```
+    /// use agape::style::BoxStyle;
+    ///
+    /// let mut style = BoxStyle::new();
+    /// style.opacity(1.5);
+    ///
+    /// assert_eq!(style.opacity,1.0);
+    /// ```
+    pub fn opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    /// Set the [`BlendMode`] used to draw the widget and its children.
+    pub fn blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// Set the [`Affine`] transform of the widget and its children.
+    pub fn transform(&mut self, transform: Affine) {
+        self.transform = transform;
+    }
+
+    /// Set the point that the transform is applied around, relative to the
+    /// size of the widget.
+    pub fn transform_origin(&mut self, x: f32, y: f32) {
+        self.transform_origin = Position::new(x, y);
+    }
+
+    /// Get the transform applied around the transform origin of a widget
+    /// with the `bounds`.
+    ///
+    /// # Example
+    /// ```
+    /// use agape::{Affine, Bounds, Position, Size, style::BoxStyle};
+    ///
+    /// let mut style = BoxStyle::new();
+    /// style.transform(Affine::scale(2.0, 2.0));
+    ///
+    /// let bounds = Bounds::new(Position::default(), Size::unit(100.0));
+    /// let transform = style.resolve_transform(bounds);
+    /// // The middle stays in the same place
+    /// assert_eq!(transform.apply(Position::unit(50.0)), Position::unit(50.0));
+    /// ```
+    pub fn resolve_transform(&self, bounds: Bounds) -> Affine {
+        if self.transform.is_identity() {
+            return self.transform;
+        }
+        let [x0, x1] = bounds.x;
+        let [y0, y1] = bounds.y;
+        let origin = Position::new(
+            (x1 - x0).mul_add(self.transform_origin.x, x0),
+            (y1 - y0).mul_add(self.transform_origin.y, y0),
+        );
+        self.transform.around(origin)
+    }
+
+    /// Get the [`LayoutParams`] passed to the parent layout.
+    ///
+    /// # Example
+    /// ```
+    /// use agape::{style::BoxStyle,layout::{AxisAlignment,Margin}};
+    ///
+    /// let mut style = BoxStyle::new();
+    /// style.margin_all(12.0);
+    /// style.align_self(AxisAlignment::End);
+    ///
+    /// let params = style.layout_params();
+    /// assert_eq!(params.margin,Margin::all(12.0));
+    /// assert_eq!(params.align_self,Some(AxisAlignment::End));
+    /// ```
+    pub fn layout_params(&self) -> LayoutParams {
+        LayoutParams {
+            align_self: self.align_self,
+            margin: self.margin,
+            flex_grow: self.flex_grow,
+            flex_shrink: self.flex_shrink,
+        }
+    }
+
+    /// Hash the properties that the layout of the widget is built from.
+    pub(crate) fn hash_layout(&self, mut hasher: &mut dyn Hasher) {
+        for sizing in [self.intrinsic_size.width, self.intrinsic_size.height] {
+            match sizing {
+                BoxSizing::Fixed(size) => (0u8, size.to_bits()).hash(&mut hasher),
+                BoxSizing::Shrink => 1u8.hash(&mut hasher),
+                BoxSizing::Flex(factor) => (2u8, factor).hash(&mut hasher),
+            }
+        }
+
+        let CornerRadius {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        } = self.corner_radius;
+        let lengths = [
+            self.padding.left,
+            self.padding.right,
+            self.padding.top,
+            self.padding.bottom,
+            self.margin.left,
+            self.margin.right,
+            self.margin.top,
+            self.margin.bottom,
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+            self.flex_shrink,
+        ];
+        for length in lengths {
+            length.to_bits().hash(&mut hasher);
+        }
+
+        self.align_self.hash(&mut hasher);
+        self.flex_grow.hash(&mut hasher);
+        self.aspect_ratio.map(f32::to_bits).hash(&mut hasher);
+        self.direction.hash(&mut hasher);
+        self.overflow.hash(&mut hasher);
+    }
+
     /// Set the intrinsic width and height to [`BoxSizing::Shrink`].
     ///
     /// # Example
@@ -193,36 +459,22 @@ impl BoxStyle {
         self.intrinsic_height(BoxSizing::Shrink);
     }
 
-    /// Set the border width.
+    /// Set the border width of every side.
     pub fn border_width(&mut self, width: f32) {
-        match &mut self.border {
-            Some(border) => {
-                border.width = width;
-            }
-            None => {
-                let border = Border {
-                    width,
-                    ..Default::default()
-                };
-                self.border = Some(border);
-            }
-        }
+        let border = self.border.take().unwrap_or_default();
+        self.border = Some(border.width(width));
     }
 
-    /// Set the border color.
+    /// Set the border color of every side.
     pub fn border_color(&mut self, color: impl IntoColor<Rgba>) {
-        match &mut self.border {
-            Some(border) => {
-                border.color = color.into_color();
-            }
-            None => {
-                let border = Border {
-                    color: color.into_color(),
-                    ..Default::default()
-                };
-                self.border = Some(border);
-            }
-        }
+        let border = self.border.take().unwrap_or_default();
+        self.border = Some(border.color(color));
+    }
+
+    /// Set the [`BorderStyle`].
+    pub fn border_style(&mut self, style: BorderStyle) {
+        let border = self.border.take().unwrap_or_default();
+        self.border = Some(border.style(style));
     }
 }
 
@@ -235,6 +487,22 @@ mod test {
         let mut style = BoxStyle::new();
         style.border_width(12.0);
 
-        assert_eq!(style.border.unwrap().width, 12.0);
+        let border = style.border.unwrap();
+        assert!(border.is_uniform());
+        assert_eq!(border.top.width, 12.0);
+    }
+
+    #[test]
+    fn layout_params() {
+        let mut style = BoxStyle::new();
+        style.margin_top(4.0);
+        style.flex_grow(2);
+        style.flex_shrink(0.5);
+
+        let params = style.layout_params();
+        assert_eq!(params.margin.top, 4.0);
+        assert_eq!(params.flex_grow, 2);
+        assert_eq!(params.flex_shrink, 0.5);
+        assert_eq!(params.align_self, None);
     }
 }
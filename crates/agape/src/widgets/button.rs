@@ -1,10 +1,11 @@
-use super::{Text, Widget};
+use super::{Text, Widget, render_children, render_styled};
 use crate::style::BoxStyle;
 use crate::{MessageQueue, impl_style};
-use agape_core::GlobalId;
-use agape_layout::{BlockLayout, Layout};
+use agape_core::{Affine, GlobalId};
+use agape_layout::{BlockLayout, Layout, LayoutNode, LayoutParams, LayoutTree};
 use agape_renderer::Renderer;
 use agape_renderer::rect::Rect;
+use std::hash::Hasher;
 
 type Callback = Box<dyn FnMut(&mut MessageQueue)>;
 
@@ -62,6 +63,19 @@ impl<W: Widget> Widget for Button<W> {
         self.id
     }
 
+    fn set_id(&mut self, id: GlobalId) {
+        self.id = id;
+    }
+
+    fn hash_layout(&self, hasher: &mut dyn Hasher) -> bool {
+        self.style.hash_layout(hasher);
+        true
+    }
+
+    fn layout_params(&self) -> LayoutParams {
+        self.style.layout_params()
+    }
+
     fn traverse(&mut self, f: &mut dyn FnMut(&mut dyn Widget)) {
         f(&mut self.child);
         self.child.traverse(f);
@@ -89,22 +103,34 @@ impl<W: Widget> Widget for Button<W> {
         layout.id = self.id;
         layout.padding = self.style.padding;
         layout.intrinsic_size = self.style.intrinsic_size;
+        layout.direction = self.style.direction;
+        layout.overflow = self.style.overflow;
+        layout.corner_radius = self.style.corner_radius;
         Box::new(layout)
     }
 
-    fn render(&self, renderer: &mut Renderer, layout: &dyn Layout) {
-        let layout = layout.get(self.id).unwrap();
-        let size = layout.size();
-        let position = layout.position();
-        let mut rect = Rect::new()
-            .size(size.width, size.height)
-            .position(position.x, position.y)
-            .corner_radius(self.style.corner_radius)
-            .color(self.style.background_color.clone());
+    fn render(&self, renderer: &mut Renderer, layout_tree: &LayoutTree) {
+        let layout = layout_tree.get(self.id).unwrap();
+        render_styled(renderer, &self.style, layout_tree, layout, |renderer| {
+            let size = layout.size();
+            let position = layout.position();
+            let mut rect = Rect::new()
+                .size(size.width, size.height)
+                .position(position.x, position.y)
+                .corner_radius(self.style.corner_radius)
+                .fill(self.style.background.clone());
+
+            rect.border = self.style.border.clone();
+            rect.shadows = self.style.shadows.clone();
+            renderer.draw_rect(rect);
+            render_children(renderer, layout, |renderer| {
+                self.child.render(renderer, layout_tree);
+            });
+        });
+    }
 
-        rect.border = self.style.border.clone();
-        renderer.draw_rect(rect);
-        self.child.render(renderer, layout);
+    fn transform(&self, layout: &LayoutNode) -> Affine {
+        self.style.resolve_transform(layout.bounds())
     }
 }
 
@@ -137,8 +163,8 @@ mod test {
         let mut renderer = Renderer::new();
         renderer.resize(100, 100);
         let mut layout = button.layout(&mut renderer);
-        solve_layout(layout.as_mut(), Size::default());
-        button.render(&mut renderer, layout.as_ref());
+        let tree = solve_layout(layout.as_mut(), Size::default());
+        button.render(&mut renderer, &tree);
 
         for pixel in renderer.pixmap().pixels() {
             assert_eq!(pixel.red(), 53);
@@ -1,10 +1,11 @@
-use super::Widget;
+use super::{Widget, render_children, render_styled};
 use crate::impl_style;
 use crate::style::BoxStyle;
-use agape_core::GlobalId;
-use agape_layout::{BlockLayout, Layout};
+use agape_core::{Affine, GlobalId};
+use agape_layout::{BlockLayout, Layout, LayoutNode, LayoutParams, LayoutTree};
 use agape_renderer::Renderer;
 use agape_renderer::rect::Rect;
+use std::hash::Hasher;
 
 /// A widget that wraps another widget.
 #[derive(Clone)]
@@ -31,6 +32,19 @@ impl<W: Widget> Widget for Container<W> {
         self.id
     }
 
+    fn set_id(&mut self, id: GlobalId) {
+        self.id = id;
+    }
+
+    fn hash_layout(&self, hasher: &mut dyn Hasher) -> bool {
+        self.style.hash_layout(hasher);
+        true
+    }
+
+    fn layout_params(&self) -> LayoutParams {
+        self.style.layout_params()
+    }
+
     fn traverse(&mut self, f: &mut dyn FnMut(&mut dyn Widget)) {
         f(&mut self.child);
         self.child.traverse(f);
@@ -46,22 +60,34 @@ impl<W: Widget> Widget for Container<W> {
         layout.id = self.id;
         layout.padding = self.style.padding;
         layout.intrinsic_size = self.style.intrinsic_size;
+        layout.direction = self.style.direction;
+        layout.overflow = self.style.overflow;
+        layout.corner_radius = self.style.corner_radius;
         Box::new(layout)
     }
 
-    fn render(&self, renderer: &mut Renderer, layout: &dyn Layout) {
-        let layout = layout.get(self.id).unwrap();
-        let size = layout.size();
-        let position = layout.position();
-        let mut rect = Rect::new()
-            .size(size.width, size.height)
-            .position(position.x, position.y)
-            .corner_radius(self.style.corner_radius)
-            .color(self.style.background_color.clone());
-
-        rect.border = self.style.border.clone();
-        renderer.draw_rect(rect);
-        self.child.render(renderer, layout);
+    fn render(&self, renderer: &mut Renderer, layout_tree: &LayoutTree) {
+        let layout = layout_tree.get(self.id).unwrap();
+        render_styled(renderer, &self.style, layout_tree, layout, |renderer| {
+            let size = layout.size();
+            let position = layout.position();
+            let mut rect = Rect::new()
+                .size(size.width, size.height)
+                .position(position.x, position.y)
+                .corner_radius(self.style.corner_radius)
+                .fill(self.style.background.clone());
+
+            rect.border = self.style.border.clone();
+            rect.shadows = self.style.shadows.clone();
+            renderer.draw_rect(rect);
+            render_children(renderer, layout, |renderer| {
+                self.child.render(renderer, layout_tree);
+            });
+        });
+    }
+
+    fn transform(&self, layout: &LayoutNode) -> Affine {
+        self.style.resolve_transform(layout.bounds())
     }
 }
 
@@ -84,8 +110,8 @@ mod test {
         let mut renderer = Renderer::new();
         renderer.resize(100, 100);
         let mut layout = container.layout(&mut renderer);
-        solve_layout(layout.as_mut(), Size::default());
-        container.render(&mut renderer, layout.as_ref());
+        let tree = solve_layout(layout.as_mut(), Size::default());
+        container.render(&mut renderer, &tree);
 
         for pixel in renderer.pixmap().pixels() {
             assert_eq!(pixel.red(), 53);
@@ -93,4 +119,91 @@ mod test {
             assert_eq!(pixel.blue(), 145);
         }
     }
+
+    #[test]
+    fn children_are_clipped_to_each_corner() {
+        let container = Container::new(
+            Rect::new()
+                .fixed(100.0, 100.0)
+                .background_color(Color::BLACK),
+        )
+        .fixed(100.0, 100.0)
+        .overflow(agape_layout::Overflow::Hidden)
+        .corner_radius(agape_core::CornerRadius::top(20.0));
+
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        let mut layout = container.layout(&mut renderer);
+        let tree = solve_layout(layout.as_mut(), Size::default());
+        container.render(&mut renderer, &tree);
+
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(1, 1).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(98, 1).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(1, 98).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(98, 98).unwrap().alpha(), 255);
+    }
+
+    #[test]
+    fn opacity_fades_children_together() {
+        let container =
+            Container::new(Rect::new().fixed(50.0, 50.0).background_color(Color::BLACK))
+                .fixed(100.0, 100.0)
+                .background_color(Color::WHITE)
+                .opacity(0.5);
+
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        let mut layout = container.layout(&mut renderer);
+        let tree = solve_layout(layout.as_mut(), Size::default());
+        container.render(&mut renderer, &tree);
+
+        // The white background doesn't show through the child
+        let child = renderer.pixmap().pixel(25, 25).unwrap();
+        assert_eq!(child.red(), 0);
+        assert!((126..=129).contains(&child.alpha()));
+        let background = renderer.pixmap().pixel(75, 75).unwrap();
+        assert_eq!(background.red(), background.alpha());
+        assert!((126..=129).contains(&background.alpha()));
+    }
+
+    #[test]
+    fn layer_covers_overflowing_children() {
+        let container =
+            Container::new(Rect::new().fixed(80.0, 80.0).background_color(Color::BLACK))
+                .fixed(50.0, 50.0)
+                .opacity(0.5);
+
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.record_display_list(true);
+        let mut layout = container.layout(&mut renderer);
+        let tree = solve_layout(layout.as_mut(), Size::default());
+        container.render(&mut renderer, &tree);
+
+        let pixmap = renderer.pixmap();
+        assert!((126..=129).contains(&pixmap.pixel(70, 70).unwrap().alpha()));
+        assert_eq!(pixmap.pixel(90, 90).unwrap().alpha(), 0);
+        let list = renderer.take_display_list().unwrap();
+        let agape_renderer::DrawCommand::PushLayer { bounds, .. } = list.commands()[0] else {
+            panic!("Expected a layer");
+        };
+        assert_eq!((bounds.x, bounds.y), ([0.0, 80.0], [0.0, 80.0]));
+    }
+
+    #[test]
+    fn transparent_widgets_are_not_drawn() {
+        let container = Container::new(Rect::new().fixed(50.0, 50.0))
+            .fixed(100.0, 100.0)
+            .background_color(Color::BLACK)
+            .opacity(0.0);
+
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        let mut layout = container.layout(&mut renderer);
+        let tree = solve_layout(layout.as_mut(), Size::default());
+        container.render(&mut renderer, &tree);
+
+        assert!(renderer.pixmap().pixels().iter().all(|p| p.alpha() == 0));
+    }
 }
@@ -1,9 +1,11 @@
+use crate::impl_style;
 use crate::style::BoxStyle;
-use crate::{impl_style, widgets::Widget};
-use agape_core::GlobalId;
-use agape_layout::{AxisAlignment, HorizontalLayout, Layout};
+use crate::widgets::{Widget, render_children, render_styled};
+use agape_core::{Affine, GlobalId};
+use agape_layout::{AxisAlignment, HorizontalLayout, Layout, LayoutNode, LayoutParams, LayoutTree};
 use agape_renderer::Renderer;
 use agape_renderer::rect::Rect;
+use std::hash::{Hash, Hasher};
 
 /// A horizontal stack of widgets, placed one after another.
 ///
@@ -86,6 +88,22 @@ impl Widget for HStack {
         self.id
     }
 
+    fn set_id(&mut self, id: GlobalId) {
+        self.id = id;
+    }
+
+    fn hash_layout(&self, mut hasher: &mut dyn Hasher) -> bool {
+        self.style.hash_layout(hasher);
+        self.layout.main_axis_alignment.hash(&mut hasher);
+        self.layout.cross_axis_alignment.hash(&mut hasher);
+        self.layout.spacing.hash(&mut hasher);
+        true
+    }
+
+    fn layout_params(&self) -> LayoutParams {
+        self.style.layout_params()
+    }
+
     fn children(&self) -> Vec<&dyn Widget> {
         self.children.iter().map(|w| w.as_ref()).collect()
     }
@@ -100,6 +118,7 @@ impl Widget for HStack {
     fn layout(&self, renderer: &mut Renderer) -> Box<dyn Layout> {
         let children: Vec<Box<dyn Layout>> =
             self.children.iter().map(|w| w.layout(renderer)).collect();
+        let child_params = self.children.iter().map(|w| w.layout_params()).collect();
         let layout = HorizontalLayout {
             id: self.id,
             intrinsic_size: self.style.intrinsic_size,
@@ -107,30 +126,43 @@ impl Widget for HStack {
             cross_axis_alignment: self.layout.cross_axis_alignment,
             spacing: self.layout.spacing,
             padding: self.style.padding,
+            direction: self.style.direction,
+            overflow: self.style.overflow,
+            corner_radius: self.style.corner_radius,
             children,
+            child_params,
             ..Default::default()
         };
 
         Box::new(layout)
     }
 
-    fn render(&self, renderer: &mut Renderer, layout: &dyn Layout) {
-        let layout = layout.get(self.id).unwrap();
-        let size = layout.size();
-        let position = layout.position();
-        // TODO: test this
-        let mut rect = Rect::new()
-            .size(size.width, size.height)
-            .color(self.style.background_color.clone())
-            .position(position.x, position.y)
-            .corner_radius(self.style.corner_radius);
-
-        rect.border = self.style.border.clone();
-        renderer.draw_rect(rect);
-        // TODO: test this
-        self.children
-            .iter()
-            .for_each(|child| child.render(renderer, layout));
+    fn render(&self, renderer: &mut Renderer, layout_tree: &LayoutTree) {
+        let layout = layout_tree.get(self.id).unwrap();
+        render_styled(renderer, &self.style, layout_tree, layout, |renderer| {
+            let size = layout.size();
+            let position = layout.position();
+            // TODO: test this
+            let mut rect = Rect::new()
+                .size(size.width, size.height)
+                .fill(self.style.background.clone())
+                .position(position.x, position.y)
+                .corner_radius(self.style.corner_radius);
+
+            rect.border = self.style.border.clone();
+            rect.shadows = self.style.shadows.clone();
+            renderer.draw_rect(rect);
+            // TODO: test this
+            render_children(renderer, layout, |renderer| {
+                for child in &self.children {
+                    child.render(renderer, layout_tree);
+                }
+            });
+        });
+    }
+
+    fn transform(&self, layout: &LayoutNode) -> Affine {
+        self.style.resolve_transform(layout.bounds())
     }
 }
 
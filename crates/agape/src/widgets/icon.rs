@@ -1,12 +1,12 @@
 use crate::assets::AssetManager;
 use crate::impl_style;
 use crate::style::BoxStyle;
-use crate::widgets::{Svg, Widget};
-use agape_core::{Color, GlobalId};
-use agape_layout::{BlockLayout, EmptyLayout, Layout};
+use crate::widgets::{Svg, Widget, render_styled};
+use agape_core::{Affine, Color, GlobalId};
+use agape_layout::{BlockLayout, EmptyLayout, Layout, LayoutNode, LayoutParams, LayoutTree};
 use agape_renderer::Renderer;
 use agape_renderer::rect::Rect;
-use std::io::Read;
+use std::hash::Hasher;
 use std::path::{Path, PathBuf};
 
 /// Displays an icon onto the screen.
@@ -36,15 +36,25 @@ impl Widget for Icon {
         self.id
     }
 
+    fn set_id(&mut self, id: GlobalId) {
+        self.id = id;
+    }
+
+    fn hash_layout(&self, hasher: &mut dyn Hasher) -> bool {
+        self.style.hash_layout(hasher);
+        true
+    }
+
+    fn layout_params(&self) -> LayoutParams {
+        self.style.layout_params()
+    }
+
     fn get_assets(&mut self, assets: &AssetManager) {
-        // TODO bad!
         if self.data.is_some() {
             return;
         }
-        let mut file = assets.get(&self.path).unwrap().unwrap();
-        let mut bytes = vec![];
-        file.read_to_end(&mut bytes).unwrap();
-        let mut svg = Svg::bytes(&bytes).unwrap();
+        // The svg is parsed once and shared by the icons of every frame
+        let mut svg = assets.svg(&self.path).unwrap().unwrap();
         svg.style = self.style.clone();
 
         self.data = Some(svg);
@@ -82,19 +92,25 @@ impl Widget for Icon {
         Box::new(layout)
     }
 
-    fn render(&self, renderer: &mut Renderer, layout_tree: &dyn Layout) {
+    fn render(&self, renderer: &mut Renderer, layout_tree: &LayoutTree) {
         let layout = layout_tree.get(self.id).unwrap();
-        let size = layout.size();
-        let position = layout.position();
+        render_styled(renderer, &self.style, layout_tree, layout, |renderer| {
+            let size = layout.size();
+            let position = layout.position();
 
-        if let Some(child) = &self.data {
-            child.render(renderer, layout_tree);
-        } else {
-            let rect = Rect::new()
-                .color(Color::BLACK)
-                .size(size.width, size.height)
-                .position(position.x, position.y);
-            renderer.draw_rect(rect);
-        }
+            if let Some(child) = &self.data {
+                child.render(renderer, layout_tree);
+            } else {
+                let rect = Rect::new()
+                    .color(Color::BLACK)
+                    .size(size.width, size.height)
+                    .position(position.x, position.y);
+                renderer.draw_rect(rect);
+            }
+        });
+    }
+
+    fn transform(&self, layout: &LayoutNode) -> Affine {
+        self.style.resolve_transform(layout.bounds())
     }
 }
@@ -1,12 +1,14 @@
 use crate::Error::UnsupportedImageFormat;
 use crate::impl_style;
 use crate::style::BoxStyle;
-use crate::widgets::Widget;
-use agape_core::{GlobalId, Size};
-use agape_layout::{EmptyLayout, IntrinsicSize, Layout};
-use agape_renderer::Renderer;
+use crate::widgets::{Widget, render_styled};
+use agape_core::{Affine, GlobalId, Position, Size};
+use agape_layout::{EmptyLayout, IntrinsicSize, Layout, LayoutNode, LayoutParams, LayoutTree};
+use agape_renderer::image::{NineSlice, ObjectFit};
+use agape_renderer::{FilterQuality, Renderer};
 use image::{DynamicImage, ImageFormat, ImageReader};
 use std::fs;
+use std::hash::Hasher;
 use std::io::Cursor;
 use std::path::Path;
 use std::sync::Arc;
@@ -20,10 +22,29 @@ use std::sync::Arc;
 ///
 /// let image = Image::open("assets/standing-sprite.jpeg");
 /// ```
+///
+/// # Fit and alignment
+/// ```no_run
+/// use agape::widgets::Image;
+/// use agape::renderer::ObjectFit;
+///
+/// let image = Image::open("assets/standing-sprite.jpeg")
+///     .unwrap()
+///     .object_fit(ObjectFit::Cover)
+///     .align(0.5, 0.0)
+///     .corner_radius(12)
+///     .fixed(200.0, 200.0);
+/// ```
 pub struct Image {
     id: GlobalId,
     data: Arc<DynamicImage>,
+    /// The content key of the image, found once when it's loaded.
+    key: u64,
     style: BoxStyle,
+    fit: ObjectFit,
+    align: Position,
+    nine_slice: Option<NineSlice>,
+    filter_quality: FilterQuality,
 }
 
 impl Image {
@@ -46,17 +67,42 @@ impl Image {
         }
 
         let image = reader.decode()?;
+        Ok(Self::from_arc(Arc::new(image)))
+    }
+
+    /// Create an image from one that is already decoded. Widgets are built
+    /// again every frame, so decode the image once and share the [`Arc`]
+    /// instead of loading it in the view.
+    ///
+    /// # Example
+    /// ```
+    /// use agape::widgets::Image;
+    /// use image::DynamicImage;
+    /// use std::sync::Arc;
+    ///
+    /// let data = Arc::new(DynamicImage::new_rgba8(20, 10));
+    /// let image = Image::from_arc(data.clone());
+    /// assert_eq!(image.dimensions().width, 20.0);
+    /// ```
+    pub fn from_arc(image: Arc<DynamicImage>) -> Self {
         let width = image.width() as f32;
         let height = image.height() as f32;
 
         let mut style = BoxStyle::new();
         style.intrinsic_size = IntrinsicSize::fixed(width, height);
+        // Keep the image from stretching when it flexes
+        style.aspect_ratio(width / height);
 
-        Ok(Self {
+        Self {
             id: GlobalId::new(),
-            data: Arc::new(image),
+            key: agape_renderer::image::Image::content_key(&image),
+            data: image,
             style,
-        })
+            fit: ObjectFit::default(),
+            align: Position::unit(0.5),
+            nine_slice: None,
+            filter_quality: FilterQuality::Bilinear,
+        }
     }
 
     /// Get the dimensions of the inner image.
@@ -64,6 +110,36 @@ impl Image {
         Size::new(self.data.width() as f32, self.data.height() as f32)
     }
 
+    /// Set how the image is sized inside its bounds, images are stretched
+    /// to fill them by default.
+    pub fn object_fit(mut self, fit: ObjectFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Set where the image is placed inside its bounds when it doesn't
+    /// fill them, `(0.0, 0.0)` is the top left and `(1.0, 1.0)` is the
+    /// bottom right.
+    pub fn align(mut self, x: f32, y: f32) -> Self {
+        self.align = Position::new(x, y);
+        self
+    }
+
+    /// Draw the image in nine slices, so the corners keep their size when
+    /// it's stretched. The image no longer keeps its aspect ratio.
+    pub fn nine_slice(mut self, slice: NineSlice) -> Self {
+        self.nine_slice = Some(slice);
+        self.style.aspect_ratio = None;
+        self
+    }
+
+    /// Set how the image is sampled when it's scaled, use
+    /// [`FilterQuality::Nearest`] to keep pixel art sharp.
+    pub fn filter_quality(mut self, quality: FilterQuality) -> Self {
+        self.filter_quality = quality;
+        self
+    }
+
     impl_style! {}
 }
 
@@ -72,6 +148,19 @@ impl Widget for Image {
         self.id
     }
 
+    fn set_id(&mut self, id: GlobalId) {
+        self.id = id;
+    }
+
+    fn hash_layout(&self, hasher: &mut dyn Hasher) -> bool {
+        self.style.hash_layout(hasher);
+        true
+    }
+
+    fn layout_params(&self) -> LayoutParams {
+        self.style.layout_params()
+    }
+
     fn children(&self) -> Vec<&dyn Widget> {
         vec![]
     }
@@ -81,19 +170,31 @@ impl Widget for Image {
         let layout = EmptyLayout {
             id: self.id,
             intrinsic_size: self.style.intrinsic_size,
+            aspect_ratio: self.style.aspect_ratio,
             ..Default::default()
         };
 
         Box::new(layout)
     }
 
-    fn render(&self, renderer: &mut Renderer, layout: &dyn Layout) {
-        let layout = layout.get(self.id()).unwrap();
-        let size = layout.size();
-        let position = layout.position();
-        let mut image = agape_renderer::image::Image::new(self.data.clone());
-        image.size = size;
-        image.position = position;
-        renderer.draw_image(image);
+    fn render(&self, renderer: &mut Renderer, layout_tree: &LayoutTree) {
+        let layout = layout_tree.get(self.id()).unwrap();
+        render_styled(renderer, &self.style, layout_tree, layout, |renderer| {
+            let size = layout.size();
+            let position = layout.position();
+            let mut image = agape_renderer::image::Image::with_key(self.data.clone(), self.key)
+                .fit(self.fit)
+                .corner_radius(self.style.corner_radius)
+                .filter_quality(self.filter_quality);
+            image.size = size;
+            image.position = position;
+            image.align = self.align;
+            image.nine_slice = self.nine_slice;
+            renderer.draw_image(image);
+        });
+    }
+
+    fn transform(&self, layout: &LayoutNode) -> Affine {
+        self.style.resolve_transform(layout.bounds())
     }
 }
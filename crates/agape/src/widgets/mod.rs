@@ -15,15 +15,17 @@ mod vstack;
 use crate::assets::AssetManager;
 use crate::message::MessageQueue;
 use crate::state::{Context, StateMap};
-use agape_core::GlobalId;
-use agape_layout::Layout;
-use agape_renderer::Renderer;
+use crate::style::BoxStyle;
+use agape_core::{Affine, GlobalId};
+use agape_layout::{Layout, LayoutNode, LayoutParams, LayoutTree};
+use agape_renderer::{BlendMode, Renderer};
 pub use button::*;
 pub use container::Container;
 pub use hstack::*;
 pub use icon::Icon;
 pub use image::Image;
 pub use rect::*;
+use std::hash::Hasher;
 pub use svg::Svg;
 pub use text::Text;
 pub use text_field::TextField;
@@ -39,15 +41,48 @@ pub trait Widget {
     /// Get the `id` of the [`Widget`].
     fn id(&self) -> GlobalId;
 
-    fn state(&self, _: usize, _: &mut StateMap) {}
+    /// The name of the [`Widget`], used in diagnostics.
+    fn name(&self) -> &'static str {
+        agape_core::short_type_name::<Self>()
+    }
+
+    /// Set the `id` of the [`Widget`]. When the layout is reused the widgets
+    /// take the ids of the widgets it was built from, see [`Widget::hash_layout`].
+    fn set_id(&mut self, _: GlobalId) {}
+
+    /// Hash the properties that the [`Layout`] of this widget is built from,
+    /// not including its children. If the whole widget tree hashes the same
+    /// as last frame, the last layout is reused without building or solving
+    /// it again.
+    ///
+    /// Returns `false` if the layout can't be reused, which is the default.
+    /// Widgets that return `true` must also implement [`Widget::set_id`].
+    fn hash_layout(&self, _: &mut dyn Hasher) -> bool {
+        false
+    }
+
+    /// Save the state of the widget, which is given back to the widget in the same
+    /// place next frame in [`Widget::get_state`]. Called after the layout is solved.
+    fn state(&self, _: usize, _: &mut StateMap, _: &LayoutTree) {}
     fn get_state(&mut self, _: usize, _: &mut StateMap) {}
 
     /// Construct a [`Layout`] to solve layout for the whole
     /// widget tree.
     fn layout(&self, _: &mut Renderer) -> Box<dyn Layout>;
 
+    /// The [`LayoutParams`] the parent [`Layout`] uses to place this widget.
+    fn layout_params(&self) -> LayoutParams {
+        LayoutParams::default()
+    }
+
     /// Draw the widget to the screen.
-    fn render(&self, _: &mut Renderer, _: &dyn Layout);
+    fn render(&self, _: &mut Renderer, _: &LayoutTree);
+
+    /// Get the transform that the widget and its children are drawn with,
+    /// relative to its parent. This is used to hit-test transformed widgets.
+    fn transform(&self, _: &LayoutNode) -> Affine {
+        Affine::identity()
+    }
 
     fn children(&self) -> Vec<&dyn Widget>;
 
@@ -91,6 +126,79 @@ impl<'a> Iterator for WidgetIter<'a> {
     }
 }
 
+/// Render a widget with the transform of its style, through an offscreen layer
+/// if it's transparent or has a blend mode, so that the widget and its children
+/// are composited as one.
+pub(crate) fn render_styled(
+    renderer: &mut Renderer,
+    style: &BoxStyle,
+    layout_tree: &LayoutTree,
+    layout: &LayoutNode,
+    render: impl FnOnce(&mut Renderer),
+) {
+    let transform = style.resolve_transform(layout.bounds());
+    if transform.is_identity() {
+        with_layer(renderer, style, layout_tree, layout, render);
+        return;
+    }
+
+    renderer.push_transform(transform);
+    with_layer(renderer, style, layout_tree, layout, render);
+    renderer.pop_transform();
+}
+
+/// Render through an offscreen layer with the opacity and blend mode of the
+/// `style`. The layer is skipped if it wouldn't change anything and nothing is
+/// rendered if the opacity is `0`.
+fn with_layer(
+    renderer: &mut Renderer,
+    style: &BoxStyle,
+    layout_tree: &LayoutTree,
+    layout: &LayoutNode,
+    render: impl FnOnce(&mut Renderer),
+) {
+    if style.opacity >= 1.0 && style.blend_mode == BlendMode::SourceOver {
+        render(renderer);
+        return;
+    }
+    // Nothing would be visible
+    if style.opacity <= 0.0 {
+        return;
+    }
+
+    // The layer only needs to cover the widget, its border and shadows, and
+    // the children that aren't clipped
+    let mut rect = agape_renderer::rect::Rect::new()
+        .size(layout.size().width, layout.size().height)
+        .position(layout.position().x, layout.position().y);
+    rect.border = style.border.clone();
+    rect.shadows = style.shadows.clone();
+    let bounds = layout_tree
+        .content_bounds(layout.id())
+        .map_or(rect.bounds(), |content| content.union(&rect.bounds()));
+
+    renderer.push_layer(bounds, style.opacity, style.blend_mode);
+    render(renderer);
+    renderer.pop_layer();
+}
+
+/// Render the children of a widget, clipped to its bounds if the layout
+/// clips its overflow.
+pub(crate) fn render_children(
+    renderer: &mut Renderer,
+    layout: &LayoutNode,
+    render: impl FnOnce(&mut Renderer),
+) {
+    if !layout.clips() {
+        render(renderer);
+        return;
+    }
+
+    renderer.push_clip(layout.bounds(), layout.corner_radius());
+    render(renderer);
+    renderer.pop_clip();
+}
+
 impl dyn Widget {
     pub fn iter(&self) -> WidgetIter<'_> {
         WidgetIter { stack: vec![self] }
@@ -1,9 +1,10 @@
-use super::Widget;
+use super::{Widget, render_styled};
 use crate::impl_style;
 use crate::style::BoxStyle;
-use agape_core::{GlobalId, Position, Size};
-use agape_layout::{EmptyLayout, Layout};
+use agape_core::{Affine, GlobalId, Position, Size};
+use agape_layout::{EmptyLayout, Layout, LayoutNode, LayoutParams, LayoutTree};
 use agape_renderer::Renderer;
+use std::hash::Hasher;
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Default)]
 pub struct Rect {
@@ -26,6 +27,19 @@ impl Widget for Rect {
         self.id
     }
 
+    fn set_id(&mut self, id: GlobalId) {
+        self.id = id;
+    }
+
+    fn hash_layout(&self, hasher: &mut dyn Hasher) -> bool {
+        self.style.hash_layout(hasher);
+        true
+    }
+
+    fn layout_params(&self) -> LayoutParams {
+        self.style.layout_params()
+    }
+
     fn children(&self) -> Vec<&dyn Widget> {
         vec![]
     }
@@ -36,24 +50,32 @@ impl Widget for Rect {
         let layout = EmptyLayout {
             id: self.id,
             intrinsic_size: self.style.intrinsic_size,
+            aspect_ratio: self.style.aspect_ratio,
             ..Default::default()
         };
         Box::new(layout)
     }
 
-    fn render(&self, renderer: &mut Renderer, layout: &dyn Layout) {
-        let layout = layout.get(self.id).unwrap();
-        let size = layout.size();
-        let position = layout.position();
+    fn render(&self, renderer: &mut Renderer, layout_tree: &LayoutTree) {
+        let layout = layout_tree.get(self.id).unwrap();
+        render_styled(renderer, &self.style, layout_tree, layout, |renderer| {
+            let size = layout.size();
+            let position = layout.position();
+
+            let mut rect = agape_renderer::rect::Rect::new()
+                .size(size.width, size.height)
+                .position(position.x, position.y)
+                .corner_radius(self.style.corner_radius)
+                .fill(self.style.background.clone());
 
-        let mut rect = agape_renderer::rect::Rect::new()
-            .size(size.width, size.height)
-            .position(position.x, position.y)
-            .corner_radius(self.style.corner_radius)
-            .color(self.style.background_color.clone());
+            rect.border = self.style.border.clone();
+            rect.shadows = self.style.shadows.clone();
+            renderer.draw_rect(rect);
+        });
+    }
 
-        rect.border = self.style.border.clone();
-        renderer.draw_rect(rect);
+    fn transform(&self, layout: &LayoutNode) -> Affine {
+        self.style.resolve_transform(layout.bounds())
     }
 }
 
@@ -73,8 +95,8 @@ mod test {
         let mut renderer = Renderer::new();
         renderer.resize(100, 100);
         let mut layout = rect.layout(&mut renderer);
-        solve_layout(layout.as_mut(), Size::default());
-        rect.render(&mut renderer, layout.as_ref());
+        let tree = solve_layout(layout.as_mut(), Size::default());
+        rect.render(&mut renderer, &tree);
 
         let pixels = renderer.pixmap().pixels();
         for pixel in pixels {
@@ -83,4 +105,69 @@ mod test {
             assert_eq!(pixel.blue(), 145);
         }
     }
+
+    #[test]
+    fn gradient_background() {
+        let gradient = agape_core::Gradient::linear(180.0)
+            .stop(0.0, Color::rgb(255, 0, 0))
+            .stop(1.0, Color::rgb(0, 0, 255));
+        let rect = Rect::new().fixed(100.0, 100.0).background(gradient);
+
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        let mut layout = rect.layout(&mut renderer);
+        let tree = solve_layout(layout.as_mut(), Size::default());
+        rect.render(&mut renderer, &tree);
+
+        // The gradient goes from the top to the bottom
+        let top = renderer.pixmap().pixel(50, 0).unwrap();
+        let bottom = renderer.pixmap().pixel(50, 99).unwrap();
+        assert!(top.red() > 250 && top.blue() < 5);
+        assert!(bottom.blue() > 250 && bottom.red() < 5);
+    }
+
+    #[test]
+    fn box_shadow() {
+        let rect = Rect::new()
+            .fixed(50.0, 50.0)
+            .background_color(Color::WHITE)
+            .shadow(
+                agape_core::BoxShadow::new()
+                    .offset(10.0, 10.0)
+                    .color(Color::BLACK),
+            );
+
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        let mut layout = rect.layout(&mut renderer);
+        let tree = solve_layout(layout.as_mut(), Size::default());
+        rect.render(&mut renderer, &tree);
+
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(55, 55).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(25, 25).unwrap().red(), 255);
+        assert_eq!(pixmap.pixel(75, 75).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn rounded_border() {
+        let rect = Rect::new()
+            .fixed(50.0, 50.0)
+            .corner_radius(agape_core::CornerRadius::top(20.0))
+            .border_width(2.0)
+            .border_color(Color::BLACK);
+
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        let mut layout = rect.layout(&mut renderer);
+        let tree = solve_layout(layout.as_mut(), Size::default());
+        rect.render(&mut renderer, &tree);
+
+        // The top corners are rounded and the bottom corners are square
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(0, 0).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(49, 0).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(0, 49).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(25, 0).unwrap().alpha(), 255);
+    }
 }
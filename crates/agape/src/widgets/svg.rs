@@ -1,10 +1,11 @@
 use crate::impl_style;
 use crate::style::BoxStyle;
-use crate::widgets::Widget;
-use agape_core::GlobalId;
-use agape_layout::{EmptyLayout, IntrinsicSize, Layout};
+use crate::widgets::{Widget, render_styled};
+use agape_core::{Affine, GlobalId};
+use agape_layout::{EmptyLayout, IntrinsicSize, Layout, LayoutNode, LayoutParams, LayoutTree};
 use agape_renderer::Renderer;
 use std::fs;
+use std::hash::Hasher;
 use std::path::Path;
 use std::sync::Arc;
 use usvg::Tree;
@@ -47,6 +48,8 @@ use usvg::Tree;
 pub struct Svg {
     id: GlobalId,
     data: Arc<Tree>,
+    /// The content key of the svg, found once when it's parsed.
+    key: u64,
     pub style: BoxStyle,
 }
 
@@ -63,13 +66,16 @@ impl Svg {
         let tree = Tree::from_data(data, &options)?;
         let size = tree.size();
         let intrinsic_size = IntrinsicSize::fixed(size.width(), size.height());
+        // Keep the svg from stretching when it flexes
         let style = BoxStyle {
             intrinsic_size,
+            aspect_ratio: Some(size.width() / size.height()),
             ..Default::default()
         };
 
         Ok(Self {
             id: GlobalId::new(),
+            key: agape_renderer::Svg::content_key(&tree),
             data: Arc::new(tree),
             style,
         })
@@ -83,6 +89,19 @@ impl Widget for Svg {
         self.id
     }
 
+    fn set_id(&mut self, id: GlobalId) {
+        self.id = id;
+    }
+
+    fn hash_layout(&self, hasher: &mut dyn Hasher) -> bool {
+        self.style.hash_layout(hasher);
+        true
+    }
+
+    fn layout_params(&self) -> LayoutParams {
+        self.style.layout_params()
+    }
+
     fn children(&self) -> Vec<&dyn Widget> {
         vec![]
     }
@@ -93,18 +112,25 @@ impl Widget for Svg {
         let layout = EmptyLayout {
             id: self.id,
             intrinsic_size: self.style.intrinsic_size,
+            aspect_ratio: self.style.aspect_ratio,
             ..Default::default()
         };
         Box::new(layout)
     }
-    fn render(&self, renderer: &mut Renderer, layout: &dyn Layout) {
-        let layout = layout.get(self.id).unwrap();
-        let size = layout.size();
-        let position = layout.position();
-        let mut svg = agape_renderer::Svg::new(self.data.clone());
-        svg.size = size;
-        svg.position = position;
-        renderer.draw_svg(svg);
+    fn render(&self, renderer: &mut Renderer, layout_tree: &LayoutTree) {
+        let layout = layout_tree.get(self.id).unwrap();
+        render_styled(renderer, &self.style, layout_tree, layout, |renderer| {
+            let size = layout.size();
+            let position = layout.position();
+            let mut svg = agape_renderer::Svg::with_key(self.data.clone(), self.key);
+            svg.size = size;
+            svg.position = position;
+            renderer.draw_svg(svg);
+        });
+    }
+
+    fn transform(&self, layout: &LayoutNode) -> Affine {
+        self.style.resolve_transform(layout.bounds())
     }
 }
 
@@ -148,6 +174,46 @@ mod test {
         assert_eq!(svg.style.intrinsic_size, IntrinsicSize::fixed(50.0, 200.0));
     }
 
+    #[test]
+    fn fill_keeps_aspect_ratio() {
+        let data = "
+            <svg
+                version='1.1'
+                width='50'
+                height='200'
+                xmlns='http://www.w3.org/2000/svg'
+            >
+            </svg>
+        ";
+        let svg = Svg::bytes(data.as_bytes()).unwrap().fill();
+        let mut renderer = Renderer::new();
+        let mut layout = svg.layout(&mut renderer);
+        agape_layout::solve_layout(layout.as_mut(), agape_core::Size::new(400.0, 400.0));
+
+        assert_eq!(layout.size(), agape_core::Size::new(100.0, 400.0));
+    }
+
+    #[test]
+    fn fill_width_keeps_aspect_ratio() {
+        let data = "
+            <svg
+                version='1.1'
+                width='50'
+                height='200'
+                xmlns='http://www.w3.org/2000/svg'
+            >
+            </svg>
+        ";
+        let svg = Svg::bytes(data.as_bytes()).unwrap().fill_width();
+        let stack = crate::vstack![crate::vstack![svg].fixed(400.0, 2000.0)].fill();
+        let mut renderer = Renderer::new();
+        let mut layout = stack.layout(&mut renderer);
+        agape_layout::solve_layout(layout.as_mut(), agape_core::Size::new(800.0, 2000.0));
+
+        let size = layout.children()[0].children()[0].size();
+        assert_eq!(size, agape_core::Size::new(400.0, 1600.0));
+    }
+
     #[test]
     fn parse_svg_from_file() -> crate::Result<()> {
         let _ = fs::create_dir("temp");
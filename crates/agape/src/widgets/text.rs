@@ -1,10 +1,16 @@
-use super::Widget;
-use agape_core::{Color, GlobalId, IntoColor, Rgba};
-use agape_layout::{EmptyLayout, IntrinsicSize, Layout};
-use agape_renderer::{Family, Renderer, Style, Weight};
+use super::{Widget, render_styled};
+use crate::style::BoxStyle;
+use agape_core::{Affine, Color, GlobalId, IntoColor, Rgba};
+use agape_layout::{Layout, LayoutNode, LayoutParams, LayoutTree, MeasureLayout};
+use agape_renderer::{BlendMode, Family, Renderer, Style, Weight};
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 /// Draw text onto the screen. Emojis are fully supported.
 ///
+/// Text wraps at word boundaries when it's wider than the space its
+/// parent has available. Text has a flex shrink of `1.0`, so it gives up
+/// width and wraps when a row overflows.
+///
 /// # Example
 ///
 /// ```
@@ -24,8 +30,10 @@ pub struct Text {
     pub color: Color,
     pub weight: Weight,
     pub line_height: f32,
-    pub style: Style,
+    pub font_style: Style,
     pub families: Vec<String>,
+    /// The opacity, blend mode and transform of the text.
+    pub style: BoxStyle,
 }
 
 // TODO: add TextStyle
@@ -38,8 +46,12 @@ impl Default for Text {
             line_height: 1.25,
             color: Color::BLACK,
             families: Vec::new(),
-            style: Style::default(),
+            font_style: Style::default(),
             weight: Weight::default(),
+            style: BoxStyle {
+                flex_shrink: 1.0,
+                ..BoxStyle::default()
+            },
         }
     }
 }
@@ -72,6 +84,24 @@ impl Text {
         self
     }
 
+    /// Set the [`Style`] of the font, such as italic.
+    pub fn font_style(mut self, style: Style) -> Self {
+        self.font_style = style;
+        self
+    }
+
+    /// Set the opacity of the text, which is clamped between `0.0` and `1.0`.
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.style.opacity(opacity);
+        self
+    }
+
+    /// Set how the text is blended with what's behind it.
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.style.blend_mode(blend_mode);
+        self
+    }
+
     /// Set the font size of the `Text` widget.
     ///
     /// ```
@@ -85,13 +115,26 @@ impl Text {
         self
     }
 
+    /// Hash the properties that change the size of the text, so that the
+    /// layout can be reused when they stay the same.
+    fn layout_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.value.hash(&mut hasher);
+        self.font_size.hash(&mut hasher);
+        self.line_height.to_bits().hash(&mut hasher);
+        self.weight.hash(&mut hasher);
+        self.font_style.hash(&mut hasher);
+        self.families.hash(&mut hasher);
+        hasher.finish()
+    }
+
     fn as_text(&self) -> agape_renderer::Text<'_> {
         let mut text = agape_renderer::Text::new(self.value.as_str())
             .font_size(self.font_size as f32)
             .line_height(self.line_height)
             .color(self.color.clone())
             .weight(self.weight)
-            .style(self.style);
+            .style(self.font_style);
 
         for family in &self.families {
             text = text.add_family(Family::Name(family.as_str()));
@@ -102,33 +145,52 @@ impl Text {
 }
 
 impl Widget for Text {
+    fn set_id(&mut self, id: GlobalId) {
+        self.id = id;
+    }
+
+    fn hash_layout(&self, hasher: &mut dyn Hasher) -> bool {
+        hasher.write_u64(self.layout_key());
+        self.style.hash_layout(hasher);
+        true
+    }
+
+    fn layout_params(&self) -> LayoutParams {
+        self.style.layout_params()
+    }
+
     fn children(&self) -> Vec<&dyn Widget> {
         vec![]
     }
 
     fn layout(&self, renderer: &mut Renderer) -> Box<dyn Layout> {
-        let text = self.as_text();
-        let size = renderer.text_size(text);
-        let layout = EmptyLayout {
-            id: self.id,
-            intrinsic_size: IntrinsicSize::from(size),
-            ..Default::default()
-        };
+        let shaped = renderer.shape_text(self.as_text());
+        let baseline = shaped.baseline();
+        let mut layout = MeasureLayout::new(move |width| shaped.size(width))
+            .key(self.layout_key())
+            .baseline(baseline);
+        layout.id = self.id;
         Box::new(layout)
     }
 
     fn traverse(&mut self, _: &mut dyn FnMut(&mut dyn Widget)) {}
 
-    fn render(&self, renderer: &mut Renderer, layout: &dyn Layout) {
+    fn render(&self, renderer: &mut Renderer, layout_tree: &LayoutTree) {
         if self.value.is_empty() {
             return;
         }
 
-        let layout = layout.get(self.id).unwrap();
+        let layout = layout_tree.get(self.id).unwrap();
         let position = layout.position();
-        let mut text = self.as_text();
+        let mut text = self.as_text().max_width(layout.size().width);
         text.position = position;
-        renderer.draw_text(text)
+        render_styled(renderer, &self.style, layout_tree, layout, |renderer| {
+            renderer.draw_text(text);
+        });
+    }
+
+    fn transform(&self, layout: &LayoutNode) -> Affine {
+        self.style.resolve_transform(layout.bounds())
     }
 
     fn id(&self) -> GlobalId {
@@ -147,3 +209,85 @@ impl From<&String> for Text {
         Text::new(value.as_str())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::widgets::{Rect, VStack};
+    use crate::{hstack, vstack};
+    use agape_core::Size;
+    use agape_layout::{BoxSizing, solve_layout};
+
+    #[test]
+    fn wrap_in_parent() {
+        let mut renderer = Renderer::new();
+        let text = Text::new("The quick brown fox jumps over the lazy dog");
+        let line = renderer.text_size(text.as_text());
+
+        let vstack = VStack::new().with_child(text).fill_width();
+        let mut layout = vstack.layout(&mut renderer);
+        solve_layout(layout.as_mut(), Size::new(line.width / 2.0, 500.0));
+
+        let text_layout = &layout.children()[0];
+        assert!(text_layout.size().width <= line.width / 2.0);
+        assert!(text_layout.size().height > line.height);
+        assert_eq!(layout.size().height, text_layout.size().height);
+        assert_eq!(layout.intrinsic_size().width, BoxSizing::Flex(1));
+    }
+
+    #[test]
+    fn wrap_in_hstack() {
+        let mut renderer = Renderer::new();
+        let text = Text::new("The quick brown fox jumps over the lazy dog");
+        let line = renderer.text_size(text.as_text());
+
+        let hstack = hstack![Rect::new().fixed(50.0, 10.0), text];
+        let mut layout = hstack.layout(&mut renderer);
+        let tree = solve_layout(layout.as_mut(), Size::new(line.width / 2.0 + 50.0, 500.0));
+
+        let text_layout = &layout.children()[1];
+        assert!(text_layout.size().width <= line.width / 2.0);
+        assert!(text_layout.size().height > line.height);
+        assert!(layout.size().width <= line.width / 2.0 + 50.0);
+        assert_eq!(layout.size().height, text_layout.size().height);
+        assert!(tree.errors().is_empty());
+    }
+
+    #[test]
+    fn wrap_in_shrink_vstack() {
+        let mut renderer = Renderer::new();
+        let text = Text::new("The quick brown fox jumps over the lazy dog");
+        let line = renderer.text_size(text.as_text());
+
+        let vstack = vstack![vstack![text]];
+        let mut layout = vstack.layout(&mut renderer);
+        let tree = solve_layout(layout.as_mut(), Size::new(line.width / 2.0, 500.0));
+
+        let text_layout = &layout.children()[0].children()[0];
+        assert!(text_layout.size().width <= line.width / 2.0);
+        assert!(text_layout.size().height > line.height);
+        assert!(layout.size().width <= line.width / 2.0);
+        assert_eq!(layout.size().height, text_layout.size().height);
+        assert!(tree.errors().is_empty());
+    }
+
+    #[test]
+    fn opacity_is_drawn_through_a_layer() {
+        let render = |text: Text| {
+            let mut renderer = Renderer::new();
+            renderer.resize(100, 100);
+            renderer.record_display_list(true);
+            let mut layout = text.layout(&mut renderer);
+            let tree = solve_layout(layout.as_mut(), Size::unit(100.0));
+            text.render(&mut renderer, &tree);
+            renderer.take_display_list().unwrap()
+        };
+
+        let list = render(Text::new("Hello").opacity(0.5));
+        assert!(matches!(
+            list.commands()[0],
+            agape_renderer::DrawCommand::PushLayer { opacity: 0.5, .. }
+        ));
+        assert!(render(Text::new("Hello").opacity(0.0)).is_empty());
+    }
+}
@@ -1,10 +1,11 @@
-use super::{Container, Text, Widget};
+use super::{Container, Text, Widget, render_styled};
 use crate::MessageQueue;
 use crate::state::{CharacterInput, NamedKeyInput, StateMap};
+use crate::style::BoxStyle;
 use agape_core::GlobalId;
-use agape_layout::{BlockLayout, Layout};
-use agape_renderer::Renderer;
+use agape_layout::{BlockLayout, Layout, LayoutTree};
 use agape_renderer::rect::Rect;
+use agape_renderer::{BlendMode, Renderer};
 use tracing::trace;
 use winit::keyboard::NamedKey;
 
@@ -20,6 +21,7 @@ pub struct TextField {
     pub child: Container<Text>,
     focused: bool,
     on_change: Callback,
+    style: BoxStyle,
 }
 
 impl TextField {
@@ -35,6 +37,18 @@ impl TextField {
         self.on_change = Some(Box::new(f));
         self
     }
+
+    /// Set the opacity of the text field, which is clamped between `0.0` and `1.0`.
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.style.opacity(opacity);
+        self
+    }
+
+    /// Set how the text field is blended with what's behind it.
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.style.blend_mode(blend_mode);
+        self
+    }
 }
 
 impl Default for TextField {
@@ -50,6 +64,7 @@ impl Default for TextField {
             child,
             focused: false,
             on_change: None,
+            style: BoxStyle::default(),
         }
     }
 }
@@ -61,7 +76,7 @@ impl Widget for TextField {
         trace!("Input ({}) focus state change: {}", self.id, self.focused);
     }
 
-    fn state(&self, index: usize, state_map: &mut StateMap) {
+    fn state(&self, index: usize, state_map: &mut StateMap, _: &LayoutTree) {
         let state = TextFieldState {
             focused: self.focused,
             value: self.child.child.value.clone(),
@@ -121,18 +136,39 @@ impl Widget for TextField {
 
     fn traverse(&mut self, _: &mut dyn FnMut(&mut dyn Widget)) {}
 
-    fn render(&self, renderer: &mut Renderer, layout: &dyn Layout) {
-        let layout = layout.get(self.id).unwrap();
+    fn render(&self, renderer: &mut Renderer, layout_tree: &LayoutTree) {
+        let layout = layout_tree.get(self.id).unwrap();
         let position = layout.position();
         // TODO use Into<Position>
         let rect = Rect::new()
             .position(position.x, position.y)
             .size(layout.size().width, layout.size().height);
-        renderer.draw_rect(rect);
-        self.child.render(renderer, layout);
+        render_styled(renderer, &self.style, layout_tree, layout, |renderer| {
+            renderer.draw_rect(rect);
+            self.child.render(renderer, layout_tree);
+        });
     }
 
     fn id(&self) -> GlobalId {
         self.id
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use agape_core::Size;
+    use agape_layout::solve_layout;
+
+    #[test]
+    fn transparent_text_field_is_not_drawn() {
+        let text_field = TextField::new().opacity(0.0);
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        let mut layout = text_field.layout(&mut renderer);
+        let tree = solve_layout(layout.as_mut(), Size::unit(100.0));
+        text_field.render(&mut renderer, &tree);
+
+        assert!(renderer.pixmap().pixels().iter().all(|p| p.alpha() == 0));
+    }
+}
@@ -1,13 +1,19 @@
-use crate::state::Scroll;
+use crate::state::{Scroll, StateMap};
 use crate::style::BoxStyle;
-use crate::{MessageQueue, impl_style, widgets::Widget};
-use agape_core::GlobalId;
-use agape_layout::{AxisAlignment, Layout, VerticalLayout};
+use crate::widgets::{Widget, render_children, render_styled};
+use crate::{MessageQueue, impl_style};
+use agape_core::{Affine, GlobalId, Position};
+use agape_layout::{
+    AxisAlignment, BoxSizing, Layout, LayoutNode, LayoutParams, LayoutTree, Overflow, ScrollLayout,
+    VerticalLayout,
+};
 use agape_renderer::Renderer;
 use agape_renderer::rect::Rect;
+use std::hash::{Hash, Hasher};
 
 /// A vertical stack that places its children vertically one after
-/// another.
+/// another. Children that don't fit are scrolled when the overflow is
+/// [`Overflow::Scroll`].
 ///
 /// ```
 /// use agape::{vstack,widgets::Text};
@@ -22,6 +28,14 @@ pub struct VStack {
     children: Vec<Box<dyn Widget>>,
     layout: VerticalLayout,
     pub style: BoxStyle,
+    /// How far the children are scrolled, only used when the overflow
+    /// is [`Overflow::Scroll`].
+    scroll_offset: f32,
+}
+
+/// The state that's kept between frames.
+struct VStackState {
+    scroll_offset: f32,
 }
 
 impl Default for VStack {
@@ -38,6 +52,7 @@ impl VStack {
             children: vec![],
             layout: VerticalLayout::new(),
             style: BoxStyle::default(),
+            scroll_offset: 0.0,
         }
     }
 
@@ -105,6 +120,12 @@ impl VStack {
         self
     }
 
+    /// Returns `true` if the children scroll, which is when the overflow
+    /// is [`Overflow::Scroll`].
+    fn scrolls(&self) -> bool {
+        self.style.overflow == Overflow::Scroll
+    }
+
     impl_style!();
 }
 
@@ -113,9 +134,49 @@ impl Widget for VStack {
         self.id
     }
 
+    fn set_id(&mut self, id: GlobalId) {
+        self.id = id;
+    }
+
+    fn hash_layout(&self, mut hasher: &mut dyn Hasher) -> bool {
+        self.style.hash_layout(hasher);
+        self.layout.main_axis_alignment.hash(&mut hasher);
+        self.layout.cross_axis_alignment.hash(&mut hasher);
+        self.layout.spacing.hash(&mut hasher);
+        self.scroll_offset.to_bits().hash(&mut hasher);
+        true
+    }
+
+    fn layout_params(&self) -> LayoutParams {
+        self.style.layout_params()
+    }
+
+    fn state(&self, index: usize, state_map: &mut StateMap, layout_tree: &LayoutTree) {
+        if !self.scrolls() {
+            return;
+        }
+
+        // Keep the offset that the layout clamped to the content
+        let scroll_offset = layout_tree
+            .get(self.id)
+            .map_or(self.scroll_offset, |layout| layout.scroll_offset().y);
+        state_map.insert(index, VStackState { scroll_offset });
+    }
+
+    fn get_state(&mut self, index: usize, state_map: &mut StateMap) {
+        if let Some(state) = state_map.get::<VStackState>(index) {
+            self.scroll_offset = state.scroll_offset;
+        }
+    }
+
     fn tick(&mut self, messages: &mut MessageQueue) {
+        if !self.scrolls() {
+            return;
+        }
+
+        // Scrolling up moves the children down, towards the start
         if let Some(scroll) = messages.get::<Scroll>() {
-            self.layout.scroll(scroll.0);
+            self.scroll_offset = (self.scroll_offset - scroll.0).max(0.0);
         }
     }
 
@@ -133,38 +194,72 @@ impl Widget for VStack {
     fn layout(&self, renderer: &mut Renderer) -> Box<dyn Layout> {
         let children: Vec<Box<dyn Layout>> =
             self.children.iter().map(|w| w.layout(renderer)).collect();
+        let child_params = self.children.iter().map(|w| w.layout_params()).collect();
         // TODO: join style and layout
-        let layout = VerticalLayout {
+        let mut layout = VerticalLayout {
             id: self.id,
             intrinsic_size: self.style.intrinsic_size,
             main_axis_alignment: self.layout.main_axis_alignment,
             cross_axis_alignment: self.layout.cross_axis_alignment,
             spacing: self.layout.spacing,
             padding: self.style.padding,
-            scroll_offset: self.layout.scroll_offset,
+            direction: self.style.direction,
+            overflow: self.style.overflow,
+            corner_radius: self.style.corner_radius,
             children,
+            child_params,
             ..Default::default()
         };
 
-        Box::new(layout)
+        if !self.scrolls() {
+            return Box::new(layout);
+        }
+
+        // The children are as tall as they need to be and scroll inside
+        // the vstack's bounds
+        let mut scroll = ScrollLayout::new(Box::new(VerticalLayout::default()));
+        scroll.id = self.id;
+        scroll.intrinsic_size = self.style.intrinsic_size;
+        scroll.direction = self.style.direction;
+        scroll.corner_radius = layout.corner_radius;
+
+        layout.id = GlobalId::new();
+        layout.overflow = Overflow::Visible;
+        layout.intrinsic_size.height = BoxSizing::Shrink;
+        if layout.intrinsic_size.width != BoxSizing::Shrink {
+            layout.intrinsic_size.width = BoxSizing::Flex(1);
+        }
+        scroll.child = Box::new(layout);
+        scroll.scroll_to(Position::new(0.0, self.scroll_offset));
+        Box::new(scroll)
+    }
+
+    fn render(&self, renderer: &mut Renderer, layout_tree: &LayoutTree) {
+        let layout = layout_tree.get(self.id).unwrap();
+        render_styled(renderer, &self.style, layout_tree, layout, |renderer| {
+            let size = layout.size();
+            let position = layout.position();
+            let mut rect = Rect::new()
+                .size(size.width, size.height)
+                .position(position.x, position.y)
+                .corner_radius(self.style.corner_radius)
+                .fill(self.style.background.clone());
+
+            rect.border = self.style.border.clone();
+            rect.shadows = self.style.shadows.clone();
+
+            renderer.draw_rect(rect);
+            // TODO: test this
+            render_children(renderer, layout, |renderer| {
+                for child in &self.children {
+                    child.render(renderer, layout_tree);
+                }
+            });
+        });
     }
 
-    fn render(&self, renderer: &mut Renderer, layout: &dyn Layout) {
-        let layout = layout.get(self.id).unwrap();
-        let size = layout.size();
-        let position = layout.position();
-        let mut rect = Rect::new()
-            .size(size.width, size.height)
-            .position(position.x, position.y)
-            .corner_radius(self.style.corner_radius);
-
-        rect.border = self.style.border.clone();
-
-        renderer.draw_rect(rect);
-        // TODO: test this
-        self.children
-            .iter()
-            .for_each(|child| child.render(renderer, layout));
+    fn transform(&self, layout: &LayoutNode) -> Affine {
+        self.style.resolve_transform(layout.bounds())
     }
 }
 
@@ -1,5 +1,8 @@
-use agape::widgets::Image;
+use agape::renderer::Renderer;
+use agape::vstack;
+use agape::widgets::{Image, VStack, Widget};
 use agape_core::Size;
+use agape_layout::solve_layout;
 use image::{ImageBuffer, ImageFormat};
 use std::fs;
 use std::path::PathBuf;
@@ -60,3 +63,17 @@ fn unsupported_image_formats() {
 
     assert!(matches!(result, Err(agape::Error::UnsupportedImageFormat)));
 }
+
+#[test]
+fn fill_width_keeps_aspect_ratio() {
+    let (_temp, path) = save_image(200, 100, ImageFormat::Png);
+    let image = Image::open(path).unwrap().fill_width();
+    let stack: VStack = vstack![vstack![image].fixed(400.0, 800.0)].fill();
+
+    let mut renderer = Renderer::new();
+    let mut layout = stack.layout(&mut renderer);
+    solve_layout(layout.as_mut(), Size::new(800.0, 800.0));
+
+    let size = layout.children()[0].children()[0].size();
+    assert_eq!(size, Size::new(400.0, 200.0));
+}
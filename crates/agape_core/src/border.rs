@@ -0,0 +1,287 @@
+use crate::{Color, IntoColor, Rgba, Size};
+
+/// The radius of each corner of a box, going clockwise from the top left.
+///
+/// # Example
+/// ```
+/// use agape_core::CornerRadius;
+///
+/// let radius = CornerRadius::all(12.0);
+/// assert_eq!(radius.bottom_left, 12.0);
+///
+/// // Only round the top corners
+/// let radius = CornerRadius::top(8.0);
+/// assert_eq!(radius.bottom_right, 0.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct CornerRadius {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32,
+}
+
+impl CornerRadius {
+    pub fn new(top_left: f32, top_right: f32, bottom_right: f32, bottom_left: f32) -> Self {
+        Self {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        }
+    }
+
+    /// Create a [`CornerRadius`] with the same radius for every corner.
+    pub fn all(radius: f32) -> Self {
+        Self::new(radius, radius, radius, radius)
+    }
+
+    /// Create a [`CornerRadius`] that only rounds the top corners.
+    pub fn top(radius: f32) -> Self {
+        Self::new(radius, radius, 0.0, 0.0)
+    }
+
+    /// Create a [`CornerRadius`] that only rounds the bottom corners.
+    pub fn bottom(radius: f32) -> Self {
+        Self::new(0.0, 0.0, radius, radius)
+    }
+
+    /// Get the corners in clockwise order, starting from the top left.
+    pub fn corners(&self) -> [f32; 4] {
+        [
+            self.top_left,
+            self.top_right,
+            self.bottom_right,
+            self.bottom_left,
+        ]
+    }
+
+    /// Get the largest radius.
+    pub fn largest(&self) -> f32 {
+        self.corners().into_iter().fold(0.0, f32::max)
+    }
+
+    /// Returns `true` if none of the corners are rounded.
+    pub fn is_zero(&self) -> bool {
+        self.largest() <= 0.0
+    }
+
+    /// Limit each radius to half the width and height of a box with the `size`,
+    /// negative radii become `0`.
+    ///
+    /// # Example
+    /// ```
+    /// use agape_core::{CornerRadius, Size};
+    ///
+    /// let radius = CornerRadius::new(100.0, 10.0, -5.0, 0.0).clamp(Size::new(200.0, 50.0));
+    /// assert_eq!(radius, CornerRadius::new(25.0, 10.0, 0.0, 0.0));
+    /// ```
+    pub fn clamp(&self, size: Size) -> Self {
+        let max = (size.width / 2.0).min(size.height / 2.0).max(0.0);
+        let [top_left, top_right, bottom_right, bottom_left] =
+            self.corners().map(|radius| radius.clamp(0.0, max));
+        Self::new(top_left, top_right, bottom_right, bottom_left)
+    }
+
+    /// Grow every rounded corner by `amount`, or shrink them if it's negative.
+    /// Radii never go below `0`.
+    pub fn expand(&self, amount: f32) -> Self {
+        let [top_left, top_right, bottom_right, bottom_left] =
+            self.corners().map(|radius| (radius + amount).max(0.0));
+        Self::new(top_left, top_right, bottom_right, bottom_left)
+    }
+}
+
+impl From<f32> for CornerRadius {
+    fn from(radius: f32) -> Self {
+        Self::all(radius)
+    }
+}
+
+impl From<i32> for CornerRadius {
+    fn from(radius: i32) -> Self {
+        Self::all(radius as f32)
+    }
+}
+
+impl From<u32> for CornerRadius {
+    fn from(radius: u32) -> Self {
+        Self::all(radius as f32)
+    }
+}
+
+/// How the lines of a [`Border`] are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash, Default)]
+pub enum BorderStyle {
+    #[default]
+    Solid,
+    /// Dashes that are three times as long as the border is wide.
+    Dashed,
+    /// Round dots as wide as the border.
+    Dotted,
+}
+
+/// The width and [`Color`] of one side of a [`Border`].
+#[derive(Debug, Clone, PartialEq, PartialOrd, Default)]
+pub struct BorderSide {
+    pub width: f32,
+    pub color: Color<Rgba>,
+}
+
+impl BorderSide {
+    pub fn new(width: f32, color: impl IntoColor<Rgba>) -> Self {
+        Self {
+            width,
+            color: color.into_color(),
+        }
+    }
+}
+
+/// Border style for [`View`]s, each side can have a different width and color.
+///
+/// The border is drawn centered on the outline of the box, following the
+/// rounded corners.
+///
+/// # Example
+/// ```
+/// use agape_core::{Border, BorderSide, BorderStyle, Color};
+///
+/// let border = Border::all(1.0, Color::BLACK)
+///     .bottom(BorderSide::new(4.0, Color::RED))
+///     .style(BorderStyle::Dashed);
+///
+/// assert_eq!(border.top.width, 1.0);
+/// assert!(!border.is_uniform());
+/// ```
+#[derive(Clone, PartialEq, Debug, Default, PartialOrd)]
+pub struct Border {
+    pub top: BorderSide,
+    pub right: BorderSide,
+    pub bottom: BorderSide,
+    pub left: BorderSide,
+    pub style: BorderStyle,
+}
+
+impl Border {
+    /// Create a new border.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a solid border with the same width and color on every side.
+    pub fn all(width: f32, color: impl IntoColor<Rgba>) -> Self {
+        let side = BorderSide::new(width, color);
+        Self {
+            top: side.clone(),
+            right: side.clone(),
+            bottom: side.clone(),
+            left: side,
+            style: BorderStyle::Solid,
+        }
+    }
+
+    /// Set the width of every side.
+    pub fn width(mut self, width: f32) -> Self {
+        for side in self.sides_mut() {
+            side.width = width;
+        }
+        self
+    }
+
+    /// Set the color of every side.
+    pub fn color(mut self, color: impl IntoColor<Rgba>) -> Self {
+        let color = color.into_color();
+        for side in self.sides_mut() {
+            side.color = color.clone();
+        }
+        self
+    }
+
+    pub fn style(mut self, style: BorderStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn top(mut self, side: BorderSide) -> Self {
+        self.top = side;
+        self
+    }
+
+    pub fn right(mut self, side: BorderSide) -> Self {
+        self.right = side;
+        self
+    }
+
+    pub fn bottom(mut self, side: BorderSide) -> Self {
+        self.bottom = side;
+        self
+    }
+
+    pub fn left(mut self, side: BorderSide) -> Self {
+        self.left = side;
+        self
+    }
+
+    /// Get the sides in clockwise order, starting from the top.
+    pub fn sides(&self) -> [&BorderSide; 4] {
+        [&self.top, &self.right, &self.bottom, &self.left]
+    }
+
+    /// Get mutable references to the sides in clockwise order, starting from the top.
+    pub fn sides_mut(&mut self) -> [&mut BorderSide; 4] {
+        [
+            &mut self.top,
+            &mut self.right,
+            &mut self.bottom,
+            &mut self.left,
+        ]
+    }
+
+    /// Returns `true` if every side has the same width and color.
+    pub fn is_uniform(&self) -> bool {
+        self.sides().iter().all(|side| **side == self.top)
+    }
+
+    /// Get the width of the widest side.
+    pub fn max_width(&self) -> f32 {
+        self.sides()
+            .iter()
+            .map(|side| side.width)
+            .fold(0.0, f32::max)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn border_builders_set_every_side() {
+        let border = Border::new().width(2.0).color(Color::RED);
+        assert!(border.is_uniform());
+        assert_eq!(border.left, BorderSide::new(2.0, Color::RED));
+        assert_eq!(border.max_width(), 2.0);
+
+        let border = border.right(BorderSide::new(6.0, Color::RED));
+        assert!(!border.is_uniform());
+        assert_eq!(border.max_width(), 6.0);
+    }
+
+    #[test]
+    fn expand_corner_radius() {
+        let radius = CornerRadius::new(10.0, 0.0, 4.0, 2.0);
+        assert_eq!(radius.expand(2.0), CornerRadius::new(12.0, 2.0, 6.0, 4.0));
+        assert_eq!(radius.expand(-4.0), CornerRadius::new(6.0, 0.0, 0.0, 0.0));
+        assert_eq!(radius.largest(), 10.0);
+    }
+
+    #[test]
+    fn integers_into_corner_radius() {
+        fn radius(radius: impl Into<CornerRadius>) -> CornerRadius {
+            radius.into()
+        }
+        assert_eq!(radius(12), CornerRadius::all(12.0));
+        assert_eq!(radius(12u32), CornerRadius::all(12.0));
+        assert_eq!(radius(1.5), CornerRadius::all(1.5));
+    }
+}
@@ -1,9 +1,12 @@
 //! Core types
+mod border;
 mod color;
+mod paint;
 mod position;
 mod size;
+mod transform;
 
-pub use {color::*, position::*, size::*};
+pub use {border::*, color::*, paint::*, position::*, size::*, transform::*};
 
 /// A global unique identifier
 #[derive(Copy, Clone, PartialOrd, PartialEq, Eq, Debug, Ord, Hash)]
@@ -28,18 +31,19 @@ impl std::fmt::Display for GlobalId {
     }
 }
 
-/// Border style for [`View`]s;
-#[derive(Clone, PartialEq, Debug, Default, PartialOrd)]
-pub struct Border {
-    pub width: f32,
-    pub color: Color<Rgba>,
-}
-
-impl Border {
-    /// Create a new border.
-    pub fn new() -> Self {
-        Self::default()
-    }
+/// Get the name of the type `T` without its module path or generics, used
+/// to name types in diagnostics.
+///
+/// # Example
+/// ```
+/// use agape_core::short_type_name;
+///
+/// assert_eq!(short_type_name::<Vec<String>>(), "Vec");
+/// ```
+pub fn short_type_name<T: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let path = name.split('<').next().unwrap_or(name);
+    path.rsplit("::").next().unwrap_or(path)
 }
 
 /// Map value from one range to another. Any overflow or underflow is clipped to the min or max
@@ -0,0 +1,234 @@
+use crate::{Color, IntoColor, Position, Rgba};
+
+/// What a shape is filled with, either a solid [`Color`] or a [`Gradient`].
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum Fill {
+    Color(Color<Rgba>),
+    Gradient(Gradient),
+}
+
+impl Default for Fill {
+    fn default() -> Self {
+        Self::Color(Color::default())
+    }
+}
+
+impl From<Color<Rgba>> for Fill {
+    fn from(color: Color<Rgba>) -> Self {
+        Self::Color(color)
+    }
+}
+
+impl From<Gradient> for Fill {
+    fn from(gradient: Gradient) -> Self {
+        Self::Gradient(gradient)
+    }
+}
+
+/// How a [`Gradient`] is drawn past its end, such as outside the radius of a
+/// radial gradient. Linear gradients always reach the corners.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub enum SpreadMode {
+    /// Use the color of the closest stop.
+    #[default]
+    Pad,
+    /// Repeat the gradient.
+    Repeat,
+    /// Repeat the gradient, mirroring every other repetition.
+    Reflect,
+}
+
+/// A [`Color`] at an `offset` along a [`Gradient`], from `0.0` at the
+/// start to `1.0` at the end.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct ColorStop {
+    pub offset: f32,
+    pub color: Color<Rgba>,
+}
+
+impl ColorStop {
+    pub fn new(offset: f32, color: impl IntoColor<Rgba>) -> Self {
+        Self {
+            offset,
+            color: color.into_color(),
+        }
+    }
+}
+
+/// The shape of a [`Gradient`].
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum GradientKind {
+    /// A gradient along a line through the center at an `angle` in degrees.
+    /// Like css, `0` goes from the bottom to the top and `90` goes from the
+    /// left to the right.
+    Linear { angle: f32 },
+    /// A gradient that spreads out in a circle from the `center`, which is
+    /// relative to the size of the shape, so `(0.5, 0.5)` is the middle. The
+    /// `radius` is relative to the distance from the center to the farthest
+    /// corner.
+    Radial { center: Position, radius: f32 },
+}
+
+/// A smooth transition between colors, used as a [`Fill`].
+///
+/// # Example
+/// ```
+/// use agape_core::{Color, Gradient, SpreadMode};
+///
+/// let gradient = Gradient::linear(90.0)
+///     .stop(0.0, Color::RED)
+///     .stop(1.0, Color::BLUE)
+///     .spread(SpreadMode::Reflect);
+///
+/// assert_eq!(gradient.stops.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<ColorStop>,
+    pub spread: SpreadMode,
+}
+
+impl Gradient {
+    /// Create a linear gradient at an `angle` in degrees, see
+    /// [`GradientKind::Linear`].
+    pub fn linear(angle: f32) -> Self {
+        Self {
+            kind: GradientKind::Linear { angle },
+            stops: Vec::new(),
+            spread: SpreadMode::default(),
+        }
+    }
+
+    /// Create a radial gradient from the middle that reaches the corners.
+    /// Use [`Gradient::center`] and [`Gradient::radius`] to change it.
+    pub fn radial() -> Self {
+        Self {
+            kind: GradientKind::Radial {
+                center: Position::unit(0.5),
+                radius: 1.0,
+            },
+            stops: Vec::new(),
+            spread: SpreadMode::default(),
+        }
+    }
+
+    /// Add a [`ColorStop`].
+    pub fn stop(mut self, offset: f32, color: impl IntoColor<Rgba>) -> Self {
+        self.stops.push(ColorStop::new(offset, color));
+        self
+    }
+
+    /// Set the [`SpreadMode`].
+    pub fn spread(mut self, spread: SpreadMode) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    /// Set the center of a radial gradient, does nothing for linear gradients.
+    pub fn center(mut self, x: f32, y: f32) -> Self {
+        if let GradientKind::Radial { center, .. } = &mut self.kind {
+            *center = Position::new(x, y);
+        }
+        self
+    }
+
+    /// Set the radius of a radial gradient, does nothing for linear gradients.
+    pub fn radius(mut self, value: f32) -> Self {
+        if let GradientKind::Radial { radius, .. } = &mut self.kind {
+            *radius = value;
+        }
+        self
+    }
+}
+
+/// A shadow behind or inside a box, like a css `box-shadow`.
+///
+/// # Example
+/// ```
+/// use agape_core::{BoxShadow, Color};
+///
+/// let shadow = BoxShadow::new()
+///     .offset(0.0, 4.0)
+///     .blur(12.0)
+///     .color(Color::rgba(0, 0, 0, 25));
+/// ```
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct BoxShadow {
+    /// How far the shadow is moved from the box.
+    pub offset: Position,
+    /// The blur radius, the shadow fades out over this distance.
+    pub blur: f32,
+    /// How much bigger the shadow is than the box, or how much smaller the
+    /// hole is for inset shadows.
+    pub spread: f32,
+    pub color: Color<Rgba>,
+    /// Draw the shadow inside the box instead of behind it.
+    pub inset: bool,
+}
+
+impl Default for BoxShadow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BoxShadow {
+    /// Create a [`BoxShadow`] with no offset or blur, that is black at 25% opacity.
+    pub fn new() -> Self {
+        Self {
+            offset: Position::default(),
+            blur: 0.0,
+            spread: 0.0,
+            color: Color::rgba(0, 0, 0, 25),
+            inset: false,
+        }
+    }
+
+    pub fn offset(mut self, x: f32, y: f32) -> Self {
+        self.offset = Position::new(x, y);
+        self
+    }
+
+    pub fn blur(mut self, blur: f32) -> Self {
+        self.blur = blur;
+        self
+    }
+
+    pub fn spread(mut self, spread: f32) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    pub fn color(mut self, color: impl IntoColor<Rgba>) -> Self {
+        self.color = color.into_color();
+        self
+    }
+
+    /// Draw the shadow inside the box.
+    pub fn inset(mut self) -> Self {
+        self.inset = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn radial_builder() {
+        let gradient = Gradient::radial().center(0.0, 1.0).radius(0.5);
+        assert_eq!(
+            gradient.kind,
+            GradientKind::Radial {
+                center: Position::new(0.0, 1.0),
+                radius: 0.5
+            }
+        );
+
+        // Linear gradients have no center
+        let gradient = Gradient::linear(45.0).center(0.0, 1.0);
+        assert_eq!(gradient.kind, GradientKind::Linear { angle: 45.0 });
+    }
+}
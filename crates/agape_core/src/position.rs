@@ -1,3 +1,4 @@
+use crate::CornerRadius;
 use crate::size::Size;
 use bytemuck::{Pod, Zeroable};
 use std::ops::{AddAssign, SubAssign};
@@ -166,6 +167,97 @@ impl Bounds {
 
         false
     }
+
+    /// Check if a [`Position`] is within the [`Bounds`] with rounded corners
+    /// of `radius`, each corner can have its own radius.
+    ///
+    /// # Example
+    /// ```
+    /// use agape_core::{CornerRadius,Position,Bounds,Size};
+    ///
+    /// let bounds = Bounds::new(Position::default(),Size::unit(100.0));
+    ///
+    /// assert!(bounds.within(&Position::unit(2.0)));
+    /// assert!(!bounds.within_rounded(&Position::unit(2.0),20.0));
+    /// assert!(bounds.within_rounded(&Position::new(50.0,2.0),20.0));
+    /// assert!(bounds.within_rounded(&Position::unit(98.0),CornerRadius::top(20.0)));
+    /// ```
+    pub fn within_rounded(&self, position: &Position, radius: impl Into<CornerRadius>) -> bool {
+        if !self.within(position) {
+            return false;
+        }
+
+        let width = self.x[1] - self.x[0];
+        let height = self.y[1] - self.y[0];
+        let radius = radius.into().clamp(Size::new(width, height));
+        let left = position.x < self.x[0] + width / 2.0;
+        let top = position.y < self.y[0] + height / 2.0;
+        let radius = match (left, top) {
+            (true, true) => radius.top_left,
+            (false, true) => radius.top_right,
+            (false, false) => radius.bottom_right,
+            (true, false) => radius.bottom_left,
+        };
+        if radius <= 0.0 {
+            return true;
+        }
+
+        // The closest point on the rect that's inset by the radius
+        let x = position.x.clamp(self.x[0] + radius, self.x[1] - radius);
+        let y = position.y.clamp(self.y[0] + radius, self.y[1] - radius);
+        let (dx, dy) = (position.x - x, position.y - y);
+        dx.mul_add(dx, dy * dy) <= radius * radius
+    }
+
+    /// Returns `true` if the bounds have no area.
+    pub fn is_empty(&self) -> bool {
+        self.x[1] <= self.x[0] || self.y[1] <= self.y[0]
+    }
+
+    /// Check if the [`Bounds`] overlap, bounds that only touch don't overlap.
+    ///
+    /// # Example
+    /// ```
+    /// use agape_core::{Position,Bounds,Size};
+    ///
+    /// let bounds = Bounds::new(Position::default(),Size::unit(100.0));
+    ///
+    /// assert!(bounds.intersects(&Bounds::new(Position::unit(50.0),Size::unit(100.0))));
+    /// assert!(!bounds.intersects(&Bounds::new(Position::new(100.0,0.0),Size::unit(100.0))));
+    /// ```
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.x[0] < other.x[1]
+            && other.x[0] < self.x[1]
+            && self.y[0] < other.y[1]
+            && other.y[0] < self.y[1]
+    }
+
+    /// Get the area covered by both bounds, which is empty if they
+    /// don't overlap.
+    pub fn intersection(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            x: [self.x[0].max(other.x[0]), self.x[1].min(other.x[1])],
+            y: [self.y[0].max(other.y[0]), self.y[1].min(other.y[1])],
+        }
+    }
+
+    /// Get the smallest [`Bounds`] that contains both bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use agape_core::{Position,Bounds,Size};
+    ///
+    /// let a = Bounds::new(Position::default(),Size::unit(20.0));
+    /// let b = Bounds::new(Position::new(50.0,10.0),Size::unit(20.0));
+    ///
+    /// assert_eq!(a.union(&b),Bounds::new(Position::default(),Size::new(70.0,30.0)));
+    /// ```
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            x: [self.x[0].min(other.x[0]), self.x[1].max(other.x[1])],
+            y: [self.y[0].min(other.y[0]), self.y[1].max(other.y[1])],
+        }
+    }
 }
 
 #[cfg(test)]
@@ -180,6 +272,41 @@ mod test {
         assert_eq!(pos, Position::new(6.0, 7.0));
     }
 
+    #[test]
+    fn within_rounded_corners() {
+        let bounds = Bounds::new(Position::unit(10.0), Size::new(100.0, 50.0));
+        assert!(bounds.within_rounded(&Position::unit(20.0), 10.0));
+        assert!(!bounds.within_rounded(&Position::unit(12.0), 10.0));
+        assert!(!bounds.within_rounded(&Position::new(108.0, 58.0), 10.0));
+        // The radius is limited to half the height
+        assert!(!bounds.within_rounded(&Position::new(12.0, 15.0), 100.0));
+        assert!(bounds.within_rounded(&Position::new(60.0, 35.0), 100.0));
+    }
+
+    #[test]
+    fn within_asymmetric_corners() {
+        let bounds = Bounds::new(Position::default(), Size::unit(100.0));
+        let radius = CornerRadius::new(20.0, 0.0, 40.0, 0.0);
+        assert!(!bounds.within_rounded(&Position::unit(3.0), radius));
+        assert!(bounds.within_rounded(&Position::new(97.0, 3.0), radius));
+        assert!(!bounds.within_rounded(&Position::unit(90.0), radius));
+        assert!(bounds.within_rounded(&Position::new(3.0, 97.0), radius));
+    }
+
+    #[test]
+    fn intersection() {
+        let a = Bounds::new(Position::default(), Size::unit(100.0));
+        let b = Bounds::new(Position::new(50.0, 80.0), Size::unit(100.0));
+        assert_eq!(
+            a.intersection(&b),
+            Bounds::new(Position::new(50.0, 80.0), Size::new(50.0, 20.0))
+        );
+
+        let c = Bounds::new(Position::unit(200.0), Size::unit(10.0));
+        assert!(a.intersection(&c).is_empty());
+        assert!(!a.intersects(&c));
+    }
+
     #[test]
     fn sub_assign() {
         let mut pos = Position::new(0.0, 15.0);
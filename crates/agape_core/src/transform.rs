@@ -0,0 +1,216 @@
+use crate::{Bounds, Position};
+
+/// A 2D affine transform, which can translate, rotate, scale and skew.
+///
+/// A position is mapped to `(sx * x + kx * y + tx, ky * x + sy * y + ty)`.
+///
+/// # Example
+/// ```
+/// use agape_core::{Affine, Position};
+///
+/// let transform = Affine::scale(2.0, 2.0).then(Affine::translate(10.0, 0.0));
+/// assert_eq!(transform.apply(Position::new(5.0, 5.0)), Position::new(20.0, 10.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Affine {
+    pub sx: f32,
+    pub ky: f32,
+    pub kx: f32,
+    pub sy: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Affine {
+    /// Create a transform that doesn't change anything.
+    pub fn identity() -> Self {
+        Self {
+            sx: 1.0,
+            ky: 0.0,
+            kx: 0.0,
+            sy: 1.0,
+            tx: 0.0,
+            ty: 0.0,
+        }
+    }
+
+    pub fn translate(x: f32, y: f32) -> Self {
+        Self {
+            tx: x,
+            ty: y,
+            ..Self::identity()
+        }
+    }
+
+    pub fn scale(x: f32, y: f32) -> Self {
+        Self {
+            sx: x,
+            sy: y,
+            ..Self::identity()
+        }
+    }
+
+    /// Create a rotation by an `angle` in degrees, positive angles rotate
+    /// clockwise on the screen.
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        Self {
+            sx: cos,
+            ky: sin,
+            kx: -sin,
+            sy: cos,
+            ..Self::identity()
+        }
+    }
+
+    /// Create a skew along the x and y axes, the angles are in degrees.
+    pub fn skew(x: f32, y: f32) -> Self {
+        Self {
+            kx: x.to_radians().tan(),
+            ky: y.to_radians().tan(),
+            ..Self::identity()
+        }
+    }
+
+    /// Get the transform that applies this transform and then `other`.
+    pub fn then(&self, other: Affine) -> Self {
+        let s = self;
+        let o = other;
+        Self {
+            sx: o.sx.mul_add(s.sx, o.kx * s.ky),
+            ky: o.ky.mul_add(s.sx, o.sy * s.ky),
+            kx: o.sx.mul_add(s.kx, o.kx * s.sy),
+            sy: o.ky.mul_add(s.kx, o.sy * s.sy),
+            tx: o.sx.mul_add(s.tx, o.kx.mul_add(s.ty, o.tx)),
+            ty: o.ky.mul_add(s.tx, o.sy.mul_add(s.ty, o.ty)),
+        }
+    }
+
+    /// Get the transform applied around the `origin` instead of `(0, 0)`, so
+    /// a rotation spins around the origin.
+    ///
+    /// # Example
+    /// ```
+    /// use agape_core::{Affine, Position};
+    ///
+    /// let origin = Position::new(50.0, 50.0);
+    /// let transform = Affine::scale(2.0, 2.0).around(origin);
+    /// assert_eq!(transform.apply(origin), origin);
+    /// ```
+    pub fn around(&self, origin: Position) -> Self {
+        Self::translate(-origin.x, -origin.y)
+            .then(*self)
+            .then(Self::translate(origin.x, origin.y))
+    }
+
+    /// Map a `position` with the transform.
+    pub fn apply(&self, position: Position) -> Position {
+        let Position { x, y } = position;
+        Position::new(
+            self.sx.mul_add(x, self.kx.mul_add(y, self.tx)),
+            self.ky.mul_add(x, self.sy.mul_add(y, self.ty)),
+        )
+    }
+
+    /// Get the transform that undoes this one, `None` if it can't be undone
+    /// such as a scale of `0`.
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.sx.mul_add(self.sy, -self.kx * self.ky);
+        if !det.is_finite() || det.abs() <= f32::EPSILON {
+            return None;
+        }
+        Some(Self {
+            sx: self.sy / det,
+            ky: -self.ky / det,
+            kx: -self.kx / det,
+            sy: self.sx / det,
+            tx: self.kx.mul_add(self.ty, -self.sy * self.tx) / det,
+            ty: self.ky.mul_add(self.tx, -self.sx * self.ty) / det,
+        })
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    /// Get the smallest [`Bounds`] that contain the transformed `bounds`.
+    pub fn map_bounds(&self, bounds: &Bounds) -> Bounds {
+        if self.is_identity() {
+            return *bounds;
+        }
+        let corners = [
+            Position::new(bounds.x[0], bounds.y[0]),
+            Position::new(bounds.x[1], bounds.y[0]),
+            Position::new(bounds.x[1], bounds.y[1]),
+            Position::new(bounds.x[0], bounds.y[1]),
+        ]
+        .map(|corner| self.apply(corner));
+
+        let mut mapped = Bounds {
+            x: [corners[0].x; 2],
+            y: [corners[0].y; 2],
+        };
+        for corner in &corners[1..] {
+            mapped.x = [mapped.x[0].min(corner.x), mapped.x[1].max(corner.x)];
+            mapped.y = [mapped.y[0].min(corner.y), mapped.y[1].max(corner.y)];
+        }
+        mapped
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Size;
+
+    fn assert_near(a: Position, b: Position) {
+        assert!(
+            (a.x - b.x).abs() < 1e-4 && (a.y - b.y).abs() < 1e-4,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn rotate_clockwise() {
+        let transform = Affine::rotate(90.0);
+        assert_near(
+            transform.apply(Position::new(10.0, 0.0)),
+            Position::new(0.0, 10.0),
+        );
+    }
+
+    #[test]
+    fn invert_undoes_the_transform() {
+        let transform = Affine::rotate(30.0)
+            .then(Affine::skew(10.0, 0.0))
+            .then(Affine::scale(2.0, 0.5))
+            .then(Affine::translate(40.0, -3.0));
+        let inverse = transform.invert().unwrap();
+        let position = Position::new(12.0, 7.0);
+        assert_near(inverse.apply(transform.apply(position)), position);
+        assert_near(
+            transform.then(inverse).apply(position),
+            Affine::identity().apply(position),
+        );
+    }
+
+    #[test]
+    fn flat_transforms_cant_be_inverted() {
+        assert!(Affine::scale(0.0, 1.0).invert().is_none());
+    }
+
+    #[test]
+    fn map_rotated_bounds() {
+        let bounds = Bounds::new(Position::default(), Size::new(20.0, 10.0));
+        let mapped = Affine::rotate(90.0).map_bounds(&bounds);
+        assert!((mapped.x[0] + 10.0).abs() < 1e-4);
+        assert!(mapped.x[1].abs() < 1e-4);
+        assert!((mapped.y[1] - 20.0).abs() < 1e-4);
+    }
+}
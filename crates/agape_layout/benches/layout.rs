@@ -0,0 +1,71 @@
+use agape_layout::{
+    BoxSizing, EmptyLayout, HorizontalLayout, IntrinsicSize, Layout, Size, VerticalLayout,
+    solve_layout,
+};
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+/// A list of rows, each with a fixed and a flex child.
+fn rows(count: usize) -> VerticalLayout {
+    let mut root = VerticalLayout::new();
+    root.intrinsic_size.width = BoxSizing::Flex(1);
+    root.spacing = 10;
+    for _ in 0..count {
+        let mut row = HorizontalLayout::new();
+        row.intrinsic_size.width = BoxSizing::Flex(1);
+        let mut icon = EmptyLayout::new();
+        icon.intrinsic_size = IntrinsicSize::fixed(20.0, 20.0);
+        let mut label = EmptyLayout::new();
+        label.intrinsic_size.width = BoxSizing::Flex(1);
+        label.intrinsic_size.height = BoxSizing::Fixed(20.0);
+        row.add_child(icon);
+        row.add_child(label);
+        root.add_child(row);
+    }
+    root
+}
+
+fn layout(c: &mut Criterion) {
+    let window = Size::new(1000.0, 1000.0);
+    let mut group = c.benchmark_group("layout");
+
+    group.bench_function("full solve 500 rows", |b| {
+        b.iter_batched_ref(
+            || rows(500),
+            |root| solve_layout(black_box(root), window),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+
+    let mut root = rows(500);
+    solve_layout(&mut root, window);
+    group.bench_function("cached solve 500 rows", |b| {
+        b.iter(|| solve_layout(black_box(&mut root), window))
+    });
+
+    let leaf = root.children[250].children()[1].id();
+    group.bench_function("dirty leaf 500 rows", |b| {
+        b.iter(|| {
+            root.mark_dirty(leaf);
+            solve_layout(black_box(&mut root), window)
+        })
+    });
+
+    // The previous tree is the solved tree from the last benchmark
+    let previous = root;
+    group.bench_function("rebuilt solve 500 rows", |b| {
+        b.iter_batched_ref(
+            || rows(500),
+            |root| {
+                root.reuse(&previous);
+                solve_layout(black_box(root), window)
+            },
+            criterion::BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, layout);
+criterion_main!(benches);
@@ -1,8 +1,9 @@
 use crate::{
-    AxisAlignment, BoxConstraints, BoxSizing, EmptyLayout, IntrinsicSize, Layout, LayoutError,
-    LayoutIter, Padding,
+    AxisAlignment, BoxConstraints, BoxSizing, Direction, EmptyLayout, IntrinsicSize, Layout,
+    LayoutCache, LayoutError, LayoutIter, Overflow, Padding,
 };
-use agape_core::{GlobalId, Position, Size};
+use agape_core::{CornerRadius, GlobalId, Position, Size};
+use std::any::Any;
 
 // TODO make these private and add builder
 /// A [`Layout`] that only has one child.
@@ -19,6 +20,16 @@ pub struct BlockLayout {
     /// The cross axis is the `y-axis`
     pub cross_axis_alignment: AxisAlignment,
     pub child: Box<dyn Layout>,
+    pub cache: LayoutCache,
+    /// The [`Direction`] of the layout, `None` to use the parent's direction.
+    pub direction: Option<Direction>,
+    /// The [`Direction`] of the parent, set when the layout is solved.
+    pub inherited_direction: Direction,
+    /// How children that don't fit in the layout are shown.
+    pub overflow: Overflow,
+    /// The radius of the corners, the children are clipped to the rounded
+    /// corners when the overflow is clipped.
+    pub corner_radius: CornerRadius,
     errors: Vec<LayoutError>,
 }
 
@@ -34,6 +45,11 @@ impl Default for BlockLayout {
             main_axis_alignment: AxisAlignment::default(),
             cross_axis_alignment: AxisAlignment::default(),
             errors: vec![],
+            cache: LayoutCache::new(),
+            direction: None,
+            inherited_direction: Direction::default(),
+            overflow: Overflow::default(),
+            corner_radius: CornerRadius::default(),
             child: Box::new(EmptyLayout::default()),
         }
     }
@@ -56,13 +72,16 @@ impl BlockLayout {
     /// Align the children on the main axis in the center
     fn align_main_axis_center(&mut self) {
         // TODO handle overflow
-        let center_start = self.position.x + (self.size.width - self.child.size().width) / 2.0;
+        let inner_width = self.size.width - self.padding.horizontal_sum();
+        let center_start =
+            self.position.x + self.padding.left + (inner_width - self.child.size().width) / 2.0;
         self.child.set_x(center_start);
     }
 
     fn align_main_axis_end(&mut self) {
         let mut x_pos = self.position.x + self.size.width;
         x_pos -= self.padding.right;
+        x_pos -= self.child.size().width;
 
         self.child.set_x(x_pos);
     }
@@ -74,13 +93,16 @@ impl BlockLayout {
 
     fn align_cross_axis_center(&mut self) {
         // TODO handle overflow
-        let y_pos = (self.size.height - self.child.size().height) / 2.0 + self.position.y;
+        let inner_height = self.size.height - self.padding.vertical_sum();
+        let y_pos =
+            self.position.y + self.padding.top + (inner_height - self.child.size().height) / 2.0;
         self.child.set_y(y_pos);
     }
 
     fn align_cross_axis_end(&mut self) {
-        self.child
-            .set_y(self.position.y + self.size.height - self.padding.bottom);
+        let y_pos =
+            self.position.y + self.size.height - self.padding.bottom - self.child.size().height;
+        self.child.set_y(y_pos);
     }
 }
 
@@ -139,7 +161,8 @@ impl Layout for BlockLayout {
 
     fn collect_errors(&mut self) -> Vec<LayoutError> {
         self.errors
-            .drain(..)
+            .iter()
+            .cloned()
             .chain(self.child.collect_errors())
             .collect::<Vec<_>>()
     }
@@ -148,7 +171,83 @@ impl Layout for BlockLayout {
         LayoutIter { stack: vec![self] }
     }
 
+    fn mark_dirty(&mut self, id: GlobalId) -> bool {
+        let found = self.id == id || self.child.mark_dirty(id);
+        if found {
+            self.cache.invalidate();
+        }
+        found
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.cache.is_dirty()
+    }
+
+    fn baseline(&self) -> Option<f32> {
+        let baseline = self.child.baseline()?;
+        match self.cross_axis_alignment {
+            AxisAlignment::Start
+            | AxisAlignment::SpaceBetween
+            | AxisAlignment::Stretch
+            | AxisAlignment::Baseline => Some(self.padding.top + baseline),
+            AxisAlignment::Center | AxisAlignment::SpaceAround | AxisAlignment::SpaceEvenly => {
+                Some((self.size.height - self.child.size().height) / 2.0 + baseline)
+            }
+            AxisAlignment::End => None,
+        }
+    }
+
+    fn overflow_mode(&self) -> Overflow {
+        self.overflow
+    }
+
+    fn corner_radius(&self) -> CornerRadius {
+        self.corner_radius
+    }
+
+    fn direction(&self) -> Direction {
+        self.direction.unwrap_or(self.inherited_direction)
+    }
+
+    fn inherit_direction(&mut self, direction: Direction) {
+        if self.direction.is_none() && self.inherited_direction != direction {
+            self.inherited_direction = direction;
+            self.cache.invalidate();
+        }
+    }
+
+    fn reuse(&mut self, previous: &dyn Layout) -> bool {
+        let previous: &dyn Any = previous;
+        let Some(previous) = previous.downcast_ref::<Self>() else {
+            return false;
+        };
+
+        let reused = self.child.reuse(previous.child.as_ref());
+        let unchanged = self.padding == previous.padding
+            && self.intrinsic_size == previous.intrinsic_size
+            && self.main_axis_alignment == previous.main_axis_alignment
+            && self.cross_axis_alignment == previous.cross_axis_alignment
+            && self.direction == previous.direction;
+        if !reused || !unchanged || previous.is_dirty() || !previous.errors.is_empty() {
+            return false;
+        }
+
+        self.size = previous.size;
+        self.position = previous.position;
+        self.constraints = previous.constraints;
+        self.cache = previous.cache;
+        self.inherited_direction = previous.inherited_direction;
+        true
+    }
+
     fn solve_min_constraints(&mut self) -> (f32, f32) {
+        if let Some((min_width, min_height)) = self.cache.min_size() {
+            // Restore the min constraints in case the parent changed them
+            self.constraints.min_width = min_width;
+            self.constraints.min_height = min_height;
+            return (min_width, min_height);
+        }
+
         let (min_width, min_height) = self.child.solve_min_constraints();
 
         // Set our min constraints to child + padding if intrinsic size
@@ -169,10 +268,19 @@ impl Layout for BlockLayout {
             BoxSizing::Fixed(height) => self.constraints.min_height = height,
         }
 
-        (self.constraints.min_width, self.constraints.min_height)
+        let min_size = (self.constraints.min_width, self.constraints.min_height);
+        self.cache.set_min_size(min_size);
+        min_size
     }
 
     fn solve_max_constraints(&mut self, space: Size) {
+        if self.cache.reuse(self.constraints, space) {
+            return;
+        }
+
+        // Restore the child's min constraints which the last solve may have changed
+        self.child.solve_min_constraints();
+
         // FIXME: how?
         let mut available_space = space;
         available_space.width -= self.padding.horizontal_sum();
@@ -203,6 +311,13 @@ impl Layout for BlockLayout {
     }
 
     fn update_size(&mut self) {
+        if self.cache.is_reused() {
+            return;
+        }
+
+        // The errors are found again every time the layout is solved
+        self.errors.clear();
+
         match self.intrinsic_size.width {
             BoxSizing::Flex(_) => {
                 self.size.width = self.constraints.max_width;
@@ -231,26 +346,46 @@ impl Layout for BlockLayout {
     }
 
     fn position_children(&mut self) {
+        if self.cache.reuse_position(self.position) {
+            return;
+        }
+
+        // With a single child, space between places it at the start while
+        // space around and space evenly place it in the center.
         match self.main_axis_alignment {
-            AxisAlignment::Start => self.align_main_axis_start(),
-            AxisAlignment::Center => self.align_main_axis_center(),
+            AxisAlignment::Start
+            | AxisAlignment::SpaceBetween
+            | AxisAlignment::Stretch
+            | AxisAlignment::Baseline => self.align_main_axis_start(),
+            AxisAlignment::Center | AxisAlignment::SpaceAround | AxisAlignment::SpaceEvenly => {
+                self.align_main_axis_center()
+            }
             AxisAlignment::End => self.align_main_axis_end(),
         }
 
         match self.cross_axis_alignment {
-            AxisAlignment::Start => self.align_cross_axis_start(),
-            AxisAlignment::Center => self.align_cross_axis_center(),
+            AxisAlignment::Start
+            | AxisAlignment::SpaceBetween
+            | AxisAlignment::Stretch
+            | AxisAlignment::Baseline => self.align_cross_axis_start(),
+            AxisAlignment::Center | AxisAlignment::SpaceAround | AxisAlignment::SpaceEvenly => {
+                self.align_cross_axis_center()
+            }
             AxisAlignment::End => self.align_cross_axis_end(),
         }
 
-        if self.child.position().x > self.position.x + self.size.width
-            || self.child.position().y > self.position.y + self.size.height
-        {
-            self.errors.push(LayoutError::OutOfBounds {
-                parent_id: self.id,
-                child_id: self.child.id().to_owned(),
-            });
+        let out_of_bounds = self.child.position().x > self.position.x + self.size.width
+            || self.child.position().y > self.position.y + self.size.height;
+        if out_of_bounds && !self.overflow.clips() {
+            let error = LayoutError::out_of_bound(self.id, self.child.id());
+            if !self.errors.contains(&error) {
+                self.errors.push(error);
+            }
         }
+
+        let direction = self.direction();
+        direction.place(self.child.as_mut(), self.position.x, self.size.width);
+        self.child.inherit_direction(direction);
         self.child.position_children();
     }
 }
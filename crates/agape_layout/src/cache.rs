@@ -0,0 +1,119 @@
+use crate::BoxConstraints;
+use agape_core::{Position, Size};
+
+/// The results from the last time a [`Layout`] was solved.
+///
+/// A clean [`Layout`] reuses its cached min constraints, and skips solving its
+/// subtree when it's given the same constraints as last time. Layouts are
+/// dirty until they're solved and become dirty again when they're marked
+/// with [`Layout::mark_dirty`].
+///
+/// [`Layout`]: crate::Layout
+/// [`Layout::mark_dirty`]: crate::Layout::mark_dirty
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LayoutCache {
+    /// The min constraints of the layout, `None` if the layout is dirty.
+    min_size: Option<(f32, f32)>,
+    /// The constraints and space the layout's children were solved with.
+    solved: Option<(BoxConstraints, Size)>,
+    /// The position the children were placed at.
+    positioned: Option<Position>,
+    /// Whether the subtree was reused in this solve.
+    reused: bool,
+}
+
+impl LayoutCache {
+    /// Create a new, dirty, [`LayoutCache`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the layout has to be solved again.
+    pub fn is_dirty(&self) -> bool {
+        self.min_size.is_none()
+    }
+
+    /// Clear the cache so that the layout is solved again.
+    pub fn invalidate(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Get the cached min constraints.
+    pub fn min_size(&self) -> Option<(f32, f32)> {
+        self.min_size
+    }
+
+    pub fn set_min_size(&mut self, min_size: (f32, f32)) {
+        self.min_size = Some(min_size);
+    }
+
+    /// Check if the layout was last solved with the same `constraints` and
+    /// `space`, in which case its subtree doesn't need to be solved again.
+    ///
+    /// This has to be called once every time the max constraints are solved.
+    pub fn reuse(&mut self, constraints: BoxConstraints, space: Size) -> bool {
+        self.reused = self.solved == Some((constraints, space));
+        if !self.reused {
+            self.solved = Some((constraints, space));
+            self.positioned = None;
+        }
+        self.reused
+    }
+
+    /// Returns `true` if the subtree was reused when solving the max constraints,
+    /// so its size doesn't need to be updated.
+    pub fn is_reused(&self) -> bool {
+        self.reused
+    }
+
+    /// Returns `true` if the children were already placed at this position.
+    pub fn reuse_position(&mut self, position: Position) -> bool {
+        let reused = self.reused && self.positioned == Some(position);
+        self.positioned = Some(position);
+        reused
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dirty_by_default() {
+        let cache = LayoutCache::new();
+        assert!(cache.is_dirty());
+    }
+
+    #[test]
+    fn reuse_same_constraints() {
+        let mut cache = LayoutCache::new();
+        cache.set_min_size((10.0, 10.0));
+        let constraints = BoxConstraints::new();
+        assert!(!cache.reuse(constraints, Size::default()));
+        assert!(cache.reuse(constraints, Size::default()));
+        assert!(!cache.reuse(constraints, Size::unit(20.0)));
+    }
+
+    #[test]
+    fn reuse_position() {
+        let mut cache = LayoutCache::new();
+        let constraints = BoxConstraints::new();
+        cache.reuse(constraints, Size::default());
+        assert!(!cache.reuse_position(Position::default()));
+
+        cache.reuse(constraints, Size::default());
+        assert!(cache.reuse_position(Position::default()));
+        assert!(!cache.reuse_position(Position::unit(20.0)));
+    }
+
+    #[test]
+    fn invalidate() {
+        let mut cache = LayoutCache::new();
+        cache.set_min_size((10.0, 10.0));
+        cache.reuse(BoxConstraints::new(), Size::default());
+        cache.invalidate();
+
+        assert!(cache.is_dirty());
+        assert!(!cache.reuse(BoxConstraints::new(), Size::default()));
+    }
+}
@@ -0,0 +1,302 @@
+use crate::{
+    BoxConstraints, BoxSizing, Direction, IntrinsicSize, Layout, LayoutError, LayoutIter, Overflow,
+};
+use agape_core::{CornerRadius, GlobalId, Position, Size};
+use std::fmt::Debug;
+
+/// The state that every [`Layout`] keeps track of, used with [`CustomLayout`].
+#[derive(Debug, Default)]
+pub struct LayoutBase {
+    pub id: GlobalId,
+    pub size: Size,
+    pub position: Position,
+    pub intrinsic_size: IntrinsicSize,
+    pub constraints: BoxConstraints,
+    pub children: Vec<Box<dyn Layout>>,
+    /// The [`Direction`] of the layout, `None` to use the parent's direction.
+    pub direction: Option<Direction>,
+    /// The [`Direction`] of the parent, set when the layout is solved.
+    pub inherited_direction: Direction,
+    /// How children that don't fit in the layout are shown.
+    pub overflow: Overflow,
+    /// The radius of the corners, the children are clipped to the rounded
+    /// corners when the overflow is clipped.
+    pub corner_radius: CornerRadius,
+    /// Errors found while arranging the children, these are cleared every
+    /// time the layout is solved.
+    pub errors: Vec<LayoutError>,
+}
+
+impl LayoutBase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_child(&mut self, child: impl Layout + 'static) {
+        self.children.push(Box::new(child));
+    }
+
+    pub fn add_children<I>(&mut self, children: I)
+    where
+        I: IntoIterator<Item: Layout + 'static>,
+    {
+        for child in children {
+            self.add_child(child);
+        }
+    }
+}
+
+/// A simpler way to write a [`Layout`], where the [`LayoutBase`] handles the
+/// sizing and the layout only measures and arranges its children. Every
+/// [`CustomLayout`] is a [`Layout`].
+///
+/// The layout is sized using its [`IntrinsicSize`] like the built-in layouts,
+/// a shrinking axis is as big as [`CustomLayout::measure`]. Children are
+/// positioned from left to right and mirrored for right to left layouts.
+/// Custom layouts aren't cached, so they're solved again every time.
+///
+/// # Example
+/// A layout that stacks its children on top of each other.
+/// ```
+/// use agape_layout::{
+///     CustomLayout, EmptyLayout, IntrinsicSize, Layout, LayoutBase, Position, Size, solve_layout,
+/// };
+///
+/// #[derive(Debug, Default)]
+/// struct StackLayout {
+///     base: LayoutBase,
+/// }
+///
+/// impl CustomLayout for StackLayout {
+///     fn base(&self) -> &LayoutBase {
+///         &self.base
+///     }
+///
+///     fn base_mut(&mut self) -> &mut LayoutBase {
+///         &mut self.base
+///     }
+///
+///     fn arrange(&mut self) {
+///         let position = self.base.position;
+///         for child in &mut self.base.children {
+///             child.set_position(position);
+///         }
+///     }
+/// }
+///
+/// let mut stack = StackLayout::default();
+/// stack.base.add_child(EmptyLayout {
+///     intrinsic_size: IntrinsicSize::fixed(50.0, 20.0),
+///     ..Default::default()
+/// });
+/// stack.base.add_child(EmptyLayout {
+///     intrinsic_size: IntrinsicSize::fixed(20.0, 50.0),
+///     ..Default::default()
+/// });
+///
+/// solve_layout(&mut stack, Size::new(500.0, 500.0));
+/// assert_eq!(stack.size(), Size::new(50.0, 50.0));
+/// assert_eq!(stack.children()[1].position(), Position::new(0.0, 0.0));
+/// ```
+pub trait CustomLayout: Debug + Send + Sync + 'static {
+    fn base(&self) -> &LayoutBase;
+
+    fn base_mut(&mut self) -> &mut LayoutBase;
+
+    /// Get the smallest size that fits the children, the minimum constraints
+    /// of the children have already been solved. Defaults to the largest minimum
+    /// width and height of the children.
+    fn measure(&self) -> Size {
+        self.base()
+            .children
+            .iter()
+            .map(|child| child.constraints())
+            .fold(Size::default(), |size, constraints| {
+                Size::new(
+                    size.width.max(constraints.min_width),
+                    size.height.max(constraints.min_height),
+                )
+            })
+    }
+
+    /// Get the space that the child at `index` can take up, `space` is the space
+    /// given to this layout. Defaults to all the space.
+    fn child_space(&self, _index: usize, space: Size) -> Size {
+        space
+    }
+
+    /// Position the children, the size of the layout and its children is known
+    /// at this point.
+    fn arrange(&mut self);
+
+    /// Get the distance from the top of the layout to the baseline of its first
+    /// line of text, this is used as the [`Layout::baseline`].
+    fn baseline_offset(&self) -> Option<f32> {
+        None
+    }
+}
+
+impl<T: CustomLayout> Layout for T {
+    fn solve_min_constraints(&mut self) -> (f32, f32) {
+        for child in &mut self.base_mut().children {
+            child.solve_min_constraints();
+        }
+
+        let content = self.measure();
+        let base = self.base_mut();
+        base.constraints.min_width = match base.intrinsic_size.width {
+            BoxSizing::Fixed(width) => width,
+            BoxSizing::Shrink | BoxSizing::Flex(_) => content.width,
+        };
+        base.constraints.min_height = match base.intrinsic_size.height {
+            BoxSizing::Fixed(height) => height,
+            BoxSizing::Shrink | BoxSizing::Flex(_) => content.height,
+        };
+
+        (base.constraints.min_width, base.constraints.min_height)
+    }
+
+    fn solve_max_constraints(&mut self, space: Size) {
+        let spaces: Vec<Size> = (0..self.base().children.len())
+            .map(|index| self.child_space(index, space))
+            .collect();
+
+        for (child, space) in self.base_mut().children.iter_mut().zip(spaces) {
+            match child.intrinsic_size().width {
+                BoxSizing::Flex(_) => child.set_max_width(space.width),
+                BoxSizing::Fixed(width) => child.set_max_width(width),
+                BoxSizing::Shrink => {}
+            }
+            match child.intrinsic_size().height {
+                BoxSizing::Flex(_) => child.set_max_height(space.height),
+                BoxSizing::Fixed(height) => child.set_max_height(height),
+                BoxSizing::Shrink => {}
+            }
+            child.solve_max_constraints(space);
+        }
+    }
+
+    fn mark_dirty(&mut self, id: GlobalId) -> bool {
+        let base = self.base_mut();
+        base.id == id || base.children.iter_mut().any(|child| child.mark_dirty(id))
+    }
+
+    fn baseline(&self) -> Option<f32> {
+        self.baseline_offset()
+    }
+
+    fn overflow_mode(&self) -> Overflow {
+        self.base().overflow
+    }
+
+    fn corner_radius(&self) -> CornerRadius {
+        self.base().corner_radius
+    }
+
+    fn direction(&self) -> Direction {
+        let base = self.base();
+        base.direction.unwrap_or(base.inherited_direction)
+    }
+
+    fn inherit_direction(&mut self, direction: Direction) {
+        self.base_mut().inherited_direction = direction;
+    }
+
+    fn position_children(&mut self) {
+        self.arrange();
+
+        let direction = self.direction();
+        let base = self.base_mut();
+        for child in &mut base.children {
+            direction.place(child.as_mut(), base.position.x, base.size.width);
+            child.inherit_direction(direction);
+            child.position_children();
+        }
+    }
+
+    fn update_size(&mut self) {
+        let base = self.base_mut();
+        // The errors are found again every time the layout is solved
+        base.errors.clear();
+
+        base.size.width = match base.intrinsic_size.width {
+            BoxSizing::Flex(_) => base.constraints.max_width,
+            BoxSizing::Shrink => base.constraints.min_width,
+            BoxSizing::Fixed(width) => width,
+        };
+        base.size.height = match base.intrinsic_size.height {
+            BoxSizing::Flex(_) => base.constraints.max_height,
+            BoxSizing::Shrink => base.constraints.min_height,
+            BoxSizing::Fixed(height) => height,
+        };
+
+        for child in &mut base.children {
+            child.update_size();
+        }
+    }
+
+    fn collect_errors(&mut self) -> Vec<LayoutError> {
+        let base = self.base_mut();
+        let mut errors = base.errors.clone();
+        for child in &mut base.children {
+            errors.extend(child.collect_errors());
+        }
+        errors
+    }
+
+    fn id(&self) -> GlobalId {
+        self.base().id
+    }
+
+    fn constraints(&self) -> BoxConstraints {
+        self.base().constraints
+    }
+
+    fn intrinsic_size(&self) -> IntrinsicSize {
+        self.base().intrinsic_size
+    }
+
+    fn size(&self) -> Size {
+        self.base().size
+    }
+
+    fn position(&self) -> Position {
+        self.base().position
+    }
+
+    fn children(&self) -> &[Box<dyn Layout>] {
+        &self.base().children
+    }
+
+    fn set_max_width(&mut self, width: f32) {
+        self.base_mut().constraints.max_width = width;
+    }
+
+    fn set_max_height(&mut self, height: f32) {
+        self.base_mut().constraints.max_height = height;
+    }
+
+    fn set_min_width(&mut self, width: f32) {
+        self.base_mut().constraints.min_width = width;
+    }
+
+    fn set_min_height(&mut self, height: f32) {
+        self.base_mut().constraints.min_height = height;
+    }
+
+    fn set_position(&mut self, position: Position) {
+        self.base_mut().position = position;
+    }
+
+    fn set_x(&mut self, x: f32) {
+        self.base_mut().position.x = x;
+    }
+
+    fn set_y(&mut self, y: f32) {
+        self.base_mut().position.y = y;
+    }
+
+    fn iter(&self) -> LayoutIter<'_> {
+        LayoutIter { stack: vec![self] }
+    }
+}
@@ -1,5 +1,7 @@
 use crate::{BoxConstraints, BoxSizing, IntrinsicSize, Layout, LayoutIter};
 use agape_core::{GlobalId, Position, Size};
+use std::any::Any;
+use std::cmp::Ordering;
 
 /// An empty [`Layout`] with no child notes.  
 #[derive(Debug, Default, Clone, PartialEq)]
@@ -9,6 +11,9 @@ pub struct EmptyLayout {
     pub position: Position,
     pub intrinsic_size: IntrinsicSize,
     pub constraints: BoxConstraints,
+    /// Keeps the `width / height` ratio of the layout, see
+    /// [`EmptyLayout::aspect_ratio`].
+    pub aspect_ratio: Option<f32>,
     pub errors: Vec<crate::LayoutError>,
 }
 
@@ -16,6 +21,90 @@ impl EmptyLayout {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Keep the `width / height` of the layout at `ratio`.
+    ///
+    /// The axis with the strongest sizing decides the size of the other axis,
+    /// where [`BoxSizing::Fixed`] is stronger than [`BoxSizing::Flex`] which is
+    /// stronger than [`BoxSizing::Shrink`]. When both axes flex, the layout is
+    /// the largest size with the ratio that fits in the available space. When
+    /// both axes are fixed the ratio is ignored. The derived size is limited
+    /// to the max constraints, and the deciding axis shrinks with it so the
+    /// ratio is kept, even when it's fixed.
+    ///
+    /// # Example
+    /// ```
+    /// use agape_layout::{BoxSizing, EmptyLayout, Layout, Size, solve_layout};
+    ///
+    /// let mut layout = EmptyLayout::new().aspect_ratio(2.0);
+    /// layout.intrinsic_size.width = BoxSizing::Flex(1);
+    ///
+    /// solve_layout(&mut layout, Size::new(500.0, 500.0));
+    /// assert_eq!(layout.size(), Size::new(500.0, 250.0));
+    /// ```
+    pub fn aspect_ratio(mut self, ratio: f32) -> Self {
+        self.aspect_ratio = Some(ratio);
+        self
+    }
+
+    /// Get the aspect ratio, if there's a valid one.
+    fn ratio(&self) -> Option<f32> {
+        self.aspect_ratio.filter(|r| *r > 0.0 && r.is_finite())
+    }
+
+    /// Get the height that a flexible width decides, if there is one.
+    fn flex_derived_height(&self) -> Option<f32> {
+        let ratio = self.ratio()?;
+        match (self.intrinsic_size.width, self.intrinsic_size.height) {
+            (BoxSizing::Flex(_), BoxSizing::Shrink) if self.constraints.max_width > 0.0 => {
+                Some(self.constraints.max_width / ratio)
+            }
+            _ => None,
+        }
+    }
+
+    /// Adjust the size to the aspect ratio, if there is one.
+    fn apply_aspect_ratio(&mut self) {
+        let Some(ratio) = self.ratio() else {
+            return;
+        };
+
+        let strength = |sizing: BoxSizing| match sizing {
+            BoxSizing::Shrink => 0,
+            BoxSizing::Flex(_) => 1,
+            BoxSizing::Fixed(_) => 2,
+        };
+        let width = strength(self.intrinsic_size.width);
+        let height = strength(self.intrinsic_size.height);
+        let max_width = self.constraints.max_width;
+        let max_height = self.constraints.max_height;
+
+        match width.cmp(&height) {
+            Ordering::Greater => {
+                self.size.height = self.size.width / ratio;
+                if max_height > 0.0 && self.size.height > max_height {
+                    self.size.height = max_height;
+                    self.size.width = max_height * ratio;
+                }
+            }
+            Ordering::Less => {
+                self.size.width = self.size.height * ratio;
+                if max_width > 0.0 && self.size.width > max_width {
+                    self.size.width = max_width;
+                    self.size.height = max_width / ratio;
+                }
+            }
+            // Contain the size in the available space
+            Ordering::Equal if width == 1 => {
+                if self.size.width / ratio > self.size.height {
+                    self.size.width = self.size.height * ratio;
+                } else {
+                    self.size.height = self.size.width / ratio;
+                }
+            }
+            Ordering::Equal => {}
+        }
+    }
 }
 
 impl Layout for EmptyLayout {
@@ -72,19 +161,59 @@ impl Layout for EmptyLayout {
     }
 
     fn collect_errors(&mut self) -> Vec<crate::LayoutError> {
-        self.errors.drain(..).collect::<Vec<_>>()
+        self.errors.clone()
     }
 
     fn iter(&self) -> LayoutIter<'_> {
         LayoutIter { stack: vec![self] }
     }
 
+    fn reuse(&mut self, previous: &dyn Layout) -> bool {
+        let previous: &dyn Any = previous;
+        let Some(previous) = previous.downcast_ref::<Self>() else {
+            return false;
+        };
+
+        let unchanged = self.intrinsic_size == previous.intrinsic_size
+            && self.aspect_ratio == previous.aspect_ratio
+            && previous.errors.is_empty();
+        if unchanged {
+            self.size = previous.size;
+            self.position = previous.position;
+            self.constraints = previous.constraints;
+        }
+        unchanged
+    }
+
     fn solve_min_constraints(&mut self) -> (f32, f32) {
-        if let BoxSizing::Fixed(width) = self.intrinsic_size.width {
-            self.constraints.min_width = width;
+        // Reset the constraints the parent set, in case the layout is solved
+        // more than once.
+        self.constraints.min_width = match self.intrinsic_size.width {
+            BoxSizing::Fixed(width) => width,
+            BoxSizing::Shrink | BoxSizing::Flex(_) => 0.0,
+        };
+
+        self.constraints.min_height = match self.intrinsic_size.height {
+            BoxSizing::Fixed(height) => height,
+            BoxSizing::Shrink | BoxSizing::Flex(_) => 0.0,
+        };
+
+        // A fixed axis decides the minimum size of the other axis
+        if let Some(ratio) = self.ratio() {
+            match (self.intrinsic_size.width, self.intrinsic_size.height) {
+                (BoxSizing::Fixed(width), BoxSizing::Shrink | BoxSizing::Flex(_)) => {
+                    self.constraints.min_height = width / ratio;
+                }
+                (BoxSizing::Shrink | BoxSizing::Flex(_), BoxSizing::Fixed(height)) => {
+                    self.constraints.min_width = height * ratio;
+                }
+                _ => {}
+            }
         }
 
-        if let BoxSizing::Fixed(height) = self.intrinsic_size.height {
+        // A flexible width decides the height once the width is known, which
+        // is from the previous pass
+        if let Some(height) = self.flex_derived_height() {
             self.constraints.min_height = height;
         }
 
@@ -94,6 +223,12 @@ impl Layout for EmptyLayout {
     // No children to solve for
     fn solve_max_constraints(&mut self, _: Size) {}
 
+    fn min_size_changed(&self) -> bool {
+        // The parent has to fit the height that the flexible width decided
+        self.flex_derived_height()
+            .is_some_and(|height| height != self.constraints.min_height)
+    }
+
     fn update_size(&mut self) {
         match self.intrinsic_size.width {
             BoxSizing::Flex(_) => {
@@ -118,6 +253,8 @@ impl Layout for EmptyLayout {
                 self.size.height = height;
             }
         }
+
+        self.apply_aspect_ratio();
     }
 
     fn position_children(&mut self) {}
@@ -126,7 +263,7 @@ impl Layout for EmptyLayout {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::solve_layout;
+    use crate::{VerticalLayout, solve_layout};
 
     #[test]
     fn test_flex_sizing() {
@@ -163,4 +300,87 @@ mod test {
 
         assert_eq!(root.size(), Size::default());
     }
+
+    #[test]
+    fn aspect_ratio_contains_flex_size() {
+        let window = Size::new(800.0, 200.0);
+        let mut root = EmptyLayout::new().aspect_ratio(2.0);
+        root.intrinsic_size = IntrinsicSize::fill();
+
+        solve_layout(&mut root, window);
+
+        assert_eq!(root.size(), Size::new(400.0, 200.0));
+    }
+
+    #[test]
+    fn aspect_ratio_fixed_overrides_flex() {
+        let window = Size::new(800.0, 800.0);
+        let mut root = EmptyLayout::new().aspect_ratio(4.0);
+        root.intrinsic_size.width = BoxSizing::Flex(1);
+        root.intrinsic_size.height = BoxSizing::Fixed(50.0);
+
+        solve_layout(&mut root, window);
+
+        assert_eq!(root.size(), Size::new(200.0, 50.0));
+    }
+
+    #[test]
+    fn aspect_ratio_clamped_to_max_constraints() {
+        let window = Size::new(800.0, 100.0);
+        let mut root = EmptyLayout::new().aspect_ratio(0.5);
+        root.intrinsic_size.width = BoxSizing::Fixed(100.0);
+
+        solve_layout(&mut root, window);
+
+        // The fixed width shrinks to keep the ratio inside the window
+        assert_eq!(root.size(), Size::new(50.0, 100.0));
+    }
+
+    #[test]
+    fn aspect_ratio_flex_width_clamped_keeps_ratio() {
+        let window = Size::new(800.0, 200.0);
+        let mut root = EmptyLayout::new().aspect_ratio(2.0);
+        root.intrinsic_size.width = BoxSizing::Flex(1);
+
+        solve_layout(&mut root, window);
+
+        assert_eq!(root.size(), Size::new(400.0, 200.0));
+    }
+
+    #[test]
+    fn aspect_ratio_parent_fits_flex_derived_height() {
+        let window = Size::new(400.0, 800.0);
+        let mut child = EmptyLayout::new().aspect_ratio(2.0);
+        child.intrinsic_size.width = BoxSizing::Flex(1);
+        let mut root = VerticalLayout::new();
+        root.intrinsic_size.width = BoxSizing::Flex(1);
+        root.add_child(child);
+
+        solve_layout(&mut root, window);
+
+        assert_eq!(root.children[0].size(), Size::new(400.0, 200.0));
+        assert_eq!(root.size(), Size::new(400.0, 200.0));
+    }
+
+    #[test]
+    fn aspect_ratio_fixed_decides_shrink() {
+        let mut root = EmptyLayout::new().aspect_ratio(0.5);
+        root.intrinsic_size.width = BoxSizing::Fixed(100.0);
+
+        let (_, min_height) = root.solve_min_constraints();
+        solve_layout(&mut root, Size::new(800.0, 800.0));
+
+        assert_eq!(min_height, 200.0);
+        assert_eq!(root.size(), Size::new(100.0, 200.0));
+    }
+
+    #[test]
+    fn aspect_ratio_ignored_when_fixed() {
+        let mut root = EmptyLayout::new().aspect_ratio(2.0);
+        root.intrinsic_size = IntrinsicSize::fixed(100.0, 100.0);
+
+        solve_layout(&mut root, Size::new(800.0, 800.0));
+
+        assert_eq!(root.size(), Size::new(100.0, 100.0));
+    }
 }
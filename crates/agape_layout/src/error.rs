@@ -36,4 +36,15 @@ impl LayoutError {
     pub fn overflow(id: GlobalId, axis: OverflowAxis) -> Self {
         Self::Overflow { id, axis }
     }
+
+    /// Get the `id` of the [`Layout`] that caused the error, for
+    /// [`LayoutError::OutOfBounds`] this is the child that's out of bounds.
+    ///
+    /// [`Layout`]: crate::Layout
+    pub fn id(&self) -> GlobalId {
+        match self {
+            Self::OutOfBounds { child_id, .. } => *child_id,
+            Self::Overflow { id, .. } => *id,
+        }
+    }
 }
@@ -1,8 +1,9 @@
 use crate::{
-    AxisAlignment, BoxConstraints, BoxSizing, IntrinsicSize, Layout, LayoutError, LayoutIter,
-    Padding,
+    AxisAlignment, BoxConstraints, BoxSizing, Direction, IntrinsicSize, Layout, LayoutCache,
+    LayoutError, LayoutIter, LayoutParams, Overflow, Padding, error::OverflowAxis,
 };
-use agape_core::{GlobalId, Position, Size};
+use agape_core::{CornerRadius, GlobalId, Position, Size};
+use std::any::Any;
 
 // TODO add example
 /// A [`Layout`] that arranges it's children horizontally.
@@ -21,7 +22,20 @@ pub struct HorizontalLayout {
     /// The cross axis is the `y-axis`
     pub cross_axis_alignment: AxisAlignment,
     pub children: Vec<Box<dyn Layout>>,
+    /// The [`LayoutParams`] of each child, children without params use the
+    /// default params.
+    pub child_params: Vec<LayoutParams>,
     pub errors: Vec<LayoutError>,
+    pub cache: LayoutCache,
+    /// The [`Direction`] of the layout, `None` to use the parent's direction.
+    pub direction: Option<Direction>,
+    /// The [`Direction`] of the parent, set when the layout is solved.
+    pub inherited_direction: Direction,
+    /// How children that don't fit in the layout are shown.
+    pub overflow: Overflow,
+    /// The radius of the corners, the children are clipped to the rounded
+    /// corners when the overflow is clipped.
+    pub corner_radius: CornerRadius,
 }
 
 impl HorizontalLayout {
@@ -30,7 +44,17 @@ impl HorizontalLayout {
     }
 
     pub fn add_child(&mut self, child: impl Layout + 'static) {
+        self.add_child_with_params(child, LayoutParams::default());
+    }
+
+    /// Add a child [`Layout`] with its [`LayoutParams`].
+    pub fn add_child_with_params(&mut self, child: impl Layout + 'static, params: LayoutParams) {
+        // Keep the params aligned with the children
+        self.child_params
+            .resize(self.children.len(), LayoutParams::default());
         self.children.push(Box::new(child));
+        self.child_params.push(params);
+        self.cache.invalidate();
     }
 
     pub fn add_children<I>(&mut self, children: I)
@@ -38,10 +62,17 @@ impl HorizontalLayout {
         I: IntoIterator<Item: Layout + 'static>,
     {
         for child in children {
-            self.children.push(Box::new(child));
+            self.add_child(child);
         }
     }
 
+    /// Get the [`LayoutParams`] of every child.
+    fn params(&self) -> Vec<LayoutParams> {
+        (0..self.children.len())
+            .map(|i| self.child_params.get(i).copied().unwrap_or_default())
+            .collect()
+    }
+
     /// Calculate the total minimum constraints of all
     /// the child nodes. The width is the sum of all
     /// the children's minimum width plus the space in
@@ -53,12 +84,19 @@ impl HorizontalLayout {
             return sum;
         }
 
+        let params = self.params();
         let space_between = (self.children.len() - 1) as f32 * self.spacing as f32;
         sum.width += space_between;
-        for child in self.children.iter_mut() {
+        for (child, params) in self.children.iter_mut().zip(&params) {
             let (min_width, min_height) = child.solve_min_constraints();
-            sum.width += min_width;
-            sum.height = sum.height.max(min_height);
+            sum.width += min_width + params.margin.horizontal_sum();
+            sum.height = sum.height.max(min_height + params.margin.vertical_sum());
+        }
+        // Children aligned by their baseline can stick out above or below each other
+        if let Some((ascent, descent)) =
+            self.baseline_metrics(|child| child.constraints().min_height)
+        {
+            sum.height = sum.height.max(ascent + descent);
         }
         sum.width += self.padding.horizontal_sum();
         sum.height += self.padding.vertical_sum();
@@ -70,7 +108,7 @@ impl HorizontalLayout {
     fn fixed_size_sum(&self) -> Size {
         let mut sum = Size::default();
 
-        for (i, child) in self.children.iter().enumerate() {
+        for (i, (child, params)) in self.children.iter().zip(self.params()).enumerate() {
             match child.intrinsic_size().width {
                 BoxSizing::Fixed(width) => {
                     sum.width += width;
@@ -80,9 +118,10 @@ impl HorizontalLayout {
                 }
                 _ => {}
             }
+            sum.width += params.margin.horizontal_sum();
 
             if let BoxSizing::Fixed(height) = child.intrinsic_size().height {
-                sum.height = sum.height.max(height);
+                sum.height = sum.height.max(height + params.margin.vertical_sum());
             }
 
             // Add the spacing between layouts
@@ -94,13 +133,29 @@ impl HorizontalLayout {
         sum
     }
 
+    /// The sum of the children's widths, margins and the spacing in between.
+    fn content_width(&self) -> f32 {
+        let mut width_sum = self
+            .children
+            .iter()
+            .zip(self.params())
+            .map(|(child, params)| child.size().width + params.margin.horizontal_sum())
+            .sum::<f32>();
+        if !self.children.is_empty() {
+            width_sum += (self.children.len() - 1) as f32 * self.spacing as f32;
+        }
+        width_sum
+    }
+
     fn align_main_axis_start(&mut self) {
+        let params = self.params();
         let mut x_pos = self.position.x;
         x_pos += self.padding.left;
 
-        for child in &mut self.children {
+        for (child, params) in self.children.iter_mut().zip(&params) {
+            x_pos += params.margin.left;
             child.set_x(x_pos);
-            x_pos += child.size().width + self.spacing as f32;
+            x_pos += child.size().width + params.margin.right + self.spacing as f32;
         }
     }
 
@@ -111,53 +166,109 @@ impl HorizontalLayout {
             return;
         }
 
-        // Sum the width of all the children.
-        let mut width_sum = self
-            .children
-            .iter()
-            .map(|child| child.size().width)
-            .sum::<f32>();
-        // Add the spacing in between each child
-        let space_between = self.spacing * (self.children.len() - 1) as u32;
-        width_sum += space_between as f32;
-        let mut center_start = self.position.x + (self.size.width - width_sum) / 2.0;
+        let params = self.params();
+        let width_sum = self.content_width();
+        let inner_width = self.size.width - self.padding.horizontal_sum();
+        let mut center_start =
+            self.position.x + self.padding.left + (inner_width - width_sum) / 2.0;
 
-        for child in &mut self.children {
+        for (child, params) in self.children.iter_mut().zip(&params) {
+            center_start += params.margin.left;
             child.set_x(center_start);
-            center_start += child.size().width + self.spacing as f32;
+            center_start += child.size().width + params.margin.right + self.spacing as f32;
         }
     }
 
     fn align_main_axis_end(&mut self) {
+        let params = self.params();
         let mut x_pos = self.position.x + self.size.width;
         x_pos -= self.padding.right;
 
-        for child in self.children.iter_mut().rev() {
+        for (child, params) in self.children.iter_mut().zip(&params).rev() {
             // Set the right edge
+            x_pos -= params.margin.right;
             x_pos -= child.size().width;
             child.set_x(x_pos);
+            x_pos -= params.margin.left;
             x_pos -= self.spacing as f32;
         }
     }
 
-    fn align_cross_axis_start(&mut self) {
-        let y = self.position.y + self.padding.top;
-        for child in &mut self.children {
-            child.set_y(y);
-        }
-    }
+    /// Distribute the remaining space on the main axis between the children.
+    fn align_main_axis_space(&mut self) {
+        let params = self.params();
+        let free_space = self.size.width - self.padding.horizontal_sum() - self.content_width();
+        let (leading, gap) = self
+            .main_axis_alignment
+            .distribute(free_space, self.children.len());
 
-    fn align_cross_axis_center(&mut self) {
-        for child in &mut self.children {
-            // TODO handle overflow
-            let y_pos = (self.size.height - child.size().height) / 2.0 + self.position.y;
-            child.set_y(y_pos);
+        let mut x_pos = self.position.x + self.padding.left + leading;
+        for (child, params) in self.children.iter_mut().zip(&params) {
+            x_pos += params.margin.left;
+            child.set_x(x_pos);
+            x_pos += child.size().width + params.margin.right + self.spacing as f32 + gap;
         }
     }
 
-    fn align_cross_axis_end(&mut self) {
-        for child in &mut self.children {
-            child.set_y(self.position.y + self.size.height - self.padding.bottom);
+    /// Get the distance from the top of the content to the shared baseline of the
+    /// children aligned by their baseline, and the largest distance from the baseline
+    /// to the bottom of a child. Returns `None` if no children are aligned by their
+    /// baseline.
+    fn baseline_metrics(&self, height: impl Fn(&dyn Layout) -> f32) -> Option<(f32, f32)> {
+        let mut metrics: Option<(f32, f32)> = None;
+        for (child, params) in self.children.iter().zip(self.params()) {
+            let alignment = params.align_self.unwrap_or(self.cross_axis_alignment);
+            if alignment != AxisAlignment::Baseline {
+                continue;
+            }
+
+            let height = height(child.as_ref());
+            let baseline = child.baseline().unwrap_or(height);
+            let ascent = params.margin.top + baseline;
+            let descent = height - baseline + params.margin.bottom;
+            let (max_ascent, max_descent) = metrics.unwrap_or_default();
+            metrics = Some((max_ascent.max(ascent), max_descent.max(descent)));
+        }
+        metrics
+    }
+
+    /// Align each child on the cross axis, using the child's own alignment
+    /// if it has one.
+    fn align_cross_axis(&mut self) {
+        let params = self.params();
+        let ascent = self
+            .baseline_metrics(|child| child.size().height)
+            .map(|(ascent, _)| ascent)
+            .unwrap_or_default();
+        for (child, params) in self.children.iter_mut().zip(&params) {
+            let alignment = params.align_self.unwrap_or(self.cross_axis_alignment);
+            let margin = params.margin;
+            // There's only one child on each cross axis line, so distributing
+            // the space is the same as placing it at the start or center.
+            let y_pos = match alignment {
+                AxisAlignment::Start | AxisAlignment::SpaceBetween | AxisAlignment::Stretch => {
+                    self.position.y + self.padding.top + margin.top
+                }
+                AxisAlignment::Baseline => {
+                    let baseline = child.baseline().unwrap_or(child.size().height);
+                    self.position.y + self.padding.top + ascent - baseline
+                }
+                AxisAlignment::Center | AxisAlignment::SpaceAround | AxisAlignment::SpaceEvenly => {
+                    // TODO handle overflow
+                    let inner_height = self.size.height - self.padding.vertical_sum();
+                    (inner_height - child.size().height) / 2.0
+                        + self.position.y
+                        + self.padding.top
+                        + (margin.top - margin.bottom) / 2.0
+                }
+                AxisAlignment::End => {
+                    self.position.y + self.size.height
+                        - self.padding.bottom
+                        - margin.bottom
+                        - child.size().height
+                }
+            };
+            child.set_y(y_pos);
         }
     }
 }
@@ -213,7 +324,8 @@ impl Layout for HorizontalLayout {
 
     fn collect_errors(&mut self) -> Vec<crate::LayoutError> {
         self.errors
-            .drain(..)
+            .iter()
+            .cloned()
             .chain(
                 self.children
                     .iter_mut()
@@ -227,7 +339,92 @@ impl Layout for HorizontalLayout {
         LayoutIter { stack: vec![self] }
     }
 
+    fn mark_dirty(&mut self, id: GlobalId) -> bool {
+        let found = self.id == id || self.children.iter_mut().any(|child| child.mark_dirty(id));
+        if found {
+            self.cache.invalidate();
+        }
+        found
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.cache.is_dirty()
+    }
+
+    fn baseline(&self) -> Option<f32> {
+        if let Some((ascent, _)) = self.baseline_metrics(|child| child.size().height) {
+            return Some(self.padding.top + ascent);
+        }
+
+        let child = self.children.first()?;
+        let params = self.params()[0];
+        let alignment = params.align_self.unwrap_or(self.cross_axis_alignment);
+        match alignment {
+            AxisAlignment::Start | AxisAlignment::SpaceBetween | AxisAlignment::Stretch => {
+                Some(self.padding.top + params.margin.top + child.baseline()?)
+            }
+            _ => None,
+        }
+    }
+
+    fn overflow_mode(&self) -> Overflow {
+        self.overflow
+    }
+
+    fn corner_radius(&self) -> CornerRadius {
+        self.corner_radius
+    }
+
+    fn direction(&self) -> Direction {
+        self.direction.unwrap_or(self.inherited_direction)
+    }
+
+    fn inherit_direction(&mut self, direction: Direction) {
+        if self.direction.is_none() && self.inherited_direction != direction {
+            self.inherited_direction = direction;
+            self.cache.invalidate();
+        }
+    }
+
+    fn reuse(&mut self, previous: &dyn Layout) -> bool {
+        let previous: &dyn Any = previous;
+        let Some(previous) = previous.downcast_ref::<Self>() else {
+            return false;
+        };
+
+        // Unchanged children are reused even if this layout has changed
+        let mut reused = self.children.len() == previous.children.len();
+        for (child, previous) in self.children.iter_mut().zip(&previous.children) {
+            reused &= child.reuse(previous.as_ref());
+        }
+
+        let unchanged = self.spacing == previous.spacing
+            && self.padding == previous.padding
+            && self.intrinsic_size == previous.intrinsic_size
+            && self.main_axis_alignment == previous.main_axis_alignment
+            && self.cross_axis_alignment == previous.cross_axis_alignment
+            && self.params() == previous.params()
+            && self.direction == previous.direction;
+        if !reused || !unchanged || previous.is_dirty() || !previous.errors.is_empty() {
+            return false;
+        }
+
+        self.size = previous.size;
+        self.position = previous.position;
+        self.constraints = previous.constraints;
+        self.cache = previous.cache;
+        self.inherited_direction = previous.inherited_direction;
+        true
+    }
+
     fn solve_min_constraints(&mut self) -> (f32, f32) {
+        if let Some((min_width, min_height)) = self.cache.min_size() {
+            // Restore the min constraints in case the parent changed them
+            self.constraints.min_width = min_width;
+            self.constraints.min_height = min_height;
+            return (min_width, min_height);
+        }
+
         let child_constraint_sum = self.compute_children_min_size();
         match self.intrinsic_size.width {
             BoxSizing::Fixed(width) => {
@@ -247,20 +444,32 @@ impl Layout for HorizontalLayout {
             }
         }
 
-        (self.constraints.min_width, self.constraints.min_height)
+        let min_size = (self.constraints.min_width, self.constraints.min_height);
+        self.cache.set_min_size(min_size);
+        min_size
     }
 
-    fn solve_max_constraints(&mut self, _space: Size) {
-        // Sum up all the flex factors
-        let flex_total: u8 = self
+    fn solve_max_constraints(&mut self, space: Size) {
+        if self.cache.reuse(self.constraints, space) {
+            return;
+        }
+
+        // Restore the children's min constraints which the last solve may
+        // have changed, clean children return their cached constraints.
+        for child in &mut self.children {
+            child.solve_min_constraints();
+        }
+
+        let params = self.params();
+        // Sum up all the flex factors, including the children that grow
+        let flex_total: f32 = self
             .children
             .iter()
-            .filter_map(|child| {
-                if let BoxSizing::Flex(factor) = child.intrinsic_size().width {
-                    Some(factor)
-                } else {
-                    None
-                }
+            .zip(&params)
+            .map(|(child, params)| match child.intrinsic_size().width {
+                BoxSizing::Flex(factor) => factor as f32,
+                BoxSizing::Shrink => params.flex_grow as f32,
+                BoxSizing::Fixed(_) => 0.0,
             })
             .sum();
 
@@ -273,6 +482,13 @@ impl Layout for HorizontalLayout {
             }
         }
 
+        // Stretched children fill the height inside the padding
+        let stretch_height = self.intrinsic_size.height.stretch_size(
+            self.constraints.min_height,
+            self.constraints.max_height,
+            self.padding.vertical_sum(),
+        );
+
         let mut available_width;
         match self.intrinsic_size.width {
             BoxSizing::Shrink => {
@@ -286,10 +502,31 @@ impl Layout for HorizontalLayout {
             }
         }
 
-        for child in &mut self.children {
+        // The shrinking children grow into, or give up, the space left over. A
+        // shrinking layout is already as small as its children so there's
+        // nothing left over, unless its children are wider than its max width.
+        let free_width = match self.intrinsic_size.width {
+            BoxSizing::Shrink if self.constraints.max_width > 0.0 => {
+                (self.constraints.max_width - self.constraints.min_width).min(0.0)
+            }
+            BoxSizing::Shrink => 0.0,
+            BoxSizing::Fixed(_) | BoxSizing::Flex(_) => available_width,
+        };
+
+        // When the children overflow, the shrinking children give up space
+        // relative to their shrink factor and size.
+        let shrink_total: f32 = self
+            .children
+            .iter()
+            .zip(&params)
+            .filter(|(child, _)| child.intrinsic_size().width == BoxSizing::Shrink)
+            .map(|(child, params)| params.flex_shrink * child.constraints().min_width)
+            .sum();
+
+        for (child, params) in self.children.iter_mut().zip(&params) {
             match child.intrinsic_size().width {
                 BoxSizing::Flex(factor) => {
-                    let grow_factor = factor as f32 / flex_total as f32;
+                    let grow_factor = factor as f32 / flex_total;
                     child.set_max_width(grow_factor * available_width);
                 }
                 BoxSizing::Fixed(width) => {
@@ -297,13 +534,22 @@ impl Layout for HorizontalLayout {
                 }
                 BoxSizing::Shrink => {
                     // Not sure about this
-                    child.set_max_width(child.constraints().min_width);
+                    let mut width = child.constraints().min_width;
+                    if free_width > 0.0 && params.flex_grow > 0 {
+                        width += params.flex_grow as f32 / flex_total * free_width;
+                    } else if free_width < 0.0 && shrink_total > 0.0 {
+                        let shrink_factor = params.flex_shrink * width / shrink_total;
+                        width = shrink_factor.mul_add(free_width, width).max(0.0);
+                    }
+                    child.set_min_width(width);
+                    child.set_max_width(width);
                 }
             }
 
+            let alignment = params.align_self.unwrap_or(self.cross_axis_alignment);
             match child.intrinsic_size().height {
                 BoxSizing::Flex(_) => {
-                    child.set_max_height(available_height);
+                    child.set_max_height(available_height - params.margin.vertical_sum());
                 }
                 BoxSizing::Fixed(height) => {
                     child.set_max_height(height);
@@ -313,6 +559,14 @@ impl Layout for HorizontalLayout {
                 }
             }
 
+            if alignment == AxisAlignment::Stretch
+                && !matches!(child.intrinsic_size().height, BoxSizing::Fixed(_))
+            {
+                let height = stretch_height - params.margin.vertical_sum();
+                child.set_min_height(height);
+                child.set_max_height(height);
+            }
+
             // Pass the max size to the children to solve their max constraints
             let space = Size {
                 width: child.constraints().max_width,
@@ -325,6 +579,13 @@ impl Layout for HorizontalLayout {
     }
 
     fn update_size(&mut self) {
+        if self.cache.is_reused() {
+            return;
+        }
+
+        // The errors are found again every time the layout is solved
+        self.errors.clear();
+
         match self.intrinsic_size.width {
             BoxSizing::Flex(_) => {
                 self.size.width = self.constraints.max_width;
@@ -352,28 +613,63 @@ impl Layout for HorizontalLayout {
         for child in &mut self.children {
             child.update_size();
         }
+
+        // Clipped children are allowed to overflow, it's not an error
+        // when they're hidden on purpose
+        if self.overflow.clips() {
+            return;
+        }
+
+        let width_sum = self.padding.horizontal_sum() + self.content_width();
+        let height_sum = self
+            .children
+            .iter()
+            .zip(self.params())
+            .map(|(child, params)| child.size().height + params.margin.vertical_sum())
+            .fold(0.0, f32::max)
+            + self.padding.vertical_sum();
+
+        let main_axis_error = LayoutError::overflow(self.id, OverflowAxis::MainAxis);
+        let cross_axis_error = LayoutError::overflow(self.id, OverflowAxis::CrossAxis);
+
+        // Prevent duplicate errors
+        if !self.errors.contains(&main_axis_error) && width_sum > self.size.width {
+            self.errors.push(main_axis_error);
+        }
+
+        if !self.errors.contains(&cross_axis_error) && height_sum > self.size.height {
+            self.errors.push(cross_axis_error);
+        }
     }
 
     fn position_children(&mut self) {
+        if self.cache.reuse_position(self.position) {
+            return;
+        }
+
         match self.main_axis_alignment {
-            AxisAlignment::Start => self.align_main_axis_start(),
+            AxisAlignment::Start | AxisAlignment::Stretch | AxisAlignment::Baseline => {
+                self.align_main_axis_start()
+            }
             AxisAlignment::Center => self.align_main_axis_center(),
             AxisAlignment::End => self.align_main_axis_end(),
+            AxisAlignment::SpaceBetween
+            | AxisAlignment::SpaceAround
+            | AxisAlignment::SpaceEvenly => self.align_main_axis_space(),
         }
 
-        match self.cross_axis_alignment {
-            AxisAlignment::Start => self.align_cross_axis_start(),
-            AxisAlignment::Center => self.align_cross_axis_center(),
-            AxisAlignment::End => self.align_cross_axis_end(),
-        }
+        self.align_cross_axis();
 
+        let direction = self.direction();
         for child in &mut self.children {
-            if child.position().x > self.position.x + self.size.width {
-                self.errors.push(LayoutError::OutOfBounds {
-                    parent_id: self.id,
-                    child_id: child.id().to_owned(),
-                });
+            if !self.overflow.clips() && child.position().x > self.position.x + self.size.width {
+                let error = LayoutError::out_of_bound(self.id, child.id());
+                if !self.errors.contains(&error) {
+                    self.errors.push(error);
+                }
             }
+            direction.place(child.as_mut(), self.position.x, self.size.width);
+            child.inherit_direction(direction);
             child.position_children();
         }
     }
@@ -17,22 +17,36 @@
 #![warn(clippy::suspicious_operation_groupings)]
 #![warn(clippy::imprecise_flops)]
 mod block;
+mod cache;
+mod custom;
 mod empty;
 mod error;
 mod horizontal;
+mod measure;
+mod scroll;
+mod snapshot;
+mod tree;
 mod vertical;
 
-use agape_core::{Bounds, GlobalId};
+use agape_core::{Bounds, CornerRadius, GlobalId};
 pub use agape_core::{Position, Size};
 pub use block::BlockLayout;
+pub use cache::LayoutCache;
+pub use custom::{CustomLayout, LayoutBase};
 pub use empty::EmptyLayout;
-pub use error::LayoutError;
+pub use error::{LayoutError, OverflowAxis};
 pub use horizontal::HorizontalLayout;
+pub use measure::{MeasureFn, MeasureLayout};
+pub use scroll::{ScrollAxis, ScrollLayout};
+pub use snapshot::{LayoutSnapshot, UPDATE_SNAPSHOTS};
+use std::any::Any;
 use std::fmt::Debug;
+pub use tree::{LayoutNode, LayoutTree};
 pub use vertical::VerticalLayout;
 
-/// Calculates the layout of all the layout nodes
-pub fn solve_layout(root: &mut dyn Layout, window_size: Size) -> Vec<LayoutError> {
+/// Calculates the layout of all the layout nodes, returning the solved nodes
+/// indexed by their id, along with the errors found while solving the layout.
+pub fn solve_layout(root: &mut dyn Layout, window_size: Size) -> LayoutTree {
     root.set_max_width(window_size.width);
     root.set_max_height(window_size.height);
 
@@ -40,23 +54,112 @@ pub fn solve_layout(root: &mut dyn Layout, window_size: Size) -> Vec<LayoutError
     // because the min constraints are used in calculating max constraints
     let _ = root.solve_min_constraints();
     root.solve_max_constraints(window_size);
+
+    // Content like wrapped text only knows its height once it knows its width,
+    // so solve the constraints again for the parents to fit the new size.
+    let changed: Vec<GlobalId> = root
+        .iter()
+        .filter(|layout| layout.min_size_changed())
+        .map(|layout| layout.id())
+        .collect();
+    if !changed.is_empty() {
+        for id in changed {
+            root.mark_dirty(id);
+        }
+        let _ = root.solve_min_constraints();
+        root.solve_max_constraints(window_size);
+    }
     root.update_size();
     root.position_children();
 
-    // TODO add a push error function that checks for equality so that we don't have duplicate errors
-    // or maybe just clear the error stack every frame
-    // root.collect_errors()
-    vec![]
+    let mut errors: Vec<LayoutError> = vec![];
+    for error in root.collect_errors() {
+        if !errors.contains(&error) {
+            errors.push(error);
+        }
+    }
+
+    let mut tree = LayoutTree::new(root);
+    tree.set_errors(errors);
+    tree
 }
 
 // TODO: add anchor layout and grid layout
-pub trait Layout: Debug + Send + Sync {
+pub trait Layout: Debug + Send + Sync + Any {
     /// Solve the minimum constraints of each [`Layout`] node recursively
     fn solve_min_constraints(&mut self) -> (f32, f32);
 
     /// Solve the max constraints for the children and pass them down the tree
     fn solve_max_constraints(&mut self, space: Size);
 
+    /// Returns `true` if the minimum size of the [`Layout`] changed while solving
+    /// the max constraints, such as content that wraps to the width it's given.
+    fn min_size_changed(&self) -> bool {
+        false
+    }
+
+    /// Mark the [`Layout`] with the `id`, and all of its ancestors, as dirty so
+    /// that they're solved again. Returns `false` if the `id` isn't in the tree.
+    ///
+    /// Layouts with children must mark their children as well.
+    fn mark_dirty(&mut self, id: GlobalId) -> bool {
+        self.id() == id
+    }
+
+    /// Returns `true` if the [`Layout`] has to be solved again, layouts without
+    /// a [`LayoutCache`] are always solved.
+    fn is_dirty(&self) -> bool {
+        true
+    }
+
+    /// Take the solved results of the `previous` layout, from an older layout tree,
+    /// if it has the same inputs. Returns `true` if the results were reused, in
+    /// which case the layout is clean and is only solved again if its constraints
+    /// change.
+    ///
+    /// This lets a rebuilt layout tree skip solving the parts that haven't changed.
+    fn reuse(&mut self, _previous: &dyn Layout) -> bool {
+        false
+    }
+
+    /// Get the name of the [`Layout`] used in snapshots, this is the type name
+    /// without its module path by default.
+    fn name(&self) -> &'static str {
+        agape_core::short_type_name::<Self>()
+    }
+
+    /// Get the distance from the top of the [`Layout`] to the baseline of its first
+    /// line of text, `None` if it doesn't have any text.
+    fn baseline(&self) -> Option<f32> {
+        None
+    }
+
+    /// Get the [`Overflow`] of the layout, which decides if the children
+    /// are clipped to its bounds.
+    fn overflow_mode(&self) -> Overflow {
+        Overflow::Visible
+    }
+
+    /// Get the radius of the rounded corners the children are clipped to.
+    fn corner_radius(&self) -> CornerRadius {
+        CornerRadius::default()
+    }
+
+    /// Get how far the content of the layout is scrolled from its start,
+    /// positive values are towards the end of the content.
+    fn scroll_offset(&self) -> Position {
+        Position::default()
+    }
+
+    /// Get the [`Direction`] that the content of the layout flows in.
+    fn direction(&self) -> Direction {
+        Direction::default()
+    }
+
+    /// Set the [`Direction`] inherited from the parent, layouts that have their
+    /// own direction keep it.
+    fn inherit_direction(&mut self, _direction: Direction) {}
+
     /// Position the layout nodes after size calculations.
     fn position_children(&mut self);
 
@@ -143,13 +246,152 @@ pub enum BoxSizing {
     Flex(u8),
 }
 
+impl BoxSizing {
+    /// Get the size that stretched children fill on the cross axis, inside the
+    /// `padding` of a layout with the `min` and `max` constraints on that axis.
+    ///
+    /// A shrinking layout is stretched to its min size, anything else to
+    /// its max size.
+    pub(crate) fn stretch_size(&self, min: f32, max: f32, padding: f32) -> f32 {
+        match self {
+            BoxSizing::Shrink => min - padding,
+            BoxSizing::Fixed(_) | BoxSizing::Flex(_) => max - padding,
+        }
+    }
+}
+
+/// How the children of a [`Layout`] that don't fit inside of it are shown.
+///
+/// Only [`Overflow::Visible`] layouts report [`LayoutError`]s for overflowing
+/// children. Clipping is how the content is meant to overflow, so clipped
+/// layouts don't report it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Overflow {
+    /// The children are drawn outside the layout.
+    #[default]
+    Visible,
+    /// The children are clipped to the layout.
+    Hidden,
+    /// The children are clipped to the layout and can be scrolled. The layout
+    /// only clips, the offset is kept by a [`ScrollLayout`] around the content.
+    Scroll,
+}
+
+impl Overflow {
+    /// Returns `true` if the children are clipped to the bounds of the layout.
+    pub fn clips(&self) -> bool {
+        matches!(self, Self::Hidden | Self::Scroll)
+    }
+}
+
+/// The direction that content flows in horizontally.
+///
+/// A [`Layout`] without its own direction inherits the direction of its parent,
+/// so setting the direction of the root sets it for the whole tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    #[default]
+    LeftToRight,
+    /// Content flows from the right, such as in Arabic and Hebrew. The horizontal
+    /// flow, start and end alignment, and the left and right padding are mirrored,
+    /// so the left padding is applied on the right side.
+    RightToLeft,
+}
+
+impl Direction {
+    pub fn is_rtl(&self) -> bool {
+        *self == Self::RightToLeft
+    }
+
+    /// Mirror the `child` inside the bounds of its parent, if the direction is
+    /// right to left. Children are always positioned left to right first.
+    pub(crate) fn place(&self, child: &mut dyn Layout, parent_x: f32, parent_width: f32) {
+        if self.is_rtl() {
+            let x = 2.0f32.mul_add(parent_x, parent_width) - child.position().x;
+            child.set_x(x - child.size().width);
+        }
+    }
+}
+
 /// Describes how a [`Layout`] should arrange its children
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum AxisAlignment {
     #[default]
     Start,
     Center,
     End,
+    /// Place the first and last children at the edges and distribute the
+    /// remaining space evenly between the children.
+    SpaceBetween,
+    /// Distribute the remaining space evenly around each child, the space at the
+    /// edges is half the space between the children.
+    SpaceAround,
+    /// Distribute the remaining space so that the space between the children and
+    /// the edges is equal.
+    SpaceEvenly,
+    /// Stretch the children to fill the cross axis, children with a fixed
+    /// size keep their size. On the main axis this is the same as [`AxisAlignment::Start`].
+    Stretch,
+    /// Line up the baselines of the children, children without a baseline use
+    /// their bottom edge. This is only used on the cross axis of a
+    /// [`HorizontalLayout`], everywhere else it's the same as [`AxisAlignment::Start`].
+    Baseline,
+}
+
+impl AxisAlignment {
+    /// Get the space before the first child and the extra space between each
+    /// child, when distributing `free_space` between `count` children.
+    ///
+    /// Alignments that don't distribute space return `(0.0, 0.0)`.
+    pub(crate) fn distribute(&self, free_space: f32, count: usize) -> (f32, f32) {
+        // Children that overflow are placed at the start
+        let free_space = free_space.max(0.0);
+        let count = count as f32;
+        match self {
+            AxisAlignment::SpaceBetween if count > 1.0 => (0.0, free_space / (count - 1.0)),
+            AxisAlignment::SpaceAround if count > 0.0 => {
+                let space = free_space / count;
+                (space / 2.0, space)
+            }
+            AxisAlignment::SpaceEvenly => {
+                let space = free_space / (count + 1.0);
+                (space, space)
+            }
+            _ => (0.0, 0.0),
+        }
+    }
+}
+
+/// The space around the outside of a [`Layout`].
+pub type Margin = Padding;
+
+/// Per child parameters of a [`HorizontalLayout`] or [`VerticalLayout`].
+///
+/// # Example
+/// ```
+/// use agape_layout::{AxisAlignment, EmptyLayout, HorizontalLayout, LayoutParams, Margin};
+///
+/// let mut layout = HorizontalLayout::new();
+/// let params = LayoutParams {
+///     align_self: Some(AxisAlignment::Center),
+///     margin: Margin::all(12.0),
+///     ..Default::default()
+/// };
+/// layout.add_child_with_params(EmptyLayout::new(), params);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct LayoutParams {
+    /// Overrides the parent's cross axis alignment for this child.
+    pub align_self: Option<AxisAlignment>,
+    /// The space around the outside of the child.
+    pub margin: Margin,
+    /// The share of the remaining main axis space the child grows by, relative
+    /// to its siblings. Children with a [`BoxSizing::Flex`] size grow by their
+    /// flex factor instead, and fixed size children never grow.
+    pub flex_grow: u8,
+    /// How much the child shrinks, relative to its siblings, when the children
+    /// overflow the main axis. Only applies to [`BoxSizing::Shrink`] children.
+    pub flex_shrink: f32,
 }
 
 /// Describes the maximum and minimum size of a [`Layout`]
@@ -226,9 +468,15 @@ impl From<Size> for IntrinsicSize {
     }
 }
 
+/// The space inside the edges of a [`Layout`].
+///
+/// The `left` and `right` sides are the logical start and end sides, they're
+/// swapped in a [`Direction::RightToLeft`] layout.
 #[derive(Clone, Copy, Default, PartialEq, PartialOrd, Debug)]
 pub struct Padding {
+    /// The start side.
     pub left: f32,
+    /// The end side.
     pub right: f32,
     pub top: f32,
     pub bottom: f32,
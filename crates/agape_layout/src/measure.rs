@@ -0,0 +1,364 @@
+use crate::{BoxConstraints, BoxSizing, IntrinsicSize, Layout, LayoutError, LayoutIter};
+use agape_core::{GlobalId, Position, Size};
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// Measures the size of content given the width it may take up, `None` means
+/// there's no limit on the width.
+pub type MeasureFn = dyn Fn(Option<f32>) -> Size + Send + Sync;
+
+/// A leaf [`Layout`] whose height depends on its width, such as text that wraps.
+///
+/// The width is solved first, then the content is measured at that width to get
+/// the height. A [`BoxSizing::Shrink`] width is the width of the content, limited
+/// to the space the parent has available.
+///
+/// # Example
+/// ```
+/// use agape_layout::{BoxSizing, Layout, MeasureLayout, Size, solve_layout};
+///
+/// // Content that has an area of 10,000
+/// let mut layout = MeasureLayout::new(|width| {
+///     let width = width.unwrap_or(1000.0).min(1000.0);
+///     Size::new(width, 10_000.0 / width)
+/// });
+///
+/// solve_layout(&mut layout, Size::new(500.0, 500.0));
+/// assert_eq!(layout.size(), Size::new(500.0, 20.0));
+/// ```
+#[derive(Clone)]
+pub struct MeasureLayout {
+    pub id: GlobalId,
+    pub size: Size,
+    pub position: Position,
+    pub intrinsic_size: IntrinsicSize,
+    pub constraints: BoxConstraints,
+    pub errors: Vec<LayoutError>,
+    /// Identifies the measured content, see [`MeasureLayout::key`].
+    pub key: Option<u64>,
+    /// The distance from the top to the baseline of the first line.
+    pub baseline: Option<f32>,
+    measure: Arc<MeasureFn>,
+    /// The size from the last time the max constraints were solved.
+    measured: Option<Size>,
+    changed: bool,
+}
+
+impl MeasureLayout {
+    /// Create a new [`MeasureLayout`] with a function that measures the content.
+    pub fn new(measure: impl Fn(Option<f32>) -> Size + Send + Sync + 'static) -> Self {
+        Self {
+            id: GlobalId::default(),
+            size: Size::default(),
+            position: Position::default(),
+            intrinsic_size: IntrinsicSize::default(),
+            constraints: BoxConstraints::default(),
+            errors: vec![],
+            key: None,
+            baseline: None,
+            measure: Arc::new(measure),
+            measured: None,
+            changed: false,
+        }
+    }
+
+    /// Set a key that identifies the measured content, such as a hash of
+    /// the text. A layout can only reuse the results of a previous layout
+    /// with the same key, see [`Layout::reuse`].
+    pub fn key(mut self, key: u64) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Set the distance from the top of the content to the baseline of its
+    /// first line, used by [`AxisAlignment::Baseline`].
+    ///
+    /// [`AxisAlignment::Baseline`]: crate::AxisAlignment::Baseline
+    pub fn baseline(mut self, baseline: f32) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    /// Measure the content with a width limit.
+    pub fn measure(&self, width: Option<f32>) -> Size {
+        (self.measure)(width)
+    }
+}
+
+impl Debug for MeasureLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MeasureLayout")
+            .field("id", &self.id)
+            .field("size", &self.size)
+            .field("position", &self.position)
+            .field("intrinsic_size", &self.intrinsic_size)
+            .field("constraints", &self.constraints)
+            .field("errors", &self.errors)
+            .field("key", &self.key)
+            .field("baseline", &self.baseline)
+            .field("measured", &self.measured)
+            .finish()
+    }
+}
+
+impl Layout for MeasureLayout {
+    fn size(&self) -> Size {
+        self.size
+    }
+
+    fn id(&self) -> GlobalId {
+        self.id
+    }
+
+    fn set_x(&mut self, x: f32) {
+        self.position.x = x;
+    }
+
+    fn set_y(&mut self, y: f32) {
+        self.position.y = y;
+    }
+
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn children(&self) -> &[Box<dyn Layout>] {
+        &[]
+    }
+
+    fn constraints(&self) -> BoxConstraints {
+        self.constraints
+    }
+
+    fn intrinsic_size(&self) -> IntrinsicSize {
+        self.intrinsic_size
+    }
+
+    fn set_max_height(&mut self, height: f32) {
+        self.constraints.max_height = height;
+    }
+
+    fn set_max_width(&mut self, width: f32) {
+        self.constraints.max_width = width;
+    }
+
+    fn set_min_height(&mut self, height: f32) {
+        self.constraints.min_height = height;
+    }
+
+    fn set_min_width(&mut self, width: f32) {
+        self.constraints.min_width = width;
+    }
+
+    fn collect_errors(&mut self) -> Vec<LayoutError> {
+        self.errors.clone()
+    }
+
+    fn iter(&self) -> LayoutIter<'_> {
+        LayoutIter { stack: vec![self] }
+    }
+
+    fn mark_dirty(&mut self, id: GlobalId) -> bool {
+        if self.id != id {
+            return false;
+        }
+
+        if self.changed {
+            // Marked by the solve that measured it, so that the parents fit
+            // the new size. The measurement is kept for the next pass.
+            self.changed = false;
+        } else {
+            // The content changed so the last measurement is stale
+            self.measured = None;
+        }
+        true
+    }
+
+    fn reuse(&mut self, previous: &dyn Layout) -> bool {
+        let previous: &dyn Any = previous;
+        let Some(previous) = previous.downcast_ref::<Self>() else {
+            return false;
+        };
+
+        // Without a key there's no way to tell if the content is the same
+        let unchanged = self.key.is_some()
+            && self.key == previous.key
+            && self.intrinsic_size == previous.intrinsic_size
+            && previous.errors.is_empty();
+        if unchanged {
+            self.size = previous.size;
+            self.position = previous.position;
+            self.constraints = previous.constraints;
+            self.measured = previous.measured;
+        }
+        unchanged
+    }
+
+    fn baseline(&self) -> Option<f32> {
+        self.baseline
+    }
+
+    fn min_size_changed(&self) -> bool {
+        self.changed
+    }
+
+    fn solve_min_constraints(&mut self) -> (f32, f32) {
+        // Use the size from the last pass, if there is one, so that
+        // the parents fit the wrapped content.
+        let size = self.measured.unwrap_or_else(|| self.measure(None));
+
+        self.constraints.min_width = match self.intrinsic_size.width {
+            BoxSizing::Fixed(width) => width,
+            BoxSizing::Shrink => size.width,
+            BoxSizing::Flex(_) => 0.0,
+        };
+
+        self.constraints.min_height = match self.intrinsic_size.height {
+            BoxSizing::Fixed(height) => height,
+            BoxSizing::Shrink | BoxSizing::Flex(_) => size.height,
+        };
+
+        (self.constraints.min_width, self.constraints.min_height)
+    }
+
+    fn solve_max_constraints(&mut self, space: Size) {
+        let width = match self.intrinsic_size.width {
+            BoxSizing::Fixed(width) => width,
+            BoxSizing::Flex(_) => self.constraints.max_width,
+            BoxSizing::Shrink => {
+                // Parents that don't know their space, or don't set the
+                // max width, give a width of zero
+                [space.width, self.constraints.max_width]
+                    .into_iter()
+                    .filter(|width| *width > 0.0)
+                    .fold(self.measure(None).width, f32::min)
+            }
+        };
+
+        let size = Size::new(width, self.measure(Some(width)).height);
+        let previous = self.measured.unwrap_or_else(|| self.measure(None));
+        self.changed = previous != size;
+        self.measured = Some(size);
+
+        if self.intrinsic_size.width == BoxSizing::Shrink {
+            self.constraints.min_width = width;
+            self.constraints.max_width = width;
+        }
+
+        if self.intrinsic_size.height == BoxSizing::Shrink {
+            self.constraints.min_height = size.height;
+            self.constraints.max_height = size.height;
+        }
+    }
+
+    fn update_size(&mut self) {
+        match self.intrinsic_size.width {
+            BoxSizing::Flex(_) => {
+                self.size.width = self.constraints.max_width;
+            }
+            BoxSizing::Shrink => {
+                self.size.width = self.constraints.min_width;
+            }
+            BoxSizing::Fixed(width) => {
+                self.size.width = width;
+            }
+        }
+
+        match self.intrinsic_size.height {
+            BoxSizing::Flex(_) => {
+                self.size.height = self.constraints.max_height;
+            }
+            BoxSizing::Shrink => {
+                self.size.height = self.constraints.min_height;
+            }
+            BoxSizing::Fixed(height) => {
+                self.size.height = height;
+            }
+        }
+    }
+
+    fn position_children(&mut self) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{VerticalLayout, solve_layout};
+
+    /// Text like content, 10 units wide per word and 20 units tall per line.
+    fn words(count: u32) -> MeasureLayout {
+        MeasureLayout::new(move |width| {
+            let per_line = width.map_or(count, |w| ((w / 10.0) as u32).max(1));
+            let lines = count.div_ceil(per_line);
+            Size::new(per_line.min(count) as f32 * 10.0, lines as f32 * 20.0)
+        })
+    }
+
+    #[test]
+    fn shrink_fits_content() {
+        let mut layout = words(5);
+        solve_layout(&mut layout, Size::new(500.0, 500.0));
+        assert_eq!(layout.size(), Size::new(50.0, 20.0));
+        assert!(!layout.min_size_changed());
+    }
+
+    #[test]
+    fn wraps_to_available_width() {
+        let mut layout = words(10);
+        solve_layout(&mut layout, Size::new(30.0, 500.0));
+        assert_eq!(layout.size(), Size::new(30.0, 80.0));
+    }
+
+    #[test]
+    fn fixed_width() {
+        let mut layout = words(10);
+        layout.intrinsic_size.width = BoxSizing::Fixed(50.0);
+        solve_layout(&mut layout, Size::new(500.0, 500.0));
+        assert_eq!(layout.size(), Size::new(50.0, 40.0));
+    }
+
+    #[test]
+    fn parent_fits_wrapped_height() {
+        let mut root = VerticalLayout::new();
+        root.intrinsic_size.width = BoxSizing::Flex(1);
+        root.add_child(words(10));
+        root.add_child(words(1));
+
+        solve_layout(&mut root, Size::new(40.0, 500.0));
+
+        assert_eq!(root.children[0].size(), Size::new(40.0, 60.0));
+        assert_eq!(root.children[1].position().y, 60.0);
+        assert_eq!(root.size(), Size::new(40.0, 80.0));
+    }
+
+    #[test]
+    fn shrink_parent_fits_max_width() {
+        let mut inner = VerticalLayout::new();
+        inner.add_child(words(10));
+        let mut root = VerticalLayout::new();
+        root.add_child(inner);
+
+        solve_layout(&mut root, Size::new(40.0, 500.0));
+
+        assert_eq!(root.children[0].children()[0].size(), Size::new(40.0, 60.0));
+        assert_eq!(root.size(), Size::new(40.0, 60.0));
+    }
+
+    #[test]
+    fn mark_dirty_keeps_measurement_once() {
+        let mut layout = words(10);
+        layout.set_max_width(30.0);
+        layout.solve_min_constraints();
+        layout.solve_max_constraints(Size::new(30.0, 500.0));
+        assert!(layout.min_size_changed());
+
+        // Marked by the solve, the wrapped size is kept for the parents
+        layout.mark_dirty(layout.id);
+        assert_eq!(layout.solve_min_constraints(), (30.0, 80.0));
+
+        // Marked because the content changed
+        layout.mark_dirty(layout.id);
+        assert!(layout.measured.is_none());
+    }
+}
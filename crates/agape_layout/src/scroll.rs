@@ -0,0 +1,666 @@
+use crate::{
+    BoxConstraints, BoxSizing, Direction, EmptyLayout, IntrinsicSize, Layout, LayoutCache,
+    LayoutError, LayoutIter, Overflow,
+};
+use agape_core::{CornerRadius, GlobalId, Position, Size};
+use std::any::Any;
+
+/// The axes that a [`ScrollLayout`] scrolls on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScrollAxis {
+    /// Scroll on the `x-axis`.
+    Horizontal,
+    /// Scroll on the `y-axis`.
+    #[default]
+    Vertical,
+    /// Scroll on both axes.
+    Both,
+}
+
+impl ScrollAxis {
+    /// Returns `true` if the content scrolls on the `x-axis`.
+    pub fn horizontal(&self) -> bool {
+        matches!(self, Self::Horizontal | Self::Both)
+    }
+
+    /// Returns `true` if the content scrolls on the `y-axis`.
+    pub fn vertical(&self) -> bool {
+        matches!(self, Self::Vertical | Self::Both)
+    }
+}
+
+/// A [`Layout`] that shows its child through a viewport, the child can be larger
+/// than the viewport on the axes that scroll.
+///
+/// The scroll offset is how far the content has been scrolled, and is clamped to
+/// the bounds of the content so that the content never scrolls out of view.
+///
+/// On an axis that scrolls, the size of the viewport doesn't depend on the content,
+/// so the viewport is usually given a [`BoxSizing::Fixed`] or [`BoxSizing::Flex`]
+/// size. A [`BoxSizing::Shrink`] viewport is the size of the content, limited to
+/// the space available.
+///
+/// # Example
+/// ```
+/// use agape_layout::{EmptyLayout, IntrinsicSize, Layout, Position, ScrollLayout, Size, solve_layout};
+///
+/// let content = EmptyLayout {
+///     intrinsic_size: IntrinsicSize::fixed(100.0, 1000.0),
+///     ..Default::default()
+/// };
+/// let mut layout = ScrollLayout::new(Box::new(content));
+/// layout.intrinsic_size = IntrinsicSize::fixed(100.0, 200.0);
+///
+/// solve_layout(&mut layout, Size::unit(500.0));
+/// assert_eq!(layout.max_offset(), Position::new(0.0, 800.0));
+///
+/// layout.scroll(0.0, 1500.0);
+/// solve_layout(&mut layout, Size::unit(500.0));
+/// assert_eq!(layout.offset(), Position::new(0.0, 800.0));
+/// assert_eq!(layout.child.position().y, -800.0);
+/// ```
+#[derive(Debug)]
+pub struct ScrollLayout {
+    pub id: GlobalId,
+    pub size: Size,
+    pub position: Position,
+    pub intrinsic_size: IntrinsicSize,
+    pub constraints: BoxConstraints,
+    pub axis: ScrollAxis,
+    pub child: Box<dyn Layout>,
+    pub cache: LayoutCache,
+    /// The [`Direction`] of the layout, `None` to use the parent's direction.
+    pub direction: Option<Direction>,
+    /// The [`Direction`] of the parent, set when the layout is solved.
+    pub inherited_direction: Direction,
+    /// The radius of the corners that the content is clipped to.
+    pub corner_radius: CornerRadius,
+    pub errors: Vec<LayoutError>,
+    offset: Position,
+    /// The size of the content, `None` until the layout is solved.
+    content_size: Option<Size>,
+    /// The size of the viewport from the last time the max constraints were solved.
+    viewport: Option<Size>,
+    changed: bool,
+}
+
+impl Default for ScrollLayout {
+    fn default() -> Self {
+        Self {
+            id: GlobalId::new(),
+            size: Size::default(),
+            position: Position::default(),
+            intrinsic_size: IntrinsicSize::default(),
+            constraints: BoxConstraints::default(),
+            axis: ScrollAxis::default(),
+            child: Box::new(EmptyLayout::default()),
+            cache: LayoutCache::new(),
+            direction: None,
+            inherited_direction: Direction::default(),
+            corner_radius: CornerRadius::default(),
+            errors: vec![],
+            offset: Position::default(),
+            content_size: None,
+            viewport: None,
+            changed: false,
+        }
+    }
+}
+
+impl ScrollLayout {
+    pub fn new(child: Box<dyn Layout>) -> Self {
+        Self {
+            child,
+            ..Default::default()
+        }
+    }
+
+    /// Set the axes that the layout scrolls on.
+    pub fn axis(mut self, axis: ScrollAxis) -> Self {
+        self.axis = axis;
+        self
+    }
+
+    /// Scroll the content by `x` and `y`, positive values scroll towards the
+    /// end of the content.
+    pub fn scroll(&mut self, x: f32, y: f32) {
+        let offset = Position::new(self.offset.x + x, self.offset.y + y);
+        self.scroll_to(offset);
+    }
+
+    /// Scroll to the `offset` from the start of the content.
+    pub fn scroll_to(&mut self, offset: Position) {
+        self.offset = offset;
+        self.clamp_offset();
+        self.cache.invalidate();
+    }
+
+    /// Scroll the least amount needed for the [`Layout`] with the `id`
+    /// to be in view. Returns `false` if the layout isn't in the content.
+    ///
+    /// This uses the position of the layout from the last time the layout
+    /// was solved.
+    pub fn scroll_into_view(&mut self, id: GlobalId) -> bool {
+        let Some(layout) = self.child.get(id) else {
+            return false;
+        };
+
+        // The position relative to the start of the content, which is on
+        // the right in right to left layouts
+        let content = self.child.position();
+        let mut start = Position::new(
+            layout.position().x - content.x,
+            layout.position().y - content.y,
+        );
+        if self.direction().is_rtl() {
+            start.x = self.child.size().width - start.x - layout.size().width;
+        }
+        let size = layout.size();
+        let viewport = self.viewport_size();
+
+        let scroll_into = |offset: f32, start: f32, length: f32, viewport: f32| {
+            if start < offset {
+                start
+            } else if start + length > offset + viewport {
+                (start + length - viewport).min(start)
+            } else {
+                offset
+            }
+        };
+
+        let x = scroll_into(self.offset.x, start.x, size.width, viewport.width);
+        let y = scroll_into(self.offset.y, start.y, size.height, viewport.height);
+        self.scroll_to(Position::new(x, y));
+        true
+    }
+
+    /// Get the current scroll offset.
+    pub fn offset(&self) -> Position {
+        self.offset
+    }
+
+    /// Get the largest the scroll offset can be, which is how far the content
+    /// extends past the viewport.
+    pub fn max_offset(&self) -> Position {
+        let content = self.content_size();
+        let viewport = self.viewport_size();
+        Position::new(
+            (content.width - viewport.width).max(0.0),
+            (content.height - viewport.height).max(0.0),
+        )
+    }
+
+    /// Get the size of the visible area.
+    pub fn viewport_size(&self) -> Size {
+        self.size
+    }
+
+    /// Get the size of the content.
+    pub fn content_size(&self) -> Size {
+        self.content_size.unwrap_or_default()
+    }
+
+    /// Keep the offset within the content. Until the size of the content is
+    /// known the offset is only kept from going before the start, and it's
+    /// clamped to the end once the layout is solved.
+    fn clamp_offset(&mut self) {
+        let max = match self.content_size {
+            Some(_) => self.max_offset(),
+            None => Position::unit(f32::INFINITY),
+        };
+        self.offset.x = if self.axis.horizontal() {
+            self.offset.x.clamp(0.0, max.x)
+        } else {
+            0.0
+        };
+        self.offset.y = if self.axis.vertical() {
+            self.offset.y.clamp(0.0, max.y)
+        } else {
+            0.0
+        };
+    }
+}
+
+impl Layout for ScrollLayout {
+    fn id(&self) -> GlobalId {
+        self.id
+    }
+
+    fn size(&self) -> Size {
+        self.size
+    }
+
+    fn set_x(&mut self, x: f32) {
+        self.position.x = x;
+    }
+
+    fn set_y(&mut self, y: f32) {
+        self.position.y = y;
+    }
+
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn children(&self) -> &[Box<dyn Layout>] {
+        std::slice::from_ref(&self.child)
+    }
+
+    fn constraints(&self) -> BoxConstraints {
+        self.constraints
+    }
+
+    fn intrinsic_size(&self) -> IntrinsicSize {
+        self.intrinsic_size
+    }
+
+    fn set_max_height(&mut self, height: f32) {
+        self.constraints.max_height = height;
+    }
+
+    fn set_max_width(&mut self, width: f32) {
+        self.constraints.max_width = width;
+    }
+
+    fn set_min_height(&mut self, height: f32) {
+        self.constraints.min_height = height;
+    }
+
+    fn set_min_width(&mut self, width: f32) {
+        self.constraints.min_width = width;
+    }
+
+    fn collect_errors(&mut self) -> Vec<LayoutError> {
+        self.errors
+            .iter()
+            .cloned()
+            .chain(self.child.collect_errors())
+            .collect::<Vec<_>>()
+    }
+
+    fn iter(&self) -> LayoutIter<'_> {
+        LayoutIter { stack: vec![self] }
+    }
+
+    fn min_size_changed(&self) -> bool {
+        self.changed
+    }
+
+    fn mark_dirty(&mut self, id: GlobalId) -> bool {
+        let found = self.id == id || self.child.mark_dirty(id);
+        if found {
+            self.cache.invalidate();
+        }
+        found
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.cache.is_dirty()
+    }
+
+    fn overflow_mode(&self) -> Overflow {
+        Overflow::Scroll
+    }
+
+    fn corner_radius(&self) -> CornerRadius {
+        self.corner_radius
+    }
+
+    fn scroll_offset(&self) -> Position {
+        self.offset
+    }
+
+    fn direction(&self) -> Direction {
+        self.direction.unwrap_or(self.inherited_direction)
+    }
+
+    fn inherit_direction(&mut self, direction: Direction) {
+        if self.direction.is_none() && self.inherited_direction != direction {
+            self.inherited_direction = direction;
+            self.cache.invalidate();
+        }
+    }
+
+    fn reuse(&mut self, previous: &dyn Layout) -> bool {
+        let previous: &dyn Any = previous;
+        let Some(previous) = previous.downcast_ref::<Self>() else {
+            return false;
+        };
+
+        let reused = self.child.reuse(previous.child.as_ref());
+        let unchanged = self.axis == previous.axis
+            && self.intrinsic_size == previous.intrinsic_size
+            && self.offset == previous.offset
+            && self.direction == previous.direction;
+        if !reused || !unchanged || previous.is_dirty() || !previous.errors.is_empty() {
+            return false;
+        }
+
+        self.size = previous.size;
+        self.position = previous.position;
+        self.constraints = previous.constraints;
+        self.cache = previous.cache;
+        self.content_size = previous.content_size;
+        self.viewport = previous.viewport;
+        true
+    }
+
+    fn solve_min_constraints(&mut self) -> (f32, f32) {
+        if let Some((min_width, min_height)) = self.cache.min_size() {
+            // Restore the min constraints in case the parent changed them
+            self.constraints.min_width = min_width;
+            self.constraints.min_height = min_height;
+            return (min_width, min_height);
+        }
+
+        let (min_width, min_height) = self.child.solve_min_constraints();
+        // A shrinking viewport is as large as the content, unless it was
+        // limited to a smaller size by the space available.
+        let viewport = self.viewport.unwrap_or(Size::new(min_width, min_height));
+
+        self.constraints.min_width = match self.intrinsic_size.width {
+            BoxSizing::Fixed(width) => width,
+            BoxSizing::Shrink if self.axis.horizontal() => viewport.width.min(min_width),
+            BoxSizing::Flex(_) if self.axis.horizontal() => 0.0,
+            BoxSizing::Shrink | BoxSizing::Flex(_) => min_width,
+        };
+
+        self.constraints.min_height = match self.intrinsic_size.height {
+            BoxSizing::Fixed(height) => height,
+            BoxSizing::Shrink if self.axis.vertical() => viewport.height.min(min_height),
+            BoxSizing::Flex(_) if self.axis.vertical() => 0.0,
+            BoxSizing::Shrink | BoxSizing::Flex(_) => min_height,
+        };
+
+        let min_size = (self.constraints.min_width, self.constraints.min_height);
+        self.cache.set_min_size(min_size);
+        min_size
+    }
+
+    fn solve_max_constraints(&mut self, space: Size) {
+        if self.cache.reuse(self.constraints, space) {
+            return;
+        }
+
+        // Restore the child's min constraints which the last solve may have changed
+        let (content_width, content_height) = self.child.solve_min_constraints();
+
+        // Parents that don't know their space, or don't set the max
+        // constraints, give a size of zero
+        let limit = |content: f32, space: f32, max: f32| {
+            [space, max]
+                .into_iter()
+                .filter(|length| *length > 0.0)
+                .fold(content, f32::min)
+        };
+
+        let width = match self.intrinsic_size.width {
+            BoxSizing::Fixed(width) => width,
+            BoxSizing::Flex(_) => self.constraints.max_width,
+            BoxSizing::Shrink if self.axis.horizontal() => {
+                limit(content_width, space.width, self.constraints.max_width)
+            }
+            BoxSizing::Shrink => self.constraints.min_width,
+        };
+
+        let height = match self.intrinsic_size.height {
+            BoxSizing::Fixed(height) => height,
+            BoxSizing::Flex(_) => self.constraints.max_height,
+            BoxSizing::Shrink if self.axis.vertical() => {
+                limit(content_height, space.height, self.constraints.max_height)
+            }
+            BoxSizing::Shrink => self.constraints.min_height,
+        };
+
+        self.changed = false;
+        if self.intrinsic_size.width == BoxSizing::Shrink && self.axis.horizontal() {
+            self.changed |= width != self.constraints.min_width;
+            self.constraints.min_width = width;
+            self.constraints.max_width = width;
+        }
+        if self.intrinsic_size.height == BoxSizing::Shrink && self.axis.vertical() {
+            self.changed |= height != self.constraints.min_height;
+            self.constraints.min_height = height;
+            self.constraints.max_height = height;
+        }
+        self.viewport = Some(Size::new(width, height));
+
+        // The content can be larger than the viewport on the axes that scroll
+        let available = Size::new(
+            if self.axis.horizontal() {
+                width.max(content_width)
+            } else {
+                width
+            },
+            if self.axis.vertical() {
+                height.max(content_height)
+            } else {
+                height
+            },
+        );
+
+        match self.child.intrinsic_size().width {
+            BoxSizing::Flex(_) => self.child.set_max_width(available.width),
+            BoxSizing::Fixed(width) => self.child.set_max_width(width),
+            BoxSizing::Shrink => {}
+        }
+
+        match self.child.intrinsic_size().height {
+            BoxSizing::Flex(_) => self.child.set_max_height(available.height),
+            BoxSizing::Fixed(height) => self.child.set_max_height(height),
+            BoxSizing::Shrink => {}
+        }
+
+        self.child.solve_max_constraints(available);
+    }
+
+    fn update_size(&mut self) {
+        if self.cache.is_reused() {
+            return;
+        }
+
+        match self.intrinsic_size.width {
+            BoxSizing::Flex(_) => {
+                self.size.width = self.constraints.max_width;
+            }
+            BoxSizing::Shrink => {
+                self.size.width = self.constraints.min_width;
+            }
+            BoxSizing::Fixed(width) => {
+                self.size.width = width;
+            }
+        }
+
+        match self.intrinsic_size.height {
+            BoxSizing::Flex(_) => {
+                self.size.height = self.constraints.max_height;
+            }
+            BoxSizing::Shrink => {
+                self.size.height = self.constraints.min_height;
+            }
+            BoxSizing::Fixed(height) => {
+                self.size.height = height;
+            }
+        }
+
+        self.child.update_size();
+        self.content_size = Some(self.child.size());
+        self.clamp_offset();
+    }
+
+    fn position_children(&mut self) {
+        if self.cache.reuse_position(self.position) {
+            return;
+        }
+
+        // The content is clipped to the viewport, so it's never out of bounds
+        self.child.set_x(self.position.x - self.offset.x);
+        self.child.set_y(self.position.y - self.offset.y);
+
+        let direction = self.direction();
+        direction.place(self.child.as_mut(), self.position.x, self.size.width);
+        self.child.inherit_direction(direction);
+        self.child.position_children();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{HorizontalLayout, VerticalLayout, solve_layout};
+
+    fn content(width: f32, height: f32) -> Box<dyn Layout> {
+        Box::new(EmptyLayout {
+            intrinsic_size: IntrinsicSize::fixed(width, height),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn content_larger_than_viewport() {
+        let mut layout = ScrollLayout::new(content(100.0, 1000.0));
+        layout.intrinsic_size = IntrinsicSize::fixed(100.0, 200.0);
+        solve_layout(&mut layout, Size::unit(500.0));
+
+        assert_eq!(layout.viewport_size(), Size::new(100.0, 200.0));
+        assert_eq!(layout.content_size(), Size::new(100.0, 1000.0));
+        assert_eq!(layout.max_offset(), Position::new(0.0, 800.0));
+    }
+
+    #[test]
+    fn clamp_offset() {
+        let mut layout = ScrollLayout::new(content(100.0, 1000.0));
+        layout.intrinsic_size = IntrinsicSize::fixed(100.0, 200.0);
+        solve_layout(&mut layout, Size::unit(500.0));
+
+        layout.scroll(0.0, -50.0);
+        assert_eq!(layout.offset(), Position::unit(0.0));
+        layout.scroll(0.0, 5000.0);
+        assert_eq!(layout.offset(), Position::new(0.0, 800.0));
+    }
+
+    #[test]
+    fn offset_set_before_solving() {
+        let mut layout = ScrollLayout::new(content(100.0, 1000.0));
+        layout.intrinsic_size = IntrinsicSize::fixed(100.0, 200.0);
+        layout.scroll_to(Position::new(20.0, 300.0));
+        // The x axis doesn't scroll
+        assert_eq!(layout.offset(), Position::new(0.0, 300.0));
+
+        solve_layout(&mut layout, Size::unit(500.0));
+        assert_eq!(layout.offset(), Position::new(0.0, 300.0));
+        assert_eq!(layout.child.position(), Position::new(0.0, -300.0));
+    }
+
+    #[test]
+    fn scroll_both_axes() {
+        let mut layout = ScrollLayout::new(content(1000.0, 1000.0)).axis(ScrollAxis::Both);
+        layout.intrinsic_size = IntrinsicSize::fill();
+        solve_layout(&mut layout, Size::unit(400.0));
+        layout.scroll(250.0, 100.0);
+        solve_layout(&mut layout, Size::unit(400.0));
+
+        assert_eq!(layout.max_offset(), Position::unit(600.0));
+        assert_eq!(layout.child.position(), Position::new(-250.0, -100.0));
+    }
+
+    #[test]
+    fn horizontal_axis_only() {
+        let mut layout = ScrollLayout::new(content(1000.0, 1000.0)).axis(ScrollAxis::Horizontal);
+        layout.intrinsic_size = IntrinsicSize::fixed(200.0, 200.0);
+        solve_layout(&mut layout, Size::unit(400.0));
+        layout.scroll(100.0, 100.0);
+
+        assert_eq!(layout.offset(), Position::new(100.0, 0.0));
+    }
+
+    #[test]
+    fn flex_content_fills_viewport() {
+        let mut child = VerticalLayout::new();
+        child.intrinsic_size = IntrinsicSize::fill();
+        child.add_child(EmptyLayout {
+            intrinsic_size: IntrinsicSize::fixed(50.0, 50.0),
+            ..Default::default()
+        });
+
+        let mut layout = ScrollLayout::new(Box::new(child));
+        layout.intrinsic_size = IntrinsicSize::fill();
+        solve_layout(&mut layout, Size::unit(400.0));
+
+        assert_eq!(layout.content_size(), Size::unit(400.0));
+        assert_eq!(layout.max_offset(), Position::unit(0.0));
+    }
+
+    #[test]
+    fn flex_viewport_in_parent() {
+        let mut layout = ScrollLayout::new(content(100.0, 1000.0));
+        layout.intrinsic_size.width = BoxSizing::Fixed(100.0);
+        layout.intrinsic_size.height = BoxSizing::Flex(1);
+
+        let mut root = HorizontalLayout::new();
+        root.intrinsic_size = IntrinsicSize::fill();
+        root.add_child(layout);
+        solve_layout(&mut root, Size::unit(400.0));
+
+        assert_eq!(root.children[0].size(), Size::new(100.0, 400.0));
+        assert!(root.collect_errors().is_empty());
+    }
+
+    #[test]
+    fn shrink_viewport_is_limited_by_space() {
+        let mut layout = ScrollLayout::new(content(100.0, 1000.0));
+        solve_layout(&mut layout, Size::unit(400.0));
+        assert_eq!(layout.viewport_size(), Size::new(100.0, 400.0));
+
+        let mut layout = ScrollLayout::new(content(100.0, 100.0));
+        solve_layout(&mut layout, Size::unit(400.0));
+        assert_eq!(layout.viewport_size(), Size::new(100.0, 100.0));
+    }
+
+    #[test]
+    fn scroll_into_view() {
+        let mut list = VerticalLayout::new();
+        for _ in 0..10 {
+            list.add_child(EmptyLayout {
+                intrinsic_size: IntrinsicSize::fixed(100.0, 100.0),
+                ..Default::default()
+            });
+        }
+        let item = list.children[5].id();
+
+        let mut layout = ScrollLayout::new(Box::new(list));
+        layout.intrinsic_size = IntrinsicSize::fixed(100.0, 200.0);
+        solve_layout(&mut layout, Size::unit(500.0));
+
+        assert!(layout.scroll_into_view(item));
+        assert_eq!(layout.offset(), Position::new(0.0, 400.0));
+        solve_layout(&mut layout, Size::unit(500.0));
+        assert_eq!(layout.child.get(item).unwrap().position().y, 100.0);
+
+        assert!(!layout.scroll_into_view(GlobalId::new()));
+    }
+
+    #[test]
+    fn scroll_to_before_solve() {
+        let mut layout = ScrollLayout::new(content(100.0, 1000.0));
+        layout.intrinsic_size = IntrinsicSize::fixed(100.0, 200.0);
+        layout.scroll_to(Position::new(0.0, -50.0));
+        assert_eq!(layout.offset(), Position::default());
+
+        layout.scroll_to(Position::new(0.0, 5000.0));
+        solve_layout(&mut layout, Size::unit(500.0));
+        assert_eq!(layout.offset(), Position::new(0.0, 800.0));
+        assert_eq!(layout.child.position().y, -800.0);
+    }
+
+    #[test]
+    fn scroll_offset_in_layout_tree() {
+        let mut layout = ScrollLayout::new(content(100.0, 1000.0));
+        layout.intrinsic_size = IntrinsicSize::fixed(100.0, 200.0);
+        layout.scroll(0.0, 300.0);
+
+        let tree = solve_layout(&mut layout, Size::unit(500.0));
+        let node = tree.get(layout.id).unwrap();
+        assert_eq!(node.scroll_offset(), Position::new(0.0, 300.0));
+    }
+}
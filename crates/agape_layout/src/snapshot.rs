@@ -0,0 +1,438 @@
+use crate::{BoxConstraints, BoxSizing, IntrinsicSize, Layout, LayoutError, OverflowAxis};
+use agape_core::{GlobalId, Position, Size};
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter, Write};
+use std::path::Path;
+
+/// Set this environment variable to write snapshot files in
+/// [`LayoutSnapshot::assert_file`] instead of comparing them.
+pub const UPDATE_SNAPSHOTS: &str = "AGAPE_UPDATE_SNAPSHOTS";
+
+/// A stable text copy of a solved layout tree, used to review layout changes
+/// as diffs in tests and to attach layouts to bug reports.
+///
+/// Ids are random, so nodes are labelled by their index in the tree, `#0` is the
+/// root and the rest are numbered depth first. Use [`LayoutSnapshot::label`] to
+/// give a node a readable name.
+///
+/// # Example
+/// ```
+/// use agape_layout::{EmptyLayout, IntrinsicSize, LayoutSnapshot, Size, VerticalLayout, solve_layout};
+///
+/// let mut root = VerticalLayout::new();
+/// root.add_child(EmptyLayout {
+///     intrinsic_size: IntrinsicSize::fixed(50.0, 20.0),
+///     ..Default::default()
+/// });
+///
+/// let tree = solve_layout(&mut root, Size::new(500.0, 500.0));
+/// LayoutSnapshot::new(&root, tree.errors()).assert_eq(
+///     "
+///     VerticalLayout #0 size=50x20 position=(0, 0) intrinsic=shrink x shrink min=50x20 max=500x500
+///       EmptyLayout #1 size=50x20 position=(0, 0) intrinsic=50 x 20 min=50x20 max=50x20
+///     ",
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutSnapshot {
+    root: SnapshotNode,
+    indices: HashMap<GlobalId, usize>,
+    labels: HashMap<GlobalId, String>,
+    errors: Vec<LayoutError>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SnapshotNode {
+    id: GlobalId,
+    name: &'static str,
+    intrinsic_size: IntrinsicSize,
+    constraints: BoxConstraints,
+    size: Size,
+    position: Position,
+    children: Vec<SnapshotNode>,
+}
+
+impl SnapshotNode {
+    fn new(layout: &dyn Layout, indices: &mut HashMap<GlobalId, usize>) -> Self {
+        indices.insert(layout.id(), indices.len());
+        Self {
+            id: layout.id(),
+            name: layout.name(),
+            intrinsic_size: layout.intrinsic_size(),
+            constraints: layout.constraints(),
+            size: layout.size(),
+            position: layout.position(),
+            children: layout
+                .children()
+                .iter()
+                .map(|child| Self::new(child.as_ref(), indices))
+                .collect(),
+        }
+    }
+}
+
+impl LayoutSnapshot {
+    /// Take a snapshot of a layout tree that has already been solved, along with
+    /// the errors from solving it.
+    pub fn new(root: &dyn Layout, errors: &[LayoutError]) -> Self {
+        let mut indices = HashMap::new();
+        let root = SnapshotNode::new(root, &mut indices);
+        Self {
+            root,
+            indices,
+            labels: HashMap::new(),
+            errors: errors.to_vec(),
+        }
+    }
+
+    /// Use `label` instead of the index for the node with the `id`.
+    pub fn label(mut self, id: GlobalId, label: &str) -> Self {
+        self.labels.insert(id, label.to_owned());
+        self
+    }
+
+    /// Get the label of the node with the `id`, ids that aren't in the tree
+    /// are written as is.
+    fn node_label(&self, id: GlobalId) -> String {
+        if let Some(label) = self.labels.get(&id) {
+            return label.clone();
+        }
+        match self.indices.get(&id) {
+            Some(index) => format!("#{index}"),
+            None => format!("id:{id}"),
+        }
+    }
+
+    fn describe_error(&self, error: &LayoutError) -> String {
+        match error {
+            LayoutError::OutOfBounds {
+                parent_id,
+                child_id,
+            } => format!(
+                "{} is out of {}'s bounds",
+                self.node_label(*child_id),
+                self.node_label(*parent_id)
+            ),
+            LayoutError::Overflow { id, axis } => {
+                let axis = match axis {
+                    OverflowAxis::MainAxis => "main axis",
+                    OverflowAxis::CrossAxis => "cross axis",
+                };
+                format!("{}'s children overflow on the {axis}", self.node_label(*id))
+            }
+        }
+    }
+
+    /// Write the snapshot as JSON, nodes are nested in their parent's `children`
+    /// and unbounded constraints are `null`.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\n  \"root\": ");
+        self.write_json_node(&mut json, &self.root, 1);
+        json.push_str(",\n  \"errors\": [");
+        for (i, error) in self.errors.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            let node = json_string(&self.node_label(error.id()));
+            let message = json_string(&self.describe_error(error));
+            let _ = write!(
+                json,
+                "{separator}\n    {{ \"node\": {node}, \"message\": {message} }}"
+            );
+        }
+        if !self.errors.is_empty() {
+            json.push_str("\n  ");
+        }
+        json.push_str("]\n}\n");
+        json
+    }
+
+    fn write_json_node(&self, json: &mut String, node: &SnapshotNode, depth: usize) {
+        let indent = "  ".repeat(depth + 1);
+        let IntrinsicSize { width, height } = node.intrinsic_size;
+        let constraints = node.constraints;
+        let (position, size) = (node.position, node.size);
+        let _ = write!(
+            json,
+            "{{\n\
+            {indent}\"type\": {},\n\
+            {indent}\"label\": {},\n\
+            {indent}\"intrinsic_size\": {{ \"width\": {}, \"height\": {} }},\n\
+            {indent}\"constraints\": {{ \"min_width\": {}, \"min_height\": {}, \"max_width\": {}, \"max_height\": {} }},\n\
+            {indent}\"bounds\": {{ \"x\": {}, \"y\": {}, \"width\": {}, \"height\": {} }},\n\
+            {indent}\"children\": [",
+            json_string(node.name),
+            json_string(&self.node_label(node.id)),
+            json_string(&sizing(width)),
+            json_string(&sizing(height)),
+            json_number(constraints.min_width),
+            json_number(constraints.min_height),
+            json_number(constraints.max_width),
+            json_number(constraints.max_height),
+            json_number(position.x),
+            json_number(position.y),
+            json_number(size.width),
+            json_number(size.height),
+        );
+        for (i, child) in node.children.iter().enumerate() {
+            let separator = if i == 0 { "" } else { "," };
+            let _ = write!(json, "{separator}\n{indent}  ");
+            self.write_json_node(json, child, depth + 2);
+        }
+        if !node.children.is_empty() {
+            let _ = write!(json, "\n{indent}");
+        }
+        let _ = write!(json, "]\n{}}}", "  ".repeat(depth));
+    }
+
+    fn write_node(&self, f: &mut Formatter<'_>, node: &SnapshotNode, depth: usize) -> fmt::Result {
+        let IntrinsicSize { width, height } = node.intrinsic_size;
+        let constraints = node.constraints;
+        let (position, size) = (node.position, node.size);
+        writeln!(
+            f,
+            "{}{} {} size={}x{} position=({}, {}) intrinsic={} x {} min={}x{} max={}x{}",
+            "  ".repeat(depth),
+            node.name,
+            self.node_label(node.id),
+            number(size.width),
+            number(size.height),
+            number(position.x),
+            number(position.y),
+            sizing(width),
+            sizing(height),
+            number(constraints.min_width),
+            number(constraints.min_height),
+            number(constraints.max_width),
+            number(constraints.max_height),
+        )?;
+        for child in &node.children {
+            self.write_node(f, child, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Assert that the snapshot matches `expected`, panicking with a line diff
+    /// if it doesn't. Leading and trailing blank lines, trailing whitespace and the
+    /// indentation shared by every line are ignored, so the expected snapshot can be
+    /// an indented string literal.
+    #[track_caller]
+    pub fn assert_eq(&self, expected: &str) {
+        let actual = self.to_string();
+        let expected = normalize(expected);
+        if normalize(&actual) != expected {
+            panic!(
+                "layout snapshot doesn't match, - expected + actual:\n{}",
+                diff(&expected, &normalize(&actual))
+            );
+        }
+    }
+
+    /// Assert that the snapshot matches the one saved at `path`. If the
+    /// [`UPDATE_SNAPSHOTS`] environment variable is set the snapshot is written
+    /// to `path` instead.
+    #[track_caller]
+    pub fn assert_file(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let actual = self.to_string();
+        if std::env::var_os(UPDATE_SNAPSHOTS).is_some() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).unwrap();
+            }
+            std::fs::write(path, actual).unwrap();
+            return;
+        }
+
+        let Ok(expected) = std::fs::read_to_string(path) else {
+            panic!(
+                "missing layout snapshot {}, run again with {UPDATE_SNAPSHOTS}=1 to create it",
+                path.display()
+            );
+        };
+        if normalize(&actual) != normalize(&expected) {
+            panic!(
+                "layout snapshot {} doesn't match, - expected + actual:\n{}\n\
+                run again with {UPDATE_SNAPSHOTS}=1 to update it",
+                path.display(),
+                diff(&normalize(&expected), &normalize(&actual))
+            );
+        }
+    }
+}
+
+impl Display for LayoutSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.write_node(f, &self.root, 0)?;
+        if !self.errors.is_empty() {
+            writeln!(f, "errors:")?;
+            for error in &self.errors {
+                writeln!(f, "  {}", self.describe_error(error))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Format a number with at most two decimal places, so that snapshots
+/// don't change with float noise.
+fn number(value: f32) -> String {
+    if value.is_infinite() {
+        return if value > 0.0 { "inf" } else { "-inf" }.to_owned();
+    }
+    let value = format!("{value:.2}");
+    let value = value.trim_end_matches('0').trim_end_matches('.');
+    match value {
+        "-0" => "0".to_owned(),
+        value => value.to_owned(),
+    }
+}
+
+fn sizing(sizing: BoxSizing) -> String {
+    match sizing {
+        BoxSizing::Fixed(size) => number(size),
+        BoxSizing::Shrink => "shrink".to_owned(),
+        BoxSizing::Flex(factor) => format!("flex({factor})"),
+    }
+}
+
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        number(value)
+    } else {
+        "null".to_owned()
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Remove blank lines at the start and end, trailing whitespace and the
+/// indentation shared by every line.
+fn normalize(snapshot: &str) -> String {
+    let lines: Vec<&str> = snapshot.lines().map(str::trim_end).collect();
+    let start = lines.iter().position(|line| !line.is_empty());
+    let end = lines.iter().rposition(|line| !line.is_empty());
+    let (Some(start), Some(end)) = (start, end) else {
+        return String::new();
+    };
+    let lines = &lines[start..=end];
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Get a line diff between two snapshots, removed lines start with `-`
+/// and added lines with `+`.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence of lines, from the end
+    let mut lengths = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lengths[i][j] = if expected[i] == actual[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            let _ = writeln!(diff, "  {}", expected[i]);
+            i += 1;
+            j += 1;
+        } else if j < actual.len()
+            && (i == expected.len() || lengths[i][j + 1] >= lengths[i + 1][j])
+        {
+            let _ = writeln!(diff, "+ {}", actual[j]);
+            j += 1;
+        } else {
+            let _ = writeln!(diff, "- {}", expected[i]);
+            i += 1;
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{EmptyLayout, HorizontalLayout, solve_layout};
+
+    #[test]
+    fn number_format() {
+        assert_eq!(number(10.0), "10");
+        assert_eq!(number(0.1 + 0.2), "0.3");
+        assert_eq!(number(33.333_332), "33.33");
+        assert_eq!(number(-0.001), "0");
+        assert_eq!(number(f32::INFINITY), "inf");
+    }
+
+    #[test]
+    fn normalize_indentation() {
+        let snapshot = "
+            Root
+              Child
+        ";
+        assert_eq!(normalize(snapshot), "Root\n  Child");
+    }
+
+    #[test]
+    fn diff_lines() {
+        let diff = diff("a\nb\nc", "a\nd\nc");
+        assert_eq!(diff, "  a\n+ d\n- b\n  c\n");
+    }
+
+    #[test]
+    fn snapshot_errors() {
+        let mut root = HorizontalLayout::new();
+        root.intrinsic_size = IntrinsicSize::fixed(50.0, 50.0);
+        let child = EmptyLayout {
+            intrinsic_size: IntrinsicSize::fixed(100.0, 20.0),
+            ..Default::default()
+        };
+        let child_id = child.id;
+        root.add_child(child);
+
+        let tree = solve_layout(&mut root, Size::unit(500.0));
+        let snapshot = LayoutSnapshot::new(&root, tree.errors()).label(child_id, "child");
+        let text = snapshot.to_string();
+        assert!(text.contains("EmptyLayout child"));
+        assert!(text.contains("errors:\n  #0's children overflow on the main axis"));
+        assert!(snapshot.to_json().contains("\"node\": \"#0\""));
+    }
+
+    #[test]
+    #[should_panic(expected = "layout snapshot doesn't match")]
+    fn assert_mismatch() {
+        let mut root = EmptyLayout::new();
+        solve_layout(&mut root, Size::unit(500.0));
+        LayoutSnapshot::new(&root, &[]).assert_eq("EmptyLayout #1");
+    }
+}
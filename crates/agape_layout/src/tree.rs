@@ -0,0 +1,365 @@
+use crate::{Layout, LayoutError, Overflow};
+use agape_core::{Affine, Bounds, CornerRadius, GlobalId, Position, Size};
+use std::collections::HashMap;
+
+/// The solved size and position of a [`Layout`] node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutNode {
+    id: GlobalId,
+    size: Size,
+    position: Position,
+    parent: Option<GlobalId>,
+    overflow: Overflow,
+    corner_radius: CornerRadius,
+    transform: Affine,
+    scroll_offset: Position,
+}
+
+impl LayoutNode {
+    pub fn id(&self) -> GlobalId {
+        self.id
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Get the id of the parent node, the root node has no parent.
+    pub fn parent(&self) -> Option<GlobalId> {
+        self.parent
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(self.position, self.size)
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /// Get the radius of the rounded corners the children are clipped to.
+    pub fn corner_radius(&self) -> CornerRadius {
+        self.corner_radius
+    }
+
+    /// Get how far the children of the node are scrolled, see [`Layout::scroll_offset`].
+    pub fn scroll_offset(&self) -> Position {
+        self.scroll_offset
+    }
+
+    /// Map a `position` into the space of the node, `None` if the node's
+    /// transform can't be undone.
+    fn local_position(&self, position: Position) -> Option<Position> {
+        if self.transform.is_identity() {
+            return Some(position);
+        }
+        self.transform
+            .invert()
+            .map(|inverse| inverse.apply(position))
+    }
+
+    /// Returns `true` if the children of the node are clipped to its bounds.
+    pub fn clips(&self) -> bool {
+        self.overflow.clips()
+    }
+
+    /// Get the transform that the node and its children are drawn with, on
+    /// top of the transforms of its ancestors.
+    pub fn transform(&self) -> Affine {
+        self.transform
+    }
+}
+
+/// The result of solving a layout tree, with every node indexed by
+/// its id so that they can be looked up in constant time.
+///
+/// # Example
+/// ```
+/// use agape_layout::{EmptyLayout, IntrinsicSize, Size, VerticalLayout, solve_layout};
+///
+/// let child = EmptyLayout {
+///     intrinsic_size: IntrinsicSize::fixed(50.0, 50.0),
+///     ..Default::default()
+/// };
+/// let child_id = child.id;
+///
+/// let mut root = VerticalLayout::new();
+/// root.add_child(child);
+///
+/// let tree = solve_layout(&mut root, Size::new(500.0, 500.0));
+/// let node = tree.get(child_id).unwrap();
+/// assert_eq!(node.size(), Size::new(50.0, 50.0));
+/// assert_eq!(node.parent(), Some(root.id));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LayoutTree {
+    root: Option<GlobalId>,
+    nodes: HashMap<GlobalId, LayoutNode>,
+    /// The ids of the children of each node.
+    children: HashMap<GlobalId, Vec<GlobalId>>,
+    errors: Vec<LayoutError>,
+}
+
+impl LayoutTree {
+    /// Index a layout tree that has already been solved.
+    pub fn new(root: &dyn Layout) -> Self {
+        let mut nodes = HashMap::new();
+        let mut children = HashMap::new();
+        let mut stack = vec![(root, None)];
+        while let Some((layout, parent)) = stack.pop() {
+            let node = LayoutNode {
+                id: layout.id(),
+                size: layout.size(),
+                position: layout.position(),
+                parent,
+                overflow: layout.overflow_mode(),
+                corner_radius: layout.corner_radius(),
+                transform: Affine::identity(),
+                scroll_offset: layout.scroll_offset(),
+            };
+            nodes.insert(node.id, node);
+            let child_ids = layout.children().iter().map(|child| child.id()).collect();
+            children.insert(node.id, child_ids);
+            stack.extend(
+                layout
+                    .children()
+                    .iter()
+                    .map(|child| (child.as_ref(), Some(node.id))),
+            );
+        }
+
+        Self {
+            root: Some(root.id()),
+            nodes,
+            children,
+            errors: vec![],
+        }
+    }
+
+    /// Get a node by its `id`.
+    pub fn get(&self, id: GlobalId) -> Option<&LayoutNode> {
+        self.nodes.get(&id)
+    }
+
+    /// Get the parent of the node with the `id`.
+    pub fn parent(&self, id: GlobalId) -> Option<&LayoutNode> {
+        self.get(id)?.parent.and_then(|parent| self.get(parent))
+    }
+
+    /// Get the bounds that the node with the `id` and its children are drawn
+    /// in, with the transforms of the children. Children that are clipped by
+    /// the node are left out.
+    pub fn content_bounds(&self, id: GlobalId) -> Option<Bounds> {
+        let node = self.get(id)?;
+        let mut bounds = node.bounds();
+        if node.clips() {
+            return Some(bounds);
+        }
+        for &child in self.children.get(&id).into_iter().flatten() {
+            if let (Some(content), Some(child)) = (self.content_bounds(child), self.get(child)) {
+                bounds = bounds.union(&child.transform.map_bounds(&content));
+            }
+        }
+        Some(bounds)
+    }
+
+    /// Set the transform of the node with the `id`, see [`LayoutNode::transform`].
+    /// Does nothing if there's no node with the `id`.
+    pub fn set_transform(&mut self, id: GlobalId, transform: Affine) {
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.transform = transform;
+        }
+    }
+
+    /// Returns `true` if the `position` is within the node with the `id` and isn't
+    /// clipped by any of its ancestors, this is used for hit-testing. The position
+    /// is mapped through the inverse of each node's transform, so transformed
+    /// nodes are hit where they are drawn.
+    pub fn contains(&self, id: GlobalId, position: Position) -> bool {
+        let Some(node) = self.get(id) else {
+            return false;
+        };
+
+        let mut ancestors = vec![];
+        let mut parent = node.parent;
+        while let Some(node) = parent.and_then(|id| self.get(id)) {
+            ancestors.push(node);
+            parent = node.parent;
+        }
+
+        // Go from the root down, so that the position is in the space of
+        // each node when it's checked
+        let mut position = position;
+        for node in ancestors.iter().rev() {
+            let Some(local) = node.local_position(position) else {
+                return false;
+            };
+            position = local;
+            if node.clips() && !node.bounds().within_rounded(&position, node.corner_radius) {
+                return false;
+            }
+        }
+        node.local_position(position)
+            .is_some_and(|position| node.bounds().within(&position))
+    }
+
+    /// Get the root node.
+    pub fn root(&self) -> Option<&LayoutNode> {
+        self.root.and_then(|id| self.get(id))
+    }
+
+    /// Get the errors found while solving the layout, each error is only
+    /// in the list once.
+    pub fn errors(&self) -> &[LayoutError] {
+        &self.errors
+    }
+
+    pub(crate) fn set_errors(&mut self, errors: Vec<LayoutError>) {
+        self.errors = errors;
+    }
+
+    /// Returns an iterator over the nodes, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &LayoutNode> {
+        self.nodes.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{EmptyLayout, HorizontalLayout, IntrinsicSize, solve_layout};
+
+    #[test]
+    fn index_every_node() {
+        let mut root = HorizontalLayout::new();
+        let mut inner = HorizontalLayout::new();
+        inner.add_child(EmptyLayout::new());
+        root.add_child(inner);
+        root.add_child(EmptyLayout::new());
+
+        let tree = solve_layout(&mut root, Size::unit(200.0));
+        assert_eq!(tree.len(), 4);
+        for layout in root.iter() {
+            let node = tree.get(layout.id()).unwrap();
+            assert_eq!(node.size(), layout.size());
+            assert_eq!(node.position(), layout.position());
+        }
+    }
+
+    #[test]
+    fn clipped_nodes_are_not_hit() {
+        let mut root = HorizontalLayout::new();
+        root.intrinsic_size = IntrinsicSize::fixed(100.0, 100.0);
+        root.overflow = Overflow::Hidden;
+        root.corner_radius = CornerRadius::all(20.0);
+        let child = EmptyLayout {
+            intrinsic_size: IntrinsicSize::fixed(300.0, 100.0),
+            ..Default::default()
+        };
+        let child_id = child.id;
+        root.add_child(child);
+
+        let tree = solve_layout(&mut root, Size::unit(500.0));
+        assert!(tree.errors().is_empty());
+        assert!(tree.contains(child_id, Position::unit(50.0)));
+        assert!(!tree.contains(child_id, Position::new(200.0, 50.0)));
+        // Outside the rounded corner
+        assert!(!tree.contains(child_id, Position::unit(2.0)));
+
+        // Only the rounded corners are cut off
+        root.corner_radius = CornerRadius::top(20.0);
+        let tree = solve_layout(&mut root, Size::unit(500.0));
+        assert!(!tree.contains(child_id, Position::unit(2.0)));
+        assert!(tree.contains(child_id, Position::new(2.0, 98.0)));
+
+        root.overflow = Overflow::Visible;
+        let tree = solve_layout(&mut root, Size::unit(500.0));
+        assert!(tree.contains(child_id, Position::new(200.0, 50.0)));
+        assert!(tree.contains(child_id, Position::unit(2.0)));
+    }
+
+    #[test]
+    fn content_bounds() {
+        let mut root = HorizontalLayout::new();
+        root.intrinsic_size = IntrinsicSize::fixed(100.0, 100.0);
+        let child = EmptyLayout {
+            intrinsic_size: IntrinsicSize::fixed(300.0, 50.0),
+            ..Default::default()
+        };
+        let child_id = child.id;
+        root.add_child(child);
+
+        let mut tree = solve_layout(&mut root, Size::unit(500.0));
+        let bounds = tree.content_bounds(root.id).unwrap();
+        assert_eq!((bounds.x, bounds.y), ([0.0, 300.0], [0.0, 100.0]));
+
+        tree.set_transform(child_id, Affine::translate(0.0, 100.0));
+        let bounds = tree.content_bounds(root.id).unwrap();
+        assert_eq!((bounds.x, bounds.y), ([0.0, 300.0], [0.0, 150.0]));
+
+        root.overflow = Overflow::Hidden;
+        let tree = solve_layout(&mut root, Size::unit(500.0));
+        assert_eq!(
+            tree.content_bounds(root.id),
+            tree.get(root.id).map(LayoutNode::bounds)
+        );
+    }
+
+    #[test]
+    fn parent_links() {
+        let mut root = HorizontalLayout::new();
+        root.padding = crate::Padding::all(10.0);
+        let child = EmptyLayout {
+            intrinsic_size: IntrinsicSize::fixed(20.0, 20.0),
+            ..Default::default()
+        };
+        let child_id = child.id;
+        root.add_child(child);
+
+        let tree = solve_layout(&mut root, Size::unit(200.0));
+        assert_eq!(tree.root().unwrap().id(), root.id);
+        assert_eq!(tree.root().unwrap().parent(), None);
+        assert_eq!(tree.parent(child_id).unwrap().id(), root.id);
+        assert_eq!(tree.get(child_id).unwrap().position(), Position::unit(10.0));
+    }
+
+    #[test]
+    fn transformed_nodes_are_hit_where_drawn() {
+        let mut root = HorizontalLayout::new();
+        let child = EmptyLayout {
+            intrinsic_size: IntrinsicSize::fixed(100.0, 20.0),
+            ..Default::default()
+        };
+        let child_id = child.id;
+        root.add_child(child);
+
+        let mut tree = solve_layout(&mut root, Size::unit(500.0));
+        assert!(tree.contains(child_id, Position::new(90.0, 10.0)));
+
+        // Rotated around the middle so that it's tall instead of wide
+        let rotate = Affine::rotate(90.0).around(Position::new(50.0, 10.0));
+        tree.set_transform(child_id, rotate);
+        assert!(!tree.contains(child_id, Position::new(90.0, 10.0)));
+        assert!(tree.contains(child_id, Position::new(50.0, 50.0)));
+
+        // The parent's transform moves the child too
+        tree.set_transform(root.id, Affine::translate(200.0, 0.0));
+        assert!(!tree.contains(child_id, Position::new(50.0, 50.0)));
+        assert!(tree.contains(child_id, Position::new(250.0, 50.0)));
+
+        tree.set_transform(child_id, Affine::scale(0.0, 1.0));
+        assert!(!tree.contains(child_id, Position::new(200.0, 10.0)));
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
-    AxisAlignment, BoxConstraints, BoxSizing, IntrinsicSize, Layout, LayoutError, LayoutIter,
-    Padding, error::OverflowAxis,
+    AxisAlignment, BoxConstraints, BoxSizing, Direction, IntrinsicSize, Layout, LayoutCache,
+    LayoutError, LayoutIter, LayoutParams, Overflow, Padding, error::OverflowAxis,
 };
-use agape_core::{GlobalId, Position, Size};
+use agape_core::{CornerRadius, GlobalId, Position, Size};
+use std::any::Any;
 
 // TODO maybe make some items private
 // TODO if min width is larger than max width then it's an overflow
@@ -14,17 +15,32 @@ pub struct VerticalLayout {
     pub position: Position,
     pub spacing: u32,
     pub padding: Padding,
-    // TODO: maybe scrolling should be handled in
-    // the UI layer instead
+    /// Moves the children down by a fixed amount, this isn't limited to the
+    /// content. Use a [`ScrollLayout`] for content that scrolls.
+    ///
+    /// [`ScrollLayout`]: crate::ScrollLayout
     pub scroll_offset: f32,
     pub intrinsic_size: IntrinsicSize,
     pub children: Vec<Box<dyn Layout>>,
+    /// The [`LayoutParams`] of each child, children without params use the
+    /// default params.
+    pub child_params: Vec<LayoutParams>,
     /// The main axis is the `y-axis`
     pub main_axis_alignment: AxisAlignment,
     /// The cross axis is the `x-axis`
     pub cross_axis_alignment: AxisAlignment,
     pub constraints: BoxConstraints,
     pub errors: Vec<LayoutError>,
+    pub cache: LayoutCache,
+    /// The [`Direction`] of the layout, `None` to use the parent's direction.
+    pub direction: Option<Direction>,
+    /// The [`Direction`] of the parent, set when the layout is solved.
+    pub inherited_direction: Direction,
+    /// How children that don't fit in the layout are shown.
+    pub overflow: Overflow,
+    /// The radius of the corners, the children are clipped to the rounded
+    /// corners when the overflow is clipped.
+    pub corner_radius: CornerRadius,
 }
 
 impl VerticalLayout {
@@ -35,7 +51,17 @@ impl VerticalLayout {
 
     /// Add a child [`Layout`] to the `VerticalLayout`
     pub fn add_child(&mut self, child: impl Layout + 'static) {
+        self.add_child_with_params(child, LayoutParams::default());
+    }
+
+    /// Add a child [`Layout`] with its [`LayoutParams`].
+    pub fn add_child_with_params(&mut self, child: impl Layout + 'static, params: LayoutParams) {
+        // Keep the params aligned with the children
+        self.child_params
+            .resize(self.children.len(), LayoutParams::default());
         self.children.push(Box::new(child));
+        self.child_params.push(params);
+        self.cache.invalidate();
     }
 
     pub fn add_children<I>(&mut self, children: I)
@@ -43,8 +69,29 @@ impl VerticalLayout {
         I: IntoIterator<Item: Layout + 'static>,
     {
         for child in children {
-            self.children.push(Box::new(child));
+            self.add_child(child);
+        }
+    }
+
+    /// Get the [`LayoutParams`] of every child.
+    fn params(&self) -> Vec<LayoutParams> {
+        (0..self.children.len())
+            .map(|i| self.child_params.get(i).copied().unwrap_or_default())
+            .collect()
+    }
+
+    /// The sum of the children's heights, margins and the spacing in between.
+    fn content_height(&self) -> f32 {
+        let mut height_sum = self
+            .children
+            .iter()
+            .zip(self.params())
+            .map(|(child, params)| child.size().height + params.margin.vertical_sum())
+            .sum::<f32>();
+        if !self.children.is_empty() {
+            height_sum += (self.children.len() - 1) as f32 * self.spacing as f32;
         }
+        height_sum
     }
 
     /// Returns `true` if a [`VerticalLayout`]'s children are overflowing.
@@ -69,10 +116,11 @@ impl VerticalLayout {
     fn fixed_size_sum(&self) -> Size {
         let mut sum = Size::default();
 
-        for child in self.children.iter() {
+        for (child, params) in self.children.iter().zip(self.params()) {
             if let BoxSizing::Fixed(width) = child.intrinsic_size().width {
-                sum.width = sum.width.max(width);
+                sum.width = sum.width.max(width + params.margin.horizontal_sum());
             }
+            sum.height += params.margin.vertical_sum();
 
             match child.intrinsic_size().height {
                 BoxSizing::Fixed(height) => {
@@ -88,71 +136,105 @@ impl VerticalLayout {
         sum
     }
 
+    /// Move the children down by `offset`, see [`VerticalLayout::scroll_offset`].
     pub fn scroll(&mut self, offset: f32) {
         self.scroll_offset += offset;
+        self.cache.invalidate();
     }
 
     /// Align the children on the main axis at the start
     fn align_main_axis_start(&mut self) {
+        let params = self.params();
         let mut y = self.position.y;
         y += self.padding.top;
 
-        for child in &mut self.children {
+        for (child, params) in self.children.iter_mut().zip(&params) {
+            y += params.margin.top;
             child.set_y(y);
-            y += child.size().height + self.spacing as f32;
+            y += child.size().height + params.margin.bottom + self.spacing as f32;
         }
     }
 
     /// Align the children on the main axis in the center
     fn align_main_axis_center(&mut self) {
         // TODO handle overflow
-        let mut height_sum = self
-            .children
-            .iter()
-            .map(|child| child.size().height)
-            .sum::<f32>();
-
-        // FIXME: panics with 0 children
-        height_sum += (self.spacing * (self.children.len() as u32 - 1)) as f32;
-        let mut center_start = self.position.y + (self.size.height - height_sum) / 2.0;
-
-        for child in &mut self.children {
+        let params = self.params();
+        let height_sum = self.content_height();
+        let inner_height = self.size.height - self.padding.vertical_sum();
+        let mut center_start =
+            self.position.y + self.padding.top + (inner_height - height_sum) / 2.0;
+
+        for (child, params) in self.children.iter_mut().zip(&params) {
+            center_start += params.margin.top;
             child.set_y(center_start);
-            center_start += child.size().height + self.spacing as f32;
+            center_start += child.size().height + params.margin.bottom + self.spacing as f32;
         }
     }
 
     fn align_main_axis_end(&mut self) {
+        let params = self.params();
         let mut y = self.position.y + self.size.height;
-        y -= self.padding.right;
+        y -= self.padding.bottom;
 
-        for child in self.children.iter_mut().rev() {
+        for (child, params) in self.children.iter_mut().zip(&params).rev() {
+            // Set the bottom edge
+            y -= params.margin.bottom;
+            y -= child.size().height;
             child.set_y(y);
-            y -= child.size().height - self.spacing as f32;
+            y -= params.margin.top;
+            y -= self.spacing as f32;
         }
     }
 
-    fn align_cross_axis_start(&mut self) {
-        let x = self.position.x + self.padding.top;
-        for child in &mut self.children {
-            child.set_x(x);
+    /// Distribute the remaining space on the main axis between the children.
+    fn align_main_axis_space(&mut self) {
+        let params = self.params();
+        let free_space = self.size.height - self.padding.vertical_sum() - self.content_height();
+        let (leading, gap) = self
+            .main_axis_alignment
+            .distribute(free_space, self.children.len());
+
+        let mut y = self.position.y + self.padding.top + leading;
+        for (child, params) in self.children.iter_mut().zip(&params) {
+            y += params.margin.top;
+            child.set_y(y);
+            y += child.size().height + params.margin.bottom + self.spacing as f32 + gap;
         }
     }
 
-    fn align_cross_axis_center(&mut self) {
-        for child in &mut self.children {
-            // TODO handle overflow
-            let x_pos = (self.size.width - child.size().width) / 2.0 + self.position.x;
+    /// Align each child on the cross axis, using the child's own alignment
+    /// if it has one.
+    fn align_cross_axis(&mut self) {
+        let params = self.params();
+        for (child, params) in self.children.iter_mut().zip(&params) {
+            let alignment = params.align_self.unwrap_or(self.cross_axis_alignment);
+            let margin = params.margin;
+            // There's only one child on each cross axis line, so distributing
+            // the space is the same as placing it at the start or center.
+            let x_pos = match alignment {
+                AxisAlignment::Start
+                | AxisAlignment::SpaceBetween
+                | AxisAlignment::Stretch
+                | AxisAlignment::Baseline => self.position.x + self.padding.left + margin.left,
+                AxisAlignment::Center | AxisAlignment::SpaceAround | AxisAlignment::SpaceEvenly => {
+                    // TODO handle overflow
+                    let inner_width = self.size.width - self.padding.horizontal_sum();
+                    (inner_width - child.size().width) / 2.0
+                        + self.position.x
+                        + self.padding.left
+                        + (margin.left - margin.right) / 2.0
+                }
+                AxisAlignment::End => {
+                    self.position.x + self.size.width
+                        - self.padding.right
+                        - margin.right
+                        - child.size().width
+                }
+            };
             child.set_x(x_pos);
         }
     }
 
-    fn align_cross_axis_end(&mut self) {
-        for child in &mut self.children {
-            child.set_x(self.position.x + self.size.width - self.padding.right);
-        }
-    }
-
     fn compute_children_min_size(&mut self) -> Size {
         let mut sum = Size::default();
         sum.width += self.padding.horizontal_sum();
@@ -161,13 +243,14 @@ impl VerticalLayout {
             return sum;
         }
 
+        let params = self.params();
         let space_between = (self.children.len() - 1) as f32 * self.spacing as f32;
         sum.height += space_between;
         let mut max_width = 0.0f32;
-        for child in self.children.iter_mut() {
+        for (child, params) in self.children.iter_mut().zip(&params) {
             let (min_width, min_height) = child.solve_min_constraints();
-            sum.height += min_height;
-            max_width = max_width.max(min_width);
+            sum.height += min_height + params.margin.vertical_sum();
+            max_width = max_width.max(min_width + params.margin.horizontal_sum());
         }
         sum.width += max_width;
         sum
@@ -229,7 +312,8 @@ impl Layout for VerticalLayout {
 
     fn collect_errors(&mut self) -> Vec<LayoutError> {
         self.errors
-            .drain(..)
+            .iter()
+            .cloned()
             .chain(
                 self.children
                     .iter_mut()
@@ -243,7 +327,93 @@ impl Layout for VerticalLayout {
         LayoutIter { stack: vec![self] }
     }
 
+    fn scroll_offset(&self) -> Position {
+        // The offset moves the children down, which is towards the start
+        Position::new(0.0, -self.scroll_offset)
+    }
+
+    fn mark_dirty(&mut self, id: GlobalId) -> bool {
+        let found = self.id == id || self.children.iter_mut().any(|child| child.mark_dirty(id));
+        if found {
+            self.cache.invalidate();
+        }
+        found
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.cache.is_dirty()
+    }
+
+    fn baseline(&self) -> Option<f32> {
+        // The first child is only at a known offset when it's at the start
+        let start = matches!(
+            self.main_axis_alignment,
+            AxisAlignment::Start | AxisAlignment::Stretch | AxisAlignment::Baseline
+        );
+        let child = self.children.first().filter(|_| start)?;
+        let margin = self.params()[0].margin;
+        Some(self.padding.top + margin.top + child.baseline()? + self.scroll_offset)
+    }
+
+    fn overflow_mode(&self) -> Overflow {
+        self.overflow
+    }
+
+    fn corner_radius(&self) -> CornerRadius {
+        self.corner_radius
+    }
+
+    fn direction(&self) -> Direction {
+        self.direction.unwrap_or(self.inherited_direction)
+    }
+
+    fn inherit_direction(&mut self, direction: Direction) {
+        if self.direction.is_none() && self.inherited_direction != direction {
+            self.inherited_direction = direction;
+            self.cache.invalidate();
+        }
+    }
+
+    fn reuse(&mut self, previous: &dyn Layout) -> bool {
+        let previous: &dyn Any = previous;
+        let Some(previous) = previous.downcast_ref::<Self>() else {
+            return false;
+        };
+
+        // Unchanged children are reused even if this layout has changed
+        let mut reused = self.children.len() == previous.children.len();
+        for (child, previous) in self.children.iter_mut().zip(&previous.children) {
+            reused &= child.reuse(previous.as_ref());
+        }
+
+        let unchanged = self.spacing == previous.spacing
+            && self.padding == previous.padding
+            && self.intrinsic_size == previous.intrinsic_size
+            && self.main_axis_alignment == previous.main_axis_alignment
+            && self.cross_axis_alignment == previous.cross_axis_alignment
+            && self.params() == previous.params()
+            && self.scroll_offset == previous.scroll_offset
+            && self.direction == previous.direction;
+        if !reused || !unchanged || previous.is_dirty() || !previous.errors.is_empty() {
+            return false;
+        }
+
+        self.size = previous.size;
+        self.position = previous.position;
+        self.constraints = previous.constraints;
+        self.cache = previous.cache;
+        self.inherited_direction = previous.inherited_direction;
+        true
+    }
+
     fn solve_min_constraints(&mut self) -> (f32, f32) {
+        if let Some((min_width, min_height)) = self.cache.min_size() {
+            // Restore the min constraints in case the parent changed them
+            self.constraints.min_width = min_width;
+            self.constraints.min_height = min_height;
+            return (min_width, min_height);
+        }
+
         let child_constraint_sum = self.compute_children_min_size();
 
         match self.intrinsic_size.width {
@@ -264,20 +434,32 @@ impl Layout for VerticalLayout {
             }
         }
 
-        (self.constraints.min_width, self.constraints.min_height)
+        let min_size = (self.constraints.min_width, self.constraints.min_height);
+        self.cache.set_min_size(min_size);
+        min_size
     }
 
-    fn solve_max_constraints(&mut self, _space: Size) {
-        // Sum up all the flex factors
-        let flex_total: u8 = self
+    fn solve_max_constraints(&mut self, space: Size) {
+        if self.cache.reuse(self.constraints, space) {
+            return;
+        }
+
+        // Restore the children's min constraints which the last solve may
+        // have changed, clean children return their cached constraints.
+        for child in &mut self.children {
+            child.solve_min_constraints();
+        }
+
+        let params = self.params();
+        // Sum up all the flex factors, including the children that grow
+        let flex_total: f32 = self
             .children
             .iter()
-            .filter_map(|child| {
-                if let BoxSizing::Flex(factor) = child.intrinsic_size().height {
-                    Some(factor)
-                } else {
-                    None
-                }
+            .zip(&params)
+            .map(|(child, params)| match child.intrinsic_size().height {
+                BoxSizing::Flex(factor) => factor as f32,
+                BoxSizing::Shrink => params.flex_grow as f32,
+                BoxSizing::Fixed(_) => 0.0,
             })
             .sum();
 
@@ -296,6 +478,10 @@ impl Layout for VerticalLayout {
 
         let mut available_width;
         match self.intrinsic_size.width {
+            // Content like wrapped text can get narrower to fit the max width
+            BoxSizing::Shrink if self.constraints.max_width > 0.0 => {
+                available_width = self.constraints.min_width.min(self.constraints.max_width);
+            }
             BoxSizing::Shrink => available_width = self.constraints.min_width,
             BoxSizing::Fixed(_) | BoxSizing::Flex(_) => {
                 available_width = self.constraints.max_width;
@@ -303,6 +489,13 @@ impl Layout for VerticalLayout {
             }
         }
 
+        // Stretched children fill the width inside the padding
+        let stretch_width = self.intrinsic_size.width.stretch_size(
+            self.constraints.min_width,
+            self.constraints.max_width,
+            self.padding.horizontal_sum(),
+        );
+
         if !self.children.is_empty() {
             // Add the spacing between layouts
             for _ in 0..self.children.len() - 1 {
@@ -310,37 +503,88 @@ impl Layout for VerticalLayout {
             }
         }
 
-        for child in self.children.iter_mut() {
+        // The shrinking children grow into, or give up, the space left over. A
+        // shrinking layout is already as small as its children so there's
+        // nothing left over.
+        let free_height = if self.intrinsic_size.height == BoxSizing::Shrink {
+            0.0
+        } else {
+            available_height
+        };
+
+        // When the children overflow, the shrinking children give up space
+        // relative to their shrink factor and size.
+        let shrink_total: f32 = self
+            .children
+            .iter()
+            .zip(&params)
+            .filter(|(child, _)| child.intrinsic_size().height == BoxSizing::Shrink)
+            .map(|(child, params)| params.flex_shrink * child.constraints().min_height)
+            .sum();
+
+        for (child, params) in self.children.iter_mut().zip(&params) {
+            let alignment = params.align_self.unwrap_or(self.cross_axis_alignment);
             match child.intrinsic_size().width {
                 BoxSizing::Flex(_) => {
-                    child.set_max_width(available_width);
+                    child.set_max_width(available_width - params.margin.horizontal_sum());
                 }
                 BoxSizing::Shrink => {
-                    child.set_max_width(child.constraints().min_width);
+                    let width = available_width - params.margin.horizontal_sum();
+                    child.set_max_width(child.constraints().min_width.min(width));
                 }
                 BoxSizing::Fixed(width) => {
                     child.set_max_width(width);
                 }
             }
 
+            if alignment == AxisAlignment::Stretch
+                && !matches!(child.intrinsic_size().width, BoxSizing::Fixed(_))
+            {
+                let width = stretch_width - params.margin.horizontal_sum();
+                child.set_min_width(width);
+                child.set_max_width(width);
+            }
+
             match child.intrinsic_size().height {
                 BoxSizing::Flex(factor) => {
-                    let grow_factor = factor as f32 / flex_total as f32;
+                    let grow_factor = factor as f32 / flex_total;
                     child.set_max_height(grow_factor * available_height);
                 }
                 BoxSizing::Fixed(height) => {
                     child.set_max_height(height);
                 }
-                BoxSizing::Shrink => {}
+                BoxSizing::Shrink => {
+                    let mut height = child.constraints().min_height;
+                    if free_height > 0.0 && params.flex_grow > 0 {
+                        height += params.flex_grow as f32 / flex_total * free_height;
+                    } else if free_height < 0.0 && shrink_total > 0.0 {
+                        let shrink_factor = params.flex_shrink * height / shrink_total;
+                        height = shrink_factor.mul_add(free_height, height).max(0.0);
+                    }
+
+                    if height != child.constraints().min_height {
+                        child.set_min_height(height);
+                        child.set_max_height(height);
+                    }
+                }
             }
 
-            // TODO not using size anymore
-            // FIXME check this
-            child.solve_max_constraints(Size::default());
+            let space = Size::new(
+                available_width - params.margin.horizontal_sum(),
+                available_height,
+            );
+            child.solve_max_constraints(space);
         }
     }
 
     fn update_size(&mut self) {
+        if self.cache.is_reused() {
+            return;
+        }
+
+        // The errors are found again every time the layout is solved
+        self.errors.clear();
+
         match self.intrinsic_size.width {
             BoxSizing::Flex(_) => {
                 self.size.width = self.constraints.max_width;
@@ -371,11 +615,22 @@ impl Layout for VerticalLayout {
             child.update_size();
         }
 
-        // TODO check for padding and spacing
-        let width_sum: f32 = self.children.iter().map(|child| child.size().width).sum();
+        // Clipped children are allowed to overflow, it's not an error
+        // when they're hidden on purpose
+        if self.overflow.clips() {
+            return;
+        }
+
+        let width_sum = self
+            .children
+            .iter()
+            .zip(self.params())
+            .map(|(child, params)| child.size().width + params.margin.horizontal_sum())
+            .fold(0.0, f32::max)
+            + self.padding.horizontal_sum();
         let mut height_sum = self.padding.vertical_sum();
-        for (i, child) in self.children.iter().enumerate() {
-            height_sum += child.size().height;
+        for (i, (child, params)) in self.children.iter().zip(self.params()).enumerate() {
+            height_sum += child.size().height + params.margin.vertical_sum();
             if i != self.children.len() - 1 {
                 height_sum += self.spacing as f32;
             }
@@ -395,28 +650,36 @@ impl Layout for VerticalLayout {
     }
 
     fn position_children(&mut self) {
+        if self.cache.reuse_position(self.position) {
+            return;
+        }
+
         match self.main_axis_alignment {
-            AxisAlignment::Start => self.align_main_axis_start(),
+            AxisAlignment::Start | AxisAlignment::Stretch | AxisAlignment::Baseline => {
+                self.align_main_axis_start()
+            }
             AxisAlignment::Center => self.align_main_axis_center(),
             AxisAlignment::End => self.align_main_axis_end(),
+            AxisAlignment::SpaceBetween
+            | AxisAlignment::SpaceAround
+            | AxisAlignment::SpaceEvenly => self.align_main_axis_space(),
         }
 
-        match self.cross_axis_alignment {
-            AxisAlignment::Start => self.align_cross_axis_start(),
-            AxisAlignment::Center => self.align_cross_axis_center(),
-            AxisAlignment::End => self.align_cross_axis_end(),
-        }
+        self.align_cross_axis();
 
+        let direction = self.direction();
         for child in &mut self.children {
             let y = child.position().y;
             child.set_y(y + self.scroll_offset);
 
-            if child.position().y > self.position.y + self.size.height {
-                self.errors.push(LayoutError::OutOfBounds {
-                    parent_id: self.id,
-                    child_id: child.id().to_owned(),
-                });
+            if !self.overflow.clips() && child.position().y > self.position.y + self.size.height {
+                let error = LayoutError::out_of_bound(self.id, child.id());
+                if !self.errors.contains(&error) {
+                    self.errors.push(error);
+                }
             }
+            direction.place(child.as_mut(), self.position.x, self.size.width);
+            child.inherit_direction(direction);
             child.position_children();
         }
     }
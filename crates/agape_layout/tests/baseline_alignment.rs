@@ -0,0 +1,114 @@
+use agape_layout::{
+    AxisAlignment, EmptyLayout, HorizontalLayout, IntrinsicSize, Layout, LayoutParams,
+    MeasureLayout, Padding, Size, VerticalLayout, solve_layout,
+};
+
+fn text(height: f32, baseline: f32) -> MeasureLayout {
+    MeasureLayout::new(move |_| Size::new(50.0, height)).baseline(baseline)
+}
+
+fn baseline_row() -> HorizontalLayout {
+    HorizontalLayout {
+        cross_axis_alignment: AxisAlignment::Baseline,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn baselines_are_aligned() {
+    let mut root = baseline_row();
+    root.add_child(text(40.0, 30.0));
+    root.add_child(text(20.0, 15.0));
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    assert_eq!(root.children[0].position().y, 0.0);
+    assert_eq!(root.children[1].position().y, 15.0);
+    assert_eq!(root.size().height, 40.0);
+    assert_eq!(root.baseline(), Some(30.0));
+}
+
+#[test]
+fn bottom_edge_is_used_without_baseline() {
+    let mut root = baseline_row();
+    root.add_child(text(40.0, 30.0));
+    root.add_child(EmptyLayout {
+        intrinsic_size: IntrinsicSize::fixed(20.0, 20.0),
+        ..Default::default()
+    });
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    assert_eq!(root.children[1].position().y, 10.0);
+}
+
+#[test]
+fn row_grows_to_fit_offset_children() {
+    let mut root = baseline_row();
+    root.add_child(text(40.0, 10.0));
+    root.add_child(text(35.0, 30.0));
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    assert_eq!(root.size().height, 60.0);
+    assert_eq!(root.children[0].position().y, 20.0);
+    assert_eq!(root.children[1].position().y, 0.0);
+}
+
+#[test]
+fn baseline_includes_padding_and_margin() {
+    let mut root = baseline_row();
+    root.padding = Padding::all(10.0);
+    root.add_child_with_params(
+        text(20.0, 15.0),
+        LayoutParams {
+            margin: Padding::new(0.0, 0.0, 20.0, 0.0),
+            ..Default::default()
+        },
+    );
+    root.add_child(text(40.0, 30.0));
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    assert_eq!(root.children[0].position().y, 30.0);
+    assert_eq!(root.children[1].position().y, 15.0);
+    assert_eq!(root.baseline(), Some(45.0));
+}
+
+#[test]
+fn align_self_baseline() {
+    let mut root = HorizontalLayout::new();
+    root.add_child(text(40.0, 30.0));
+    root.add_child_with_params(
+        text(20.0, 15.0),
+        LayoutParams {
+            align_self: Some(AxisAlignment::Baseline),
+            ..Default::default()
+        },
+    );
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    // Children aligned to the start don't move the shared baseline
+    assert_eq!(root.children[0].position().y, 0.0);
+    assert_eq!(root.children[1].position().y, 0.0);
+}
+
+#[test]
+fn nested_baselines() {
+    let mut column = VerticalLayout::new();
+    column.padding = Padding::all(5.0);
+    column.add_child(text(20.0, 15.0));
+    column.add_child(text(20.0, 15.0));
+
+    let mut root = baseline_row();
+    root.add_child(column);
+    root.add_child(text(40.0, 30.0));
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    assert_eq!(root.children[0].baseline(), Some(20.0));
+    assert_eq!(root.children[0].position().y, 10.0);
+    assert_eq!(root.children[0].children()[0].position().y, 15.0);
+    assert_eq!(root.children[1].position().y, 0.0);
+}
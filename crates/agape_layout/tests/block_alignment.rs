@@ -78,9 +78,10 @@ fn test_end_alignment() {
 
     solve_layout(&mut root, window);
 
+    // The child's right and bottom edges line up with the padding
     let mut child_1_pos = Position {
-        x: root.position.x + root.size.width,
-        y: root.position.y + root.size.height,
+        x: root.position.x + root.size.width - 240.0,
+        y: root.position.y + root.size.height - 40.0,
     };
     child_1_pos -= padding as f32;
 
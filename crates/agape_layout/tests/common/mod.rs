@@ -0,0 +1,11 @@
+use agape_layout::{EmptyLayout, IntrinsicSize};
+
+/// Create `count` layouts with a fixed `width` and `height`.
+pub fn fixed_children(width: f32, height: f32, count: usize) -> Vec<EmptyLayout> {
+    (0..count)
+        .map(|_| EmptyLayout {
+            intrinsic_size: IntrinsicSize::fixed(width, height),
+            ..Default::default()
+        })
+        .collect()
+}
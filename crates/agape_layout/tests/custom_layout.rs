@@ -0,0 +1,219 @@
+use agape_layout::{
+    BoxSizing, CustomLayout, Direction, EmptyLayout, IntrinsicSize, Layout, LayoutBase,
+    LayoutSnapshot, MeasureLayout, Position, Size, VerticalLayout, solve_layout,
+};
+
+/// Places each child in the shortest column.
+#[derive(Debug)]
+struct MasonryLayout {
+    base: LayoutBase,
+    columns: usize,
+}
+
+impl MasonryLayout {
+    fn new(columns: usize) -> Self {
+        let mut base = LayoutBase::new();
+        base.intrinsic_size.width = BoxSizing::Flex(1);
+        Self { base, columns }
+    }
+
+    fn column_width(&self) -> f32 {
+        self.base.size.width.max(self.base.constraints.max_width) / self.columns as f32
+    }
+
+    /// Get the position of each child relative to the layout.
+    fn place(&self) -> Vec<Position> {
+        let width = self.column_width();
+        let mut heights = vec![0.0f32; self.columns];
+        let mut positions = vec![];
+        for child in &self.base.children {
+            let (column, height) = heights
+                .iter()
+                .copied()
+                .enumerate()
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            positions.push(Position::new(column as f32 * width, height));
+            heights[column] += child.constraints().min_height;
+        }
+        positions
+    }
+}
+
+impl CustomLayout for MasonryLayout {
+    fn base(&self) -> &LayoutBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut LayoutBase {
+        &mut self.base
+    }
+
+    fn measure(&self) -> Size {
+        let height = self
+            .place()
+            .iter()
+            .zip(&self.base.children)
+            .map(|(position, child)| position.y + child.constraints().min_height)
+            .fold(0.0, f32::max);
+        Size::new(0.0, height)
+    }
+
+    fn child_space(&self, _index: usize, space: Size) -> Size {
+        Size::new(space.width / self.columns as f32, space.height)
+    }
+
+    fn arrange(&mut self) {
+        let origin = self.base.position;
+        let positions = self.place();
+        for (child, position) in self.base.children.iter_mut().zip(positions) {
+            child.set_position(Position::new(origin.x + position.x, origin.y + position.y));
+        }
+    }
+}
+
+/// Places the children in a circle around the center.
+#[derive(Debug)]
+struct RadialLayout {
+    base: LayoutBase,
+    radius: f32,
+}
+
+impl CustomLayout for RadialLayout {
+    fn base(&self) -> &LayoutBase {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut LayoutBase {
+        &mut self.base
+    }
+
+    fn arrange(&mut self) {
+        let center = Position::new(
+            self.base.size.width.mul_add(0.5, self.base.position.x),
+            self.base.size.height.mul_add(0.5, self.base.position.y),
+        );
+        let count = self.base.children.len() as f32;
+        for (i, child) in self.base.children.iter_mut().enumerate() {
+            let angle = std::f32::consts::TAU * i as f32 / count;
+            let size = child.size();
+            child.set_position(Position::new(
+                self.radius.mul_add(angle.cos(), center.x) - size.width / 2.0,
+                self.radius.mul_add(angle.sin(), center.y) - size.height / 2.0,
+            ));
+        }
+    }
+}
+
+fn fixed(width: f32, height: f32) -> EmptyLayout {
+    EmptyLayout {
+        intrinsic_size: IntrinsicSize::fixed(width, height),
+        ..Default::default()
+    }
+}
+
+fn tile(height: f32) -> EmptyLayout {
+    EmptyLayout {
+        intrinsic_size: IntrinsicSize {
+            width: BoxSizing::Flex(1),
+            height: BoxSizing::Fixed(height),
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn masonry_layout() {
+    let mut masonry = MasonryLayout::new(2);
+    masonry
+        .base
+        .add_children([tile(100.0), tile(50.0), tile(30.0), tile(80.0)]);
+
+    solve_layout(&mut masonry, Size::new(400.0, 500.0));
+
+    let positions: Vec<Position> = masonry
+        .children()
+        .iter()
+        .map(|child| child.position())
+        .collect();
+    assert_eq!(
+        positions,
+        [
+            Position::new(0.0, 0.0),
+            Position::new(200.0, 0.0),
+            Position::new(200.0, 50.0),
+            Position::new(200.0, 80.0),
+        ]
+    );
+    assert_eq!(masonry.children()[0].size(), Size::new(200.0, 100.0));
+    assert_eq!(masonry.size(), Size::new(400.0, 160.0));
+}
+
+#[test]
+fn radial_layout() {
+    let mut radial = RadialLayout {
+        base: LayoutBase::new(),
+        radius: 50.0,
+    };
+    radial.base.intrinsic_size = IntrinsicSize::fixed(200.0, 200.0);
+    radial
+        .base
+        .add_children([fixed(20.0, 20.0), fixed(20.0, 20.0)]);
+
+    solve_layout(&mut radial, Size::new(500.0, 500.0));
+
+    assert_eq!(radial.children()[0].position(), Position::new(140.0, 90.0));
+    let position = radial.children()[1].position();
+    assert_eq!(position.x, 40.0);
+    assert!((position.y - 90.0).abs() < 0.001);
+}
+
+#[test]
+fn custom_layout_in_built_in_layout() {
+    let mut masonry = MasonryLayout::new(2);
+    masonry.base.add_children([tile(100.0), tile(50.0)]);
+
+    let mut root = VerticalLayout::new();
+    root.intrinsic_size = IntrinsicSize::fill();
+    root.add_child(fixed(400.0, 20.0));
+    root.add_child(masonry);
+
+    let tree = solve_layout(&mut root, Size::new(400.0, 500.0));
+    let masonry = &root.children[1];
+    assert!(tree.errors().is_empty());
+    assert_eq!(masonry.size(), Size::new(400.0, 100.0));
+    assert_eq!(masonry.children()[1].position(), Position::new(200.0, 20.0));
+    assert!(
+        LayoutSnapshot::new(&root, tree.errors())
+            .to_string()
+            .contains("  MasonryLayout #2 size=400x100")
+    );
+}
+
+#[test]
+fn children_are_measured_again() {
+    // Text that wraps onto two lines when it's narrower than 100
+    let text = MeasureLayout::new(|width| match width {
+        Some(width) if width < 100.0 => Size::new(width, 40.0),
+        _ => Size::new(100.0, 20.0),
+    });
+    let mut masonry = MasonryLayout::new(2);
+    masonry.base.add_child(text);
+
+    solve_layout(&mut masonry, Size::new(160.0, 500.0));
+
+    assert_eq!(masonry.children()[0].size(), Size::new(80.0, 40.0));
+    assert_eq!(masonry.size().height, 40.0);
+}
+
+#[test]
+fn custom_layout_is_mirrored() {
+    let mut masonry = MasonryLayout::new(2);
+    masonry.base.direction = Some(Direction::RightToLeft);
+    masonry.base.add_children([tile(100.0), tile(50.0)]);
+
+    solve_layout(&mut masonry, Size::new(400.0, 500.0));
+
+    assert_eq!(masonry.children()[0].position().x, 200.0);
+    assert_eq!(masonry.children()[1].position().x, 0.0);
+}
@@ -0,0 +1,105 @@
+use agape_layout::{
+    AxisAlignment, BlockLayout, Direction, EmptyLayout, HorizontalLayout, IntrinsicSize, Layout,
+    Padding, Position, Size, VerticalLayout, solve_layout,
+};
+
+fn fixed(width: f32, height: f32) -> EmptyLayout {
+    EmptyLayout {
+        intrinsic_size: IntrinsicSize::fixed(width, height),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn horizontal_flows_right_to_left() {
+    let mut root = HorizontalLayout::new();
+    root.intrinsic_size = IntrinsicSize::fill();
+    root.spacing = 10;
+    root.direction = Some(Direction::RightToLeft);
+    root.add_child(fixed(100.0, 50.0));
+    root.add_child(fixed(50.0, 50.0));
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    assert_eq!(root.children[0].position().x, 400.0);
+    assert_eq!(root.children[1].position().x, 340.0);
+}
+
+#[test]
+fn padding_is_mirrored() {
+    let mut root = HorizontalLayout::new();
+    root.intrinsic_size = IntrinsicSize::fill();
+    root.padding = Padding::new(20.0, 5.0, 0.0, 0.0);
+    root.direction = Some(Direction::RightToLeft);
+    root.add_child(fixed(100.0, 50.0));
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    assert_eq!(root.children[0].position().x, 380.0);
+}
+
+#[test]
+fn start_alignment_is_mirrored() {
+    let mut root = VerticalLayout::new();
+    root.intrinsic_size = IntrinsicSize::fill();
+    root.cross_axis_alignment = AxisAlignment::Start;
+    root.direction = Some(Direction::RightToLeft);
+    root.add_child(fixed(100.0, 50.0));
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    assert_eq!(root.children[0].position(), Position::new(400.0, 0.0));
+}
+
+#[test]
+fn direction_is_inherited() {
+    let mut row = HorizontalLayout::new();
+    row.intrinsic_size = IntrinsicSize::fixed(200.0, 50.0);
+    row.add_child(fixed(50.0, 50.0));
+    let mut block = BlockLayout::new(Box::new(row));
+    block.intrinsic_size = IntrinsicSize::fill();
+
+    let mut root = VerticalLayout::new();
+    root.intrinsic_size = IntrinsicSize::fill();
+    root.inherit_direction(Direction::RightToLeft);
+    root.add_child(block);
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    let row = &root.children[0].children()[0];
+    assert_eq!(row.direction(), Direction::RightToLeft);
+    assert_eq!(row.position().x, 300.0);
+    assert_eq!(row.children()[0].position().x, 450.0);
+}
+
+#[test]
+fn child_keeps_own_direction() {
+    let mut row = HorizontalLayout::new();
+    row.intrinsic_size = IntrinsicSize::fixed(200.0, 50.0);
+    row.direction = Some(Direction::LeftToRight);
+    row.add_child(fixed(50.0, 50.0));
+
+    let mut root = VerticalLayout::new();
+    root.intrinsic_size = IntrinsicSize::fill();
+    root.direction = Some(Direction::RightToLeft);
+    root.add_child(row);
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    assert_eq!(root.children[0].direction(), Direction::LeftToRight);
+    assert_eq!(root.children[0].position().x, 300.0);
+    assert_eq!(root.children[0].children()[0].position().x, 300.0);
+}
+
+#[test]
+fn changing_direction_positions_again() {
+    let mut root = HorizontalLayout::new();
+    root.intrinsic_size = IntrinsicSize::fill();
+    root.add_child(fixed(100.0, 50.0));
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+    assert_eq!(root.children[0].position().x, 0.0);
+
+    root.inherit_direction(Direction::RightToLeft);
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+    assert_eq!(root.children[0].position().x, 400.0);
+}
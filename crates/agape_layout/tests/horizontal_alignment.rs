@@ -1,7 +1,10 @@
+mod common;
+
 use agape_layout::{
-    AxisAlignment, BoxSizing, EmptyLayout, HorizontalLayout, IntrinsicSize, Padding, Position,
-    Size, solve_layout,
+    AxisAlignment, BoxSizing, EmptyLayout, HorizontalLayout, IntrinsicSize, Layout, Padding,
+    Position, Size, solve_layout,
 };
+use common::fixed_children;
 
 #[test]
 fn single_horizontal_center_alignment() {
@@ -148,4 +151,207 @@ fn start_alignment() {
     assert_eq!(root.children[1].position(), child_2_pos);
 }
 
+#[test]
+fn space_between_alignment() {
+    let window = Size::new(500.0, 500.0);
+    let mut root = HorizontalLayout {
+        main_axis_alignment: AxisAlignment::SpaceBetween,
+        padding: Padding::all(20.0),
+        spacing: 10,
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    root.add_children(fixed_children(100.0, 50.0, 3));
+
+    solve_layout(&mut root, window);
+
+    // 500 - 40 padding - 300 width - 20 spacing = 140
+    assert_eq!(root.children[0].position().x, 20.0);
+    assert_eq!(root.children[1].position().x, 20.0 + 100.0 + 10.0 + 70.0);
+    assert_eq!(root.children[2].position().x, 500.0 - 20.0 - 100.0);
+}
+
+#[test]
+fn space_between_single_child() {
+    let window = Size::new(500.0, 500.0);
+    let mut root = HorizontalLayout {
+        main_axis_alignment: AxisAlignment::SpaceBetween,
+        padding: Padding::all(20.0),
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    root.add_children(fixed_children(100.0, 50.0, 1));
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.children[0].position().x, 20.0);
+}
+
+#[test]
+fn space_around_alignment() {
+    let window = Size::new(400.0, 400.0);
+    let mut root = HorizontalLayout {
+        main_axis_alignment: AxisAlignment::SpaceAround,
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    root.add_children(fixed_children(100.0, 50.0, 2));
+
+    solve_layout(&mut root, window);
+
+    // 200 free space, 100 around each child
+    assert_eq!(root.children[0].position().x, 50.0);
+    assert_eq!(root.children[1].position().x, 250.0);
+}
+
+#[test]
+fn space_evenly_alignment() {
+    let window = Size::new(400.0, 400.0);
+    let mut root = HorizontalLayout {
+        main_axis_alignment: AxisAlignment::SpaceEvenly,
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    root.add_children(fixed_children(50.0, 50.0, 3));
+
+    solve_layout(&mut root, window);
+
+    // 250 free space, 62.5 between each child and the edges
+    assert_eq!(root.children[0].position().x, 62.5);
+    assert_eq!(root.children[1].position().x, 175.0);
+    assert_eq!(root.children[2].position().x, 287.5);
+}
+
+#[test]
+fn space_between_overflow_starts_at_padding() {
+    let window = Size::new(100.0, 100.0);
+    let mut root = HorizontalLayout {
+        main_axis_alignment: AxisAlignment::SpaceBetween,
+        padding: Padding::all(10.0),
+        intrinsic_size: IntrinsicSize::fixed(100.0, 100.0),
+        ..Default::default()
+    };
+    root.add_children(fixed_children(100.0, 50.0, 2));
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.children[0].position().x, 10.0);
+    assert_eq!(root.children[1].position().x, 110.0);
+}
+
+#[test]
+fn stretch_alignment() {
+    let window = Size::new(500.0, 500.0);
+    let mut shrink = EmptyLayout::new();
+    shrink.intrinsic_size.width = BoxSizing::Fixed(100.0);
+    let fixed = EmptyLayout {
+        intrinsic_size: IntrinsicSize::fixed(100.0, 50.0),
+        ..Default::default()
+    };
+
+    let mut root = HorizontalLayout {
+        cross_axis_alignment: AxisAlignment::Stretch,
+        padding: Padding::all(24.0),
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    root.add_child(shrink);
+    root.add_child(fixed);
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.children[0].size(), Size::new(100.0, 500.0 - 48.0));
+    assert_eq!(root.children[0].position().y, 24.0);
+    // Fixed sizes are not stretched
+    assert_eq!(root.children[1].size(), Size::new(100.0, 50.0));
+}
+
+#[test]
+fn stretch_to_tallest_child() {
+    let window = Size::new(500.0, 500.0);
+    let mut short = EmptyLayout::new();
+    short.intrinsic_size.width = BoxSizing::Fixed(50.0);
+    let tall = EmptyLayout {
+        intrinsic_size: IntrinsicSize::fixed(50.0, 200.0),
+        ..Default::default()
+    };
+
+    let mut root = HorizontalLayout {
+        cross_axis_alignment: AxisAlignment::Stretch,
+        ..Default::default()
+    };
+    root.add_child(short);
+    root.add_child(tall);
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.size(), Size::new(100.0, 200.0));
+    assert_eq!(root.children[0].size(), Size::new(50.0, 200.0));
+}
+
+#[test]
+fn stretch_inside_padding() {
+    let window = Size::new(500.0, 500.0);
+    let mut short = EmptyLayout::new();
+    short.intrinsic_size.width = BoxSizing::Fixed(50.0);
+
+    let mut root = HorizontalLayout {
+        cross_axis_alignment: AxisAlignment::Stretch,
+        padding: Padding::all(10.0),
+        ..Default::default()
+    };
+    root.add_child(short);
+    root.add_children(fixed_children(50.0, 200.0, 1));
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.size(), Size::new(120.0, 220.0));
+    assert_eq!(root.children[0].size(), Size::new(50.0, 200.0));
+    assert_eq!(root.children[0].position().y, 10.0);
+}
+
+#[test]
+fn end_alignment_inside_padding() {
+    let window = Size::new(500.0, 500.0);
+    let mut root = HorizontalLayout {
+        main_axis_alignment: AxisAlignment::End,
+        cross_axis_alignment: AxisAlignment::End,
+        padding: Padding::new(10.0, 20.0, 30.0, 40.0),
+        spacing: 10,
+        intrinsic_size: IntrinsicSize::fixed(300.0, 200.0),
+        ..Default::default()
+    };
+    root.add_children(fixed_children(50.0, 40.0, 2));
+
+    solve_layout(&mut root, window);
+
+    // The last child's right and bottom edges touch the padding
+    assert_eq!(
+        root.children[1].position(),
+        Position::new(300.0 - 20.0 - 50.0, 200.0 - 40.0 - 40.0)
+    );
+    assert_eq!(root.children[0].position().x, 230.0 - 10.0 - 50.0);
+}
+
+#[test]
+fn center_alignment_inside_padding() {
+    let window = Size::new(500.0, 500.0);
+    let mut root = HorizontalLayout {
+        main_axis_alignment: AxisAlignment::Center,
+        cross_axis_alignment: AxisAlignment::Center,
+        padding: Padding::new(10.0, 20.0, 30.0, 40.0),
+        intrinsic_size: IntrinsicSize::fixed(300.0, 200.0),
+        ..Default::default()
+    };
+    root.add_children(fixed_children(100.0, 40.0, 1));
+
+    solve_layout(&mut root, window);
+
+    // Centered in the 270x130 box inside the padding
+    assert_eq!(
+        root.children[0].position(),
+        Position::new(10.0 + 85.0, 30.0 + 45.0)
+    );
+}
+
 // TODO test overflow
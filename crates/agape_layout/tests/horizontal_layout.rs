@@ -1,6 +1,6 @@
 use agape_layout::{
-    BlockLayout, BoxSizing, EmptyLayout, HorizontalLayout, IntrinsicSize, Layout, Padding, Size,
-    solve_layout,
+    BlockLayout, BoxSizing, EmptyLayout, HorizontalLayout, IntrinsicSize, Layout, LayoutError,
+    OverflowAxis, Padding, Size, solve_layout,
 };
 
 #[test]
@@ -205,3 +205,32 @@ fn test_flex_factor() {
     assert!(node.children()[1].size().width == 3.0 * node.children()[0].size().width);
     assert!(node.children()[1].size().height != 3.0 * node.children()[0].size().height);
 }
+
+#[test]
+fn solve_layout_returns_overflow_errors() {
+    let window = Size::new(100.0, 100.0);
+    let mut root = HorizontalLayout {
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    root.add_child(EmptyLayout {
+        intrinsic_size: IntrinsicSize::fixed(80.0, 50.0),
+        ..Default::default()
+    });
+    root.add_child(EmptyLayout {
+        intrinsic_size: IntrinsicSize::fixed(80.0, 50.0),
+        ..Default::default()
+    });
+
+    let tree = solve_layout(&mut root, window);
+    assert_eq!(
+        tree.errors(),
+        vec![LayoutError::overflow(root.id, OverflowAxis::MainAxis)]
+    );
+
+    // Solving again doesn't keep the old errors
+    let tree = solve_layout(&mut root, window);
+    assert_eq!(tree.errors().len(), 1);
+    let tree = solve_layout(&mut root, Size::new(200.0, 100.0));
+    assert!(tree.errors().is_empty());
+}
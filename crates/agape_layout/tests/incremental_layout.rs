@@ -0,0 +1,162 @@
+use agape_core::GlobalId;
+use agape_layout::{
+    BoxSizing, EmptyLayout, HorizontalLayout, Layout, MeasureLayout, Position, Size,
+    VerticalLayout, solve_layout,
+};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// A list of rows, each with a fixed and a flex child.
+fn rows(count: usize) -> VerticalLayout {
+    let mut root = VerticalLayout::new();
+    root.intrinsic_size.width = BoxSizing::Flex(1);
+    root.spacing = 10;
+    for _ in 0..count {
+        let mut row = HorizontalLayout::new();
+        row.intrinsic_size.width = BoxSizing::Flex(1);
+        let mut icon = EmptyLayout::new();
+        icon.intrinsic_size.width = BoxSizing::Fixed(20.0);
+        icon.intrinsic_size.height = BoxSizing::Fixed(20.0);
+        let mut label = EmptyLayout::new();
+        label.intrinsic_size.width = BoxSizing::Flex(1);
+        label.intrinsic_size.height = BoxSizing::Fixed(20.0);
+        row.add_child(icon);
+        row.add_child(label);
+        root.add_child(row);
+    }
+    root
+}
+
+fn snapshot(layout: &dyn Layout) -> Vec<(GlobalId, Size, Position)> {
+    layout
+        .iter()
+        .map(|layout| (layout.id(), layout.size(), layout.position()))
+        .collect()
+}
+
+#[test]
+fn clean_layout_is_reused() {
+    let mut root = rows(5);
+    assert!(root.is_dirty());
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+    let first = snapshot(&root);
+    assert!(!root.is_dirty());
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+    assert_eq!(snapshot(&root), first);
+}
+
+#[test]
+fn resize_solves_again() {
+    let mut root = rows(3);
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+    assert_eq!(root.children[0].children()[1].size().width, 480.0);
+
+    solve_layout(&mut root, Size::new(800.0, 500.0));
+    assert_eq!(root.size().width, 800.0);
+    assert_eq!(root.children[0].children()[1].size().width, 780.0);
+}
+
+#[test]
+fn mark_dirty_marks_ancestors() {
+    let mut root = rows(3);
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    let label = root.children[1].children()[1].id();
+    assert!(root.mark_dirty(label));
+    assert!(root.is_dirty());
+    assert!(root.children[1].is_dirty());
+    assert!(!root.children[0].is_dirty());
+    assert!(!root.children[2].is_dirty());
+
+    assert!(!root.mark_dirty(GlobalId::new()));
+}
+
+#[test]
+fn dirty_layout_is_solved_again() {
+    let mut root = rows(3);
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+    assert_eq!(root.children[2].position().y, 60.0);
+
+    root.spacing = 20;
+    root.mark_dirty(root.id);
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+
+    assert_eq!(root.children[2].position().y, 80.0);
+    assert_eq!(root.size().height, 100.0);
+}
+
+#[test]
+fn changed_content_is_measured_again() {
+    let words = Arc::new(AtomicU32::new(2));
+    let count = words.clone();
+    // 10 units per word, 20 units per line
+    let text = MeasureLayout::new(move |width| {
+        let count = count.load(Ordering::Relaxed);
+        let per_line = width.map_or(count, |w| ((w / 10.0) as u32).max(1));
+        let lines = count.div_ceil(per_line);
+        Size::new(per_line.min(count) as f32 * 10.0, lines as f32 * 20.0)
+    });
+    let text_id = text.id;
+
+    let mut root = rows(1);
+    root.add_child(text);
+    solve_layout(&mut root, Size::new(50.0, 500.0));
+    assert_eq!(root.size().height, 50.0);
+
+    words.store(10, Ordering::Relaxed);
+    root.mark_dirty(text_id);
+    solve_layout(&mut root, Size::new(50.0, 500.0));
+
+    assert_eq!(root.children[1].size(), Size::new(50.0, 40.0));
+    assert_eq!(root.size().height, 70.0);
+}
+
+#[test]
+fn rebuilt_tree_reuses_previous() {
+    let mut previous = rows(3);
+    solve_layout(&mut previous, Size::new(500.0, 500.0));
+
+    let mut root = rows(3);
+    assert!(root.reuse(&previous));
+    assert!(!root.is_dirty());
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+    let sizes = |layout: &dyn Layout| {
+        layout
+            .iter()
+            .map(|layout| (layout.size(), layout.position()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(sizes(&root), sizes(&previous));
+}
+
+#[test]
+fn rebuilt_tree_with_changes() {
+    let mut previous = rows(3);
+    solve_layout(&mut previous, Size::new(500.0, 500.0));
+
+    let mut root = rows(3);
+    root.spacing = 0;
+    assert!(!root.reuse(&previous));
+    assert!(root.is_dirty());
+    assert!(!root.children[0].is_dirty());
+
+    solve_layout(&mut root, Size::new(500.0, 500.0));
+    assert_eq!(root.children[2].position().y, 40.0);
+    assert_eq!(root.children[2].children()[1].size().width, 480.0);
+}
+
+#[test]
+fn measure_layout_needs_key_to_reuse() {
+    let text = || MeasureLayout::new(|_| Size::new(20.0, 20.0));
+    let mut previous = text();
+    solve_layout(&mut previous, Size::new(500.0, 500.0));
+    assert!(!text().reuse(&previous));
+
+    let mut previous = text().key(1);
+    solve_layout(&mut previous, Size::new(500.0, 500.0));
+    assert!(text().key(1).reuse(&previous));
+    assert!(!text().key(2).reuse(&previous));
+}
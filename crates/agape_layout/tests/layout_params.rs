@@ -0,0 +1,150 @@
+use agape_layout::{
+    AxisAlignment, BoxSizing, EmptyLayout, HorizontalLayout, IntrinsicSize, Layout, LayoutParams,
+    Margin, Position, Size, VerticalLayout, solve_layout,
+};
+
+fn fixed(width: f32, height: f32) -> EmptyLayout {
+    EmptyLayout {
+        intrinsic_size: IntrinsicSize::fixed(width, height),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn horizontal_margins() {
+    let window = Size::new(500.0, 500.0);
+    let mut root = HorizontalLayout::new();
+    let params = LayoutParams {
+        margin: Margin::new(10.0, 20.0, 5.0, 0.0),
+        ..Default::default()
+    };
+    root.add_child_with_params(fixed(100.0, 50.0), params);
+    root.add_child(fixed(100.0, 50.0));
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.size(), Size::new(230.0, 55.0));
+    assert_eq!(root.children[0].position(), Position::new(10.0, 5.0));
+    assert_eq!(root.children[1].position(), Position::new(130.0, 0.0));
+}
+
+#[test]
+fn vertical_margins() {
+    let window = Size::new(500.0, 500.0);
+    let mut root = VerticalLayout::new();
+    let params = LayoutParams {
+        margin: Margin::new(5.0, 0.0, 10.0, 20.0),
+        ..Default::default()
+    };
+    root.add_child_with_params(fixed(100.0, 50.0), params);
+    root.add_child(fixed(100.0, 50.0));
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.size(), Size::new(105.0, 130.0));
+    assert_eq!(root.children[0].position(), Position::new(5.0, 10.0));
+    assert_eq!(root.children[1].position(), Position::new(0.0, 80.0));
+}
+
+#[test]
+fn horizontal_align_self() {
+    let window = Size::new(500.0, 500.0);
+    let mut root = HorizontalLayout {
+        intrinsic_size: IntrinsicSize::fixed(300.0, 200.0),
+        ..Default::default()
+    };
+    let params = LayoutParams {
+        align_self: Some(AxisAlignment::Center),
+        ..Default::default()
+    };
+    root.add_child(fixed(100.0, 50.0));
+    root.add_child_with_params(fixed(100.0, 50.0), params);
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.children[0].position().y, 0.0);
+    assert_eq!(root.children[1].position().y, 75.0);
+}
+
+#[test]
+fn vertical_align_self() {
+    let window = Size::new(500.0, 500.0);
+    let mut root = VerticalLayout {
+        intrinsic_size: IntrinsicSize::fixed(300.0, 200.0),
+        ..Default::default()
+    };
+    let params = LayoutParams {
+        align_self: Some(AxisAlignment::Center),
+        ..Default::default()
+    };
+    root.add_child(fixed(100.0, 50.0));
+    root.add_child_with_params(fixed(100.0, 50.0), params);
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.children[0].position().x, 0.0);
+    assert_eq!(root.children[1].position().x, 100.0);
+}
+
+#[test]
+fn horizontal_flex_grow() {
+    let window = Size::new(400.0, 100.0);
+    let mut root = HorizontalLayout {
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    let mut child = EmptyLayout::new();
+    child.intrinsic_size.height = BoxSizing::Fixed(50.0);
+    let params = LayoutParams {
+        flex_grow: 1,
+        ..Default::default()
+    };
+    root.add_child(fixed(100.0, 50.0));
+    root.add_child_with_params(child, params);
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.children[1].size(), Size::new(300.0, 50.0));
+}
+
+#[test]
+fn vertical_flex_grow() {
+    let window = Size::new(100.0, 400.0);
+    let mut root = VerticalLayout {
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    let mut child = EmptyLayout::new();
+    child.intrinsic_size.width = BoxSizing::Fixed(50.0);
+    let params = LayoutParams {
+        flex_grow: 1,
+        ..Default::default()
+    };
+    root.add_child(fixed(50.0, 100.0));
+    root.add_child_with_params(child, params);
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.children[1].size(), Size::new(50.0, 300.0));
+}
+
+#[test]
+fn horizontal_flex_shrink() {
+    let window = Size::new(200.0, 100.0);
+    let mut root = HorizontalLayout {
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    let params = LayoutParams {
+        flex_shrink: 1.0,
+        ..Default::default()
+    };
+    let mut child = HorizontalLayout::new();
+    child.add_child(fixed(150.0, 50.0));
+    root.add_child(fixed(100.0, 50.0));
+    root.add_child_with_params(child, params);
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.children[1].size().width, 100.0);
+}
@@ -0,0 +1,55 @@
+use agape_layout::{
+    AxisAlignment, BoxSizing, EmptyLayout, HorizontalLayout, IntrinsicSize, LayoutSnapshot,
+    Padding, Size, VerticalLayout, solve_layout,
+};
+
+fn fixed(width: f32, height: f32) -> EmptyLayout {
+    EmptyLayout {
+        intrinsic_size: IntrinsicSize::fixed(width, height),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn inline_snapshot() {
+    let mut root = HorizontalLayout::new();
+    root.spacing = 10;
+    root.padding = Padding::all(5.0);
+    root.add_child(fixed(50.0, 20.0));
+    root.add_child(fixed(30.0, 40.0));
+
+    let tree = solve_layout(&mut root, Size::new(500.0, 500.0));
+    LayoutSnapshot::new(&root, tree.errors()).assert_eq(
+        "
+        HorizontalLayout #0 size=100x50 position=(0, 0) intrinsic=shrink x shrink min=100x50 max=500x500
+          EmptyLayout #1 size=50x20 position=(5, 5) intrinsic=50 x 20 min=50x20 max=50x20
+          EmptyLayout #2 size=30x40 position=(65, 5) intrinsic=30 x 40 min=30x40 max=30x40
+        ",
+    );
+}
+
+#[test]
+fn file_snapshot() {
+    let mut header = HorizontalLayout::new();
+    header.intrinsic_size.width = BoxSizing::Flex(1);
+    header.main_axis_alignment = AxisAlignment::SpaceBetween;
+    header.add_child(fixed(80.0, 24.0));
+    header.add_child(fixed(24.0, 24.0));
+    let header_id = header.id;
+
+    let mut root = VerticalLayout::new();
+    root.intrinsic_size = IntrinsicSize::fill();
+    root.padding = Padding::all(16.0);
+    root.spacing = 8;
+    root.add_child(header);
+    root.add_child(fixed(200.0, 600.0));
+
+    let tree = solve_layout(&mut root, Size::new(400.0, 300.0));
+    LayoutSnapshot::new(&root, tree.errors())
+        .label(root.id, "page")
+        .label(header_id, "header")
+        .assert_file(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/snapshots/page.snap"
+        ));
+}
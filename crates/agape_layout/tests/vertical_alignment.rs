@@ -1,7 +1,10 @@
+mod common;
+
 use agape_layout::{
-    AxisAlignment, BoxSizing, EmptyLayout, IntrinsicSize, Padding, Position, Size, VerticalLayout,
-    solve_layout,
+    AxisAlignment, BoxSizing, EmptyLayout, IntrinsicSize, Layout, Padding, Position, Size,
+    VerticalLayout, solve_layout,
 };
+use common::fixed_children;
 
 #[test]
 fn test_single_center_alignment() {
@@ -182,17 +185,181 @@ fn test_end_alignment() {
 
     solve_layout(&mut root, window);
 
-    let mut child_2_pos = Position {
-        x: root.position.x + root.size.width,
-        y: root.position.y + root.size.height,
-    };
-    child_2_pos -= padding.right;
-
-    let mut child_1_pos = child_2_pos;
-    child_1_pos.y -= root.children[1].size().height - spacing as f32;
+    // The children's right and bottom edges line up with the padding
+    let right = root.position.x + root.size.width - padding.right;
+    let bottom = root.position.y + root.size.height - padding.bottom;
+    let child_2_pos = Position::new(right - 20.0, bottom);
+    let child_1_pos = Position::new(right - 240.0, bottom - spacing as f32 - 40.0);
 
     assert_eq!(root.children[0].position(), child_1_pos);
     assert_eq!(root.children[1].position(), child_2_pos);
 }
 
+#[test]
+fn space_between_alignment() {
+    let window = Size::new(500.0, 500.0);
+    let mut root = VerticalLayout {
+        main_axis_alignment: AxisAlignment::SpaceBetween,
+        padding: Padding::all(20.0),
+        spacing: 10,
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    root.add_children(fixed_children(50.0, 100.0, 3));
+
+    solve_layout(&mut root, window);
+
+    // 500 - 40 padding - 300 height - 20 spacing = 140
+    assert_eq!(root.children[0].position().y, 20.0);
+    assert_eq!(root.children[1].position().y, 20.0 + 100.0 + 10.0 + 70.0);
+    assert_eq!(root.children[2].position().y, 500.0 - 20.0 - 100.0);
+}
+
+#[test]
+fn space_around_alignment() {
+    let window = Size::new(400.0, 400.0);
+    let mut root = VerticalLayout {
+        main_axis_alignment: AxisAlignment::SpaceAround,
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    root.add_children(fixed_children(50.0, 100.0, 2));
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.children[0].position().y, 50.0);
+    assert_eq!(root.children[1].position().y, 250.0);
+}
+
+#[test]
+fn space_evenly_alignment() {
+    let window = Size::new(400.0, 400.0);
+    let mut root = VerticalLayout {
+        main_axis_alignment: AxisAlignment::SpaceEvenly,
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    root.add_children(fixed_children(50.0, 50.0, 3));
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.children[0].position().y, 62.5);
+    assert_eq!(root.children[1].position().y, 175.0);
+    assert_eq!(root.children[2].position().y, 287.5);
+}
+
+#[test]
+fn stretch_alignment() {
+    let window = Size::new(500.0, 500.0);
+    let mut shrink = EmptyLayout::new();
+    shrink.intrinsic_size.height = BoxSizing::Fixed(100.0);
+    let fixed = EmptyLayout {
+        intrinsic_size: IntrinsicSize::fixed(50.0, 100.0),
+        ..Default::default()
+    };
+
+    let mut root = VerticalLayout {
+        cross_axis_alignment: AxisAlignment::Stretch,
+        padding: Padding::all(24.0),
+        intrinsic_size: IntrinsicSize::fill(),
+        ..Default::default()
+    };
+    root.add_child(shrink);
+    root.add_child(fixed);
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.children[0].size(), Size::new(500.0 - 48.0, 100.0));
+    // Fixed sizes are not stretched
+    assert_eq!(root.children[1].size(), Size::new(50.0, 100.0));
+}
+
+#[test]
+fn stretch_to_widest_child() {
+    let window = Size::new(500.0, 500.0);
+    let mut narrow = EmptyLayout::new();
+    narrow.intrinsic_size.height = BoxSizing::Fixed(50.0);
+    let wide = EmptyLayout {
+        intrinsic_size: IntrinsicSize::fixed(200.0, 50.0),
+        ..Default::default()
+    };
+
+    let mut root = VerticalLayout {
+        cross_axis_alignment: AxisAlignment::Stretch,
+        ..Default::default()
+    };
+    root.add_child(narrow);
+    root.add_child(wide);
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.size(), Size::new(200.0, 100.0));
+    assert_eq!(root.children[0].size(), Size::new(200.0, 50.0));
+}
+
+#[test]
+fn stretch_inside_padding() {
+    let window = Size::new(500.0, 500.0);
+    let mut narrow = EmptyLayout::new();
+    narrow.intrinsic_size.height = BoxSizing::Fixed(50.0);
+
+    let mut root = VerticalLayout {
+        cross_axis_alignment: AxisAlignment::Stretch,
+        padding: Padding::all(10.0),
+        ..Default::default()
+    };
+    root.add_child(narrow);
+    root.add_children(fixed_children(200.0, 50.0, 1));
+
+    solve_layout(&mut root, window);
+
+    assert_eq!(root.size(), Size::new(220.0, 120.0));
+    assert_eq!(root.children[0].size(), Size::new(200.0, 50.0));
+    assert_eq!(root.children[0].position().x, 10.0);
+}
+
+#[test]
+fn end_alignment_inside_padding() {
+    let window = Size::new(500.0, 500.0);
+    let mut root = VerticalLayout {
+        main_axis_alignment: AxisAlignment::End,
+        cross_axis_alignment: AxisAlignment::End,
+        padding: Padding::new(10.0, 20.0, 30.0, 40.0),
+        spacing: 10,
+        intrinsic_size: IntrinsicSize::fixed(300.0, 200.0),
+        ..Default::default()
+    };
+    root.add_children(fixed_children(50.0, 40.0, 2));
+
+    solve_layout(&mut root, window);
+
+    // The last child's right and bottom edges touch the padding
+    assert_eq!(
+        root.children[1].position(),
+        Position::new(300.0 - 20.0 - 50.0, 200.0 - 40.0 - 40.0)
+    );
+    assert_eq!(root.children[0].position().y, 120.0 - 10.0 - 40.0);
+}
+
+#[test]
+fn center_alignment_inside_padding() {
+    let window = Size::new(500.0, 500.0);
+    let mut root = VerticalLayout {
+        main_axis_alignment: AxisAlignment::Center,
+        cross_axis_alignment: AxisAlignment::Center,
+        padding: Padding::new(10.0, 20.0, 30.0, 40.0),
+        intrinsic_size: IntrinsicSize::fixed(300.0, 200.0),
+        ..Default::default()
+    };
+    root.add_children(fixed_children(100.0, 40.0, 1));
+
+    solve_layout(&mut root, window);
+
+    // Centered in the 270x130 box inside the padding
+    assert_eq!(
+        root.children[0].position(),
+        Position::new(10.0 + 85.0, 30.0 + 45.0)
+    );
+}
+
 // TODO test overflow
@@ -29,7 +29,7 @@ pub fn expand_widget(input: DeriveInput) -> proc_macro::TokenStream {
             fn render(
                 &self,
                 renderer: &mut agape::renderer::Renderer,
-                layout: &dyn agape::layout::Layout
+                layout: &agape::layout::LayoutTree
             ) {
                 self.#child.render(renderer, layout);
             }
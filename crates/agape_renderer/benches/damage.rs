@@ -0,0 +1,46 @@
+use agape_core::Position;
+use agape_renderer::rect::Rect;
+use agape_renderer::{Renderer, Text};
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+/// Draw a view with a background, a title and a paragraph per row.
+fn rows(renderer: &mut Renderer, count: usize) {
+    for i in 0..count {
+        let y = i as f32 * 40.0;
+        renderer.draw_rect(
+            Rect::new()
+                .size(1000.0, 40.0)
+                .position(0.0, y)
+                .corner_radius(8.0),
+        );
+        let mut title = Text::new(&format!("Row {i}")).font_size(18.0);
+        title.position = Position::new(0.0, y);
+        renderer.draw_text(black_box(title));
+        let mut paragraph =
+            Text::new("The quick brown fox jumps over the lazy dog").max_width(400.0);
+        paragraph.position = Position::new(100.0, y);
+        renderer.draw_text(black_box(paragraph));
+    }
+}
+
+fn damage(c: &mut Criterion) {
+    let mut renderer = Renderer::new();
+    renderer.resize(1000, 1000);
+    let mut group = c.benchmark_group("damage");
+
+    // Recording walks the widgets a second time but only hashes the draw calls,
+    // this is the cost of a frame where nothing changed
+    group.bench_function("record 200 rows", |b| {
+        b.iter(|| {
+            renderer.record_damage(|renderer| rows(renderer, 200));
+        })
+    });
+
+    group.bench_function("draw 200 rows", |b| b.iter(|| rows(&mut renderer, 200)));
+
+    group.finish();
+}
+
+criterion_group!(benches, damage);
+criterion_main!(benches);
@@ -0,0 +1,66 @@
+use agape_core::Position;
+use agape_renderer::{Renderer, Text};
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+/// A text-heavy view with a title and a paragraph per row.
+fn rows(count: usize) -> Vec<Text<'static>> {
+    let mut texts = vec![];
+    for i in 0..count {
+        let y = i as f32 * 40.0;
+        let mut title = Text::new(&format!("Row {i}")).font_size(18.0);
+        title.position = Position::new(0.0, y);
+        let mut paragraph =
+            Text::new("The quick brown fox jumps over the lazy dog").max_width(400.0);
+        paragraph.position = Position::new(100.0, y);
+        texts.push(title);
+        texts.push(paragraph);
+    }
+    texts
+}
+
+fn text(c: &mut Criterion) {
+    let texts = rows(200);
+    let mut renderer = Renderer::new();
+    renderer.resize(1000, 1000);
+    let mut group = c.benchmark_group("text");
+
+    group.bench_function("uncached draw 200 rows", |b| {
+        b.iter(|| {
+            renderer.text_cache_mut().clear();
+            for text in &texts {
+                renderer.draw_text(black_box(text.clone()));
+            }
+        })
+    });
+
+    group.bench_function("cached draw 200 rows", |b| {
+        b.iter(|| {
+            for text in &texts {
+                renderer.draw_text(black_box(text.clone()));
+            }
+        })
+    });
+
+    group.bench_function("uncached measure 200 rows", |b| {
+        b.iter(|| {
+            renderer.text_cache_mut().clear();
+            for text in &texts {
+                black_box(renderer.text_size(text.clone()));
+            }
+        })
+    });
+
+    group.bench_function("cached measure 200 rows", |b| {
+        b.iter(|| {
+            for text in &texts {
+                black_box(renderer.text_size(text.clone()));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, text);
+criterion_main!(benches);
@@ -0,0 +1,328 @@
+use crate::text::{ShapedText, Text};
+use agape_core::Size;
+use cosmic_text::{FamilyOwned, FontSystem, Style, SwashCache, Weight};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use tiny_skia::Pixmap;
+
+/// The number of entries kept in each text cache by default.
+pub const DEFAULT_TEXT_CACHE_CAPACITY: usize = 1024;
+
+/// The number of hits, misses and evictions of a cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// The number of entries removed to make space for new ones.
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// Get the fraction of lookups that were hits, `0.0` if there haven't been
+    /// any lookups.
+    pub fn hit_rate(&self) -> f32 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f32 / lookups as f32
+    }
+}
+
+/// A cache that removes the least recently used entry when it's full.
+#[derive(Debug)]
+pub(crate) struct LruCache<K, V> {
+    capacity: usize,
+    entries: HashMap<K, (V, u64)>,
+    /// The keys ordered by the last time they were used.
+    order: BTreeMap<u64, K>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Get the value of the `key`, calling `f` to create it if it isn't
+    /// in the cache.
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce() -> V) -> &V {
+        self.tick += 1;
+        if let Some((_, tick)) = self.entries.get_mut(&key) {
+            self.stats.hits += 1;
+            let key = self.order.remove(tick).expect("Entry should be ordered");
+            *tick = self.tick;
+            self.order.insert(self.tick, key);
+        } else {
+            self.stats.misses += 1;
+            self.entries.insert(key.clone(), (f(), self.tick));
+            self.order.insert(self.tick, key.clone());
+            self.evict();
+        }
+
+        &self.entries[&key].0
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let Some((_, key)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&key);
+            self.stats.evictions += 1;
+        }
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.evict();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+/// Everything that changes how text is shaped and rasterised, apart
+/// from the color.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TextKey {
+    content: String,
+    families: Vec<FamilyOwned>,
+    weight: Weight,
+    style: Style,
+    font_size: u32,
+    line_height: u32,
+    max_width: Option<u32>,
+}
+
+impl TextKey {
+    fn new(text: &Text, max_width: Option<f32>) -> Self {
+        Self {
+            content: text.content.clone(),
+            families: text
+                .font
+                .families
+                .iter()
+                .map(|family| FamilyOwned::new(*family))
+                .collect(),
+            weight: text.font.weight,
+            style: text.font.style,
+            font_size: text.font_size.to_bits(),
+            line_height: text.line_height.to_bits(),
+            max_width: max_width.map(f32::to_bits),
+        }
+    }
+}
+
+/// Keeps shaped and rasterised text between frames, so that text that
+/// hasn't changed isn't shaped or rasterised again.
+///
+/// Shaped text is keyed by the content, font query, font size and line
+/// height, and is used to measure the text at any width. Rasterised text is
+/// also keyed by the max width and color. When a cache is full the least
+/// recently used entry is removed.
+#[derive(Debug)]
+pub struct TextCache {
+    shaped: LruCache<TextKey, ShapedText>,
+    rasterised: LruCache<(TextKey, [u8; 4]), (Size, Option<Pixmap>)>,
+}
+
+impl Default for TextCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_TEXT_CACHE_CAPACITY)
+    }
+}
+
+impl TextCache {
+    /// Create a new [`TextCache`] that keeps up to `capacity` shaped and
+    /// `capacity` rasterised texts, the capacity is at least 1.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            shaped: LruCache::new(capacity),
+            rasterised: LruCache::new(capacity),
+        }
+    }
+
+    /// Set the capacity, removing the least recently used entries if there
+    /// are more than `capacity`.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.shaped.set_capacity(capacity);
+        self.rasterised.set_capacity(capacity);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shaped.capacity
+    }
+
+    /// Get the stats of the shaped text cache.
+    pub fn shaped_stats(&self) -> CacheStats {
+        self.shaped.stats()
+    }
+
+    /// Get the stats of the rasterised text cache.
+    pub fn rasterised_stats(&self) -> CacheStats {
+        self.rasterised.stats()
+    }
+
+    /// Get the number of shaped and rasterised texts in the cache.
+    pub fn len(&self) -> usize {
+        self.shaped.len() + self.rasterised.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all the entries, the stats are kept.
+    pub fn clear(&mut self) {
+        self.shaped.clear();
+        self.rasterised.clear();
+    }
+
+    /// Get the shaped text, shaping it if it isn't in the cache.
+    pub fn shape(&mut self, text: &Text, font_system: &mut FontSystem) -> &ShapedText {
+        self.shaped
+            .get_or_insert_with(TextKey::new(text, None), || text.shape(font_system))
+    }
+
+    /// Get the rasterised text, rasterising it if it isn't in the cache.
+    /// Returns `None` if the text has no size.
+    pub fn rasterise(
+        &mut self,
+        text: &Text,
+        font_system: &mut FontSystem,
+        swash_cache: &mut SwashCache,
+    ) -> Option<&Pixmap> {
+        self.rasterise_sized(text, font_system, swash_cache).1
+    }
+
+    /// Get the rasterised text and the size it was shaped at, so that the
+    /// size doesn't have to be looked up in the shaped cache again.
+    pub(crate) fn rasterise_sized(
+        &mut self,
+        text: &Text,
+        font_system: &mut FontSystem,
+        swash_cache: &mut SwashCache,
+    ) -> (Size, Option<&Pixmap>) {
+        let (r, g, b, a) = text.color.inner();
+        let key = (TextKey::new(text, text.max_width), [r, g, b, a]);
+        let (size, glyphs) = self
+            .rasterised
+            .get_or_insert_with(key, || text.rasterise_sized(font_system, swash_cache));
+        (*size, glyphs.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn least_recently_used_is_evicted() {
+        let mut cache = LruCache::new(2);
+        cache.get_or_insert_with(1, || "a");
+        cache.get_or_insert_with(2, || "b");
+        // Use 1 so that 2 is the least recently used
+        cache.get_or_insert_with(1, || unreachable!());
+        cache.get_or_insert_with(3, || "c");
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(*cache.get_or_insert_with(1, || "d"), "a");
+        assert_eq!(*cache.get_or_insert_with(2, || "e"), "e");
+        assert_eq!(
+            cache.stats,
+            CacheStats {
+                hits: 2,
+                misses: 4,
+                evictions: 2
+            }
+        );
+    }
+
+    #[test]
+    fn shrink_capacity() {
+        let mut cache = LruCache::new(4);
+        for i in 0..4 {
+            cache.get_or_insert_with(i, || i);
+        }
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(*cache.get_or_insert_with(3, || 0), 3);
+    }
+
+    #[test]
+    fn hit_rate() {
+        let stats = CacheStats {
+            hits: 3,
+            misses: 1,
+            evictions: 0,
+        };
+        assert_eq!(stats.hit_rate(), 0.75);
+        assert_eq!(CacheStats::default().hit_rate(), 0.0);
+    }
+
+    #[test]
+    fn shaped_text_is_cached() {
+        let mut font_system = FontSystem::new();
+        let mut cache = TextCache::default();
+        let text = Text::new("Hello world");
+        cache.shape(&text, &mut font_system);
+        // The shaped text doesn't depend on the max width or color
+        cache.shape(&text.clone().max_width(20.0).color(255), &mut font_system);
+        cache.shape(&text.clone().font_size(24.0), &mut font_system);
+
+        let stats = cache.shaped_stats();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+    }
+
+    #[test]
+    fn rasterised_text_is_cached() {
+        let mut font_system = FontSystem::new();
+        let mut swash_cache = SwashCache::new();
+        let mut cache = TextCache::default();
+        let text = Text::new("Hello world");
+        let size = cache
+            .rasterise(&text, &mut font_system, &mut swash_cache)
+            .map(|pixmap| (pixmap.width(), pixmap.height()));
+        cache.rasterise(&text, &mut font_system, &mut swash_cache);
+        cache.rasterise(&text.clone().color(255), &mut font_system, &mut swash_cache);
+
+        let expected = text.size(&mut font_system);
+        assert_eq!(
+            size,
+            Some((expected.width.ceil() as u32, expected.height.ceil() as u32))
+        );
+        let stats = cache.rasterised_stats();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+    }
+
+    #[test]
+    fn empty_text_is_not_rasterised() {
+        let mut font_system = FontSystem::new();
+        let mut swash_cache = SwashCache::new();
+        let mut cache = TextCache::default();
+        let text = Text::new("");
+        assert!(
+            cache
+                .rasterise(&text, &mut font_system, &mut swash_cache)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use agape_core::{Affine, Bounds, CornerRadius, Position, Size};
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// The number of regions kept before they're merged into one.
+const MAX_REGIONS: usize = 16;
+
+/// The parts of the pixmap that changed since the last frame, in whole pixels.
+///
+/// Overlapping regions are merged, so the regions never overlap each other.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Damage {
+    regions: Vec<Bounds>,
+}
+
+impl Damage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a damaged region, merging it with the regions it overlaps. Empty
+    /// bounds are ignored.
+    pub fn add(&mut self, bounds: Bounds) {
+        if bounds.is_empty() {
+            return;
+        }
+
+        let mut bounds = bounds;
+        while let Some(index) = self
+            .regions
+            .iter()
+            .position(|region| region.intersects(&bounds))
+        {
+            bounds = bounds.union(&self.regions.swap_remove(index));
+        }
+        self.regions.push(bounds);
+
+        // Many small regions are slower to clip to than one large region
+        if self.regions.len() > MAX_REGIONS
+            && let Some(bounds) = self.bounds()
+        {
+            self.regions = vec![bounds];
+        }
+    }
+
+    pub fn regions(&self) -> &[Bounds] {
+        &self.regions
+    }
+
+    /// Get the smallest [`Bounds`] that contains all the regions.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.regions
+            .iter()
+            .copied()
+            .reduce(|bounds, region| bounds.union(&region))
+    }
+
+    /// Returns `true` if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+}
+
+/// A draw call that was recorded to find the damaged regions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DrawRecord {
+    /// The bounds that the draw call could have drawn to.
+    pub bounds: Bounds,
+    /// The hash of everything that changes what was drawn, including
+    /// the bounds, clips and layers.
+    pub fingerprint: u64,
+}
+
+/// The draw calls recorded for one frame.
+#[derive(Debug, Default)]
+pub(crate) struct Recording {
+    pub records: Vec<DrawRecord>,
+    /// The clip bounds and fingerprint of each pushed clip, layer or transform.
+    stack: Vec<(Option<Bounds>, u64)>,
+}
+
+impl Recording {
+    /// Record a draw call to the `bounds`, `hash` adds the primitive
+    /// to the fingerprint.
+    pub fn record(&mut self, bounds: Bounds, hash: impl FnOnce(&mut DefaultHasher)) {
+        let (clip, state) = self.top();
+        let bounds = match clip {
+            Some(clip) => bounds.intersection(&clip),
+            None => bounds,
+        };
+        if bounds.is_empty() {
+            return;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        state.hash(&mut hasher);
+        hash_bounds(&mut hasher, bounds);
+        hash(&mut hasher);
+        self.records.push(DrawRecord {
+            bounds,
+            fingerprint: hasher.finish(),
+        });
+    }
+
+    pub fn push_clip(&mut self, bounds: Bounds, radius: CornerRadius) {
+        let (clip, state) = self.top();
+        let bounds = match clip {
+            Some(clip) => bounds.intersection(&clip),
+            None => bounds,
+        };
+
+        let mut hasher = DefaultHasher::new();
+        state.hash(&mut hasher);
+        hash_bounds(&mut hasher, bounds);
+        for radius in radius.corners() {
+            radius.to_bits().hash(&mut hasher);
+        }
+        self.stack.push((Some(bounds), hasher.finish()));
+    }
+
+    /// Push a layer or transform, `hash` adds it to the fingerprint of
+    /// everything recorded until it's popped.
+    pub fn push_state(&mut self, hash: impl FnOnce(&mut DefaultHasher)) {
+        let (clip, state) = self.top();
+        let mut hasher = DefaultHasher::new();
+        state.hash(&mut hasher);
+        hash(&mut hasher);
+        self.stack.push((clip, hasher.finish()));
+    }
+
+    /// Pop the last clip, layer or transform.
+    pub fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    fn top(&self) -> (Option<Bounds>, u64) {
+        self.stack.last().copied().unwrap_or_default()
+    }
+}
+
+pub(crate) fn hash_bounds(hasher: &mut impl Hasher, bounds: Bounds) {
+    for value in bounds.x.iter().chain(&bounds.y) {
+        value.to_bits().hash(hasher);
+    }
+}
+
+pub(crate) fn hash_transform(hasher: &mut impl Hasher, transform: &Affine) {
+    let Affine {
+        sx,
+        ky,
+        kx,
+        sy,
+        tx,
+        ty,
+    } = *transform;
+    for value in [sx, ky, kx, sy, tx, ty] {
+        value.to_bits().hash(hasher);
+    }
+}
+
+/// Get the [`Damage`] between the draw calls of the last frame and this frame,
+/// the regions are snapped to whole pixels inside the `size`.
+pub(crate) fn diff(previous: &[DrawRecord], current: &[DrawRecord], size: Size) -> Damage {
+    let mut damaged = vec![];
+    if previous.len() == current.len() {
+        // The draw calls are usually the same, apart from the ones that changed
+        for (old, new) in previous.iter().zip(current) {
+            if old != new {
+                damaged.extend([old.bounds, new.bounds]);
+            }
+        }
+    } else {
+        // Draw calls were added or removed, so compare them as sets
+        let mut unmatched: HashMap<u64, usize> = HashMap::new();
+        for record in previous {
+            *unmatched.entry(record.fingerprint).or_default() += 1;
+        }
+        for record in current {
+            match unmatched.get_mut(&record.fingerprint) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => damaged.push(record.bounds),
+            }
+        }
+        for record in previous {
+            if let Some(count) = unmatched.get_mut(&record.fingerprint)
+                && *count > 0
+            {
+                *count -= 1;
+                damaged.push(record.bounds);
+            }
+        }
+    }
+
+    let mut damage = Damage::new();
+    for bounds in damaged {
+        damage.add(snap(bounds, size));
+    }
+    damage
+}
+
+/// Expand the bounds to whole pixels, with an extra pixel for anti-aliasing,
+/// and clip them to the `size`.
+pub(crate) fn snap(bounds: Bounds, size: Size) -> Bounds {
+    let expanded = Bounds {
+        x: [(bounds.x[0] - 1.0).floor(), (bounds.x[1] + 1.0).ceil()],
+        y: [(bounds.y[0] - 1.0).floor(), (bounds.y[1] + 1.0).ceil()],
+    };
+    expanded.intersection(&Bounds::new(Position::default(), size))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bounds(x: f32, y: f32, width: f32, height: f32) -> Bounds {
+        Bounds::new(Position::new(x, y), Size::new(width, height))
+    }
+
+    fn record(bounds: Bounds, color: u8) -> DrawRecord {
+        let mut recording = Recording::default();
+        recording.record(bounds, |hasher| color.hash(hasher));
+        recording.records[0]
+    }
+
+    #[test]
+    fn overlapping_regions_are_merged() {
+        let mut damage = Damage::new();
+        damage.add(bounds(0.0, 0.0, 20.0, 20.0));
+        damage.add(bounds(100.0, 100.0, 20.0, 20.0));
+        assert_eq!(damage.regions().len(), 2);
+
+        // Overlaps both regions
+        damage.add(bounds(10.0, 10.0, 100.0, 100.0));
+        assert_eq!(damage.regions(), &[bounds(0.0, 0.0, 120.0, 120.0)]);
+    }
+
+    #[test]
+    fn empty_regions_are_ignored() {
+        let mut damage = Damage::new();
+        damage.add(bounds(10.0, 10.0, 0.0, 20.0));
+        assert!(damage.is_empty());
+        assert_eq!(damage.bounds(), None);
+    }
+
+    #[test]
+    fn changed_records_are_damaged() {
+        let size = Size::unit(500.0);
+        let previous = [
+            record(bounds(0.0, 0.0, 50.0, 50.0), 0),
+            record(bounds(100.0, 0.0, 50.0, 50.0), 0),
+        ];
+        let current = [
+            record(bounds(0.0, 0.0, 50.0, 50.0), 0),
+            record(bounds(100.0, 0.0, 50.0, 50.0), 255),
+        ];
+        assert!(diff(&previous, &previous, size).is_empty());
+
+        let damage = diff(&previous, &current, size);
+        assert_eq!(damage.regions(), &[bounds(99.0, 0.0, 52.0, 51.0)]);
+    }
+
+    #[test]
+    fn moved_records_damage_both_bounds() {
+        let previous = [record(bounds(0.0, 0.0, 10.0, 10.0), 0)];
+        let current = [record(bounds(100.0, 0.0, 10.0, 10.0), 0)];
+
+        let damage = diff(&previous, &current, Size::unit(500.0));
+        assert_eq!(damage.regions().len(), 2);
+    }
+
+    #[test]
+    fn added_records_are_damaged() {
+        let a = record(bounds(0.0, 0.0, 10.0, 10.0), 0);
+        let b = record(bounds(100.0, 100.0, 10.0, 10.0), 0);
+
+        let damage = diff(&[a, b], &[b], Size::unit(500.0));
+        assert_eq!(damage.regions(), &[bounds(0.0, 0.0, 11.0, 11.0)]);
+        let damage = diff(&[a], &[a, b], Size::unit(500.0));
+        assert_eq!(damage.regions(), &[bounds(99.0, 99.0, 12.0, 12.0)]);
+    }
+
+    #[test]
+    fn records_are_clipped() {
+        let mut recording = Recording::default();
+        recording.push_clip(bounds(0.0, 0.0, 50.0, 50.0), CornerRadius::default());
+        recording.record(bounds(25.0, 25.0, 100.0, 100.0), |_| {});
+        // Hidden by the clip
+        recording.record(bounds(100.0, 100.0, 10.0, 10.0), |_| {});
+        recording.pop();
+
+        assert_eq!(recording.records.len(), 1);
+        assert_eq!(recording.records[0].bounds, bounds(25.0, 25.0, 25.0, 25.0));
+    }
+
+    #[test]
+    fn layers_change_the_fingerprint() {
+        let mut recording = Recording::default();
+        recording.push_state(|hasher| 50u8.hash(hasher));
+        recording.record(bounds(0.0, 0.0, 10.0, 10.0), |_| {});
+        recording.pop();
+        recording.record(bounds(0.0, 0.0, 10.0, 10.0), |_| {});
+
+        let records = &recording.records;
+        assert_ne!(records[0].fingerprint, records[1].fingerprint);
+    }
+}
@@ -0,0 +1,380 @@
+use crate::rect::Rect;
+use crate::{Image, Renderer, Svg, Text};
+use agape_core::{Affine, Bounds, Color, CornerRadius, Position, Size};
+use cosmic_text::{FamilyOwned, Style, Weight};
+use tiny_skia::BlendMode;
+
+/// A run of text in a [`DisplayList`], with the size it was measured at.
+///
+/// Unlike [`Text`] the font families are owned, so the run can be kept
+/// after the frame is drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextRun {
+    pub content: String,
+    pub font_size: f32,
+    pub line_height: f32,
+    pub max_width: Option<f32>,
+    pub position: Position,
+    pub size: Size,
+    pub families: Vec<FamilyOwned>,
+    pub weight: Weight,
+    pub style: Style,
+    pub color: Color,
+}
+
+impl TextRun {
+    pub(crate) fn new(text: &Text, size: Size) -> Self {
+        Self {
+            content: text.content.clone(),
+            font_size: text.font_size,
+            line_height: text.line_height,
+            max_width: text.max_width,
+            position: text.position,
+            size,
+            families: text
+                .font
+                .families
+                .iter()
+                .map(|family| FamilyOwned::new(*family))
+                .collect(),
+            weight: text.font.weight,
+            style: text.font.style,
+            color: text.color.clone(),
+        }
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds::new(self.position, self.size)
+    }
+
+    /// Get the [`Text`] primitive that draws this run.
+    pub fn text(&self) -> Text<'_> {
+        let mut text = Text::new(&self.content);
+        text.font_size = self.font_size;
+        text.line_height = self.line_height;
+        text.max_width = self.max_width;
+        text.position = self.position;
+        text.color = self.color.clone();
+        text.font.families = self.families.iter().map(FamilyOwned::as_family).collect();
+        text.font.weight = self.weight;
+        text.font.style = self.style;
+        text
+    }
+}
+
+/// A single command in a [`DisplayList`]. Draw commands and clips have the
+/// transform that was applied when they were recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    Rect {
+        rect: Rect,
+        transform: Affine,
+    },
+    Text {
+        run: TextRun,
+        transform: Affine,
+    },
+    Image {
+        image: Image,
+        transform: Affine,
+    },
+    Svg {
+        svg: Svg,
+        transform: Affine,
+    },
+    PushClip {
+        bounds: Bounds,
+        corner_radius: CornerRadius,
+        transform: Affine,
+    },
+    /// Clip drawing to the damaged regions of the frame.
+    PushDamageClip {
+        regions: Vec<Bounds>,
+    },
+    PopClip,
+    PushLayer {
+        bounds: Bounds,
+        opacity: f32,
+        blend_mode: BlendMode,
+        transform: Affine,
+    },
+    PopLayer,
+}
+
+impl DrawCommand {
+    /// Get the bounds that the command covers on the pixmap, `None` for
+    /// commands that don't draw anything.
+    pub fn bounds(&self) -> Option<Bounds> {
+        let (bounds, transform) = match self {
+            Self::Rect { rect, transform } => (rect.bounds(), transform),
+            Self::Text { run, transform } => (run.bounds(), transform),
+            Self::Image { image, transform } => (image.bounds(), transform),
+            Self::Svg { svg, transform } => (svg.bounds(), transform),
+            _ => return None,
+        };
+        Some(transform.map_bounds(&bounds))
+    }
+
+    fn replay(&self, renderer: &mut Renderer) {
+        match self {
+            Self::Rect { rect, transform } => {
+                transformed(renderer, transform, |r| r.draw_rect(rect.clone()))
+            }
+            Self::Text { run, transform } => {
+                transformed(renderer, transform, |r| r.draw_text(run.text()))
+            }
+            Self::Image { image, transform } => {
+                transformed(renderer, transform, |r| r.draw_image(image.clone()))
+            }
+            Self::Svg { svg, transform } => {
+                transformed(renderer, transform, |r| r.draw_svg(svg.clone()))
+            }
+            Self::PushClip {
+                bounds,
+                corner_radius,
+                transform,
+            } => transformed(renderer, transform, |r| {
+                r.push_clip(*bounds, *corner_radius)
+            }),
+            Self::PushDamageClip { regions } => renderer.push_regions_clip(regions),
+            Self::PopClip => renderer.pop_clip(),
+            Self::PushLayer {
+                bounds,
+                opacity,
+                blend_mode,
+                transform,
+            } => transformed(renderer, transform, |r| {
+                r.push_layer(*bounds, *opacity, *blend_mode)
+            }),
+            Self::PopLayer => renderer.pop_layer(),
+        }
+    }
+}
+
+/// Run `f` with the `transform` pushed onto the `renderer`.
+fn transformed(renderer: &mut Renderer, transform: &Affine, f: impl FnOnce(&mut Renderer)) {
+    if transform.is_identity() {
+        f(renderer);
+        return;
+    }
+    renderer.push_transform(*transform);
+    f(renderer);
+    renderer.pop_transform();
+}
+
+/// The draw commands of a frame, recorded by the [`Renderer`] with
+/// [`Renderer::record_display_list`].
+///
+/// A display list can be inspected to check what was drawn without looking
+/// at pixels, replayed onto another renderer or compared with another frame.
+///
+/// # Example
+/// ```
+/// use agape_renderer::{DrawCommand, Renderer};
+/// use agape_renderer::rect::Rect;
+///
+/// let mut renderer = Renderer::new();
+/// renderer.record_display_list(true);
+/// renderer.draw_rect(Rect::new().size(20.0, 20.0));
+///
+/// let list = renderer.take_display_list().unwrap();
+/// assert!(matches!(list.commands()[0], DrawCommand::Rect { .. }));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DisplayList {
+    commands: Vec<DrawCommand>,
+}
+
+impl DisplayList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DrawCommand> {
+        self.commands.iter()
+    }
+
+    /// Get the rects in the list, in the order they were drawn.
+    pub fn rects(&self) -> impl Iterator<Item = &Rect> {
+        self.iter().filter_map(|command| match command {
+            DrawCommand::Rect { rect, .. } => Some(rect),
+            _ => None,
+        })
+    }
+
+    /// Get the text runs in the list, in the order they were drawn.
+    pub fn text_runs(&self) -> impl Iterator<Item = &TextRun> {
+        self.iter().filter_map(|command| match command {
+            DrawCommand::Text { run, .. } => Some(run),
+            _ => None,
+        })
+    }
+
+    /// Draw every command onto the `renderer` again.
+    pub fn replay(&self, renderer: &mut Renderer) {
+        for command in &self.commands {
+            command.replay(renderer);
+        }
+    }
+
+    /// Get the indices of the commands that are different in the `other`
+    /// list, including the commands that only one of the lists has.
+    pub fn diff(&self, other: &DisplayList) -> Vec<usize> {
+        let len = self.len().max(other.len());
+        (0..len)
+            .filter(|&i| self.commands.get(i) != other.commands.get(i))
+            .collect()
+    }
+}
+
+impl<'a> IntoIterator for &'a DisplayList {
+    type Item = &'a DrawCommand;
+    type IntoIter = std::slice::Iter<'a, DrawCommand>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use agape_core::Color;
+
+    fn record(draw: impl FnOnce(&mut Renderer)) -> DisplayList {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.record_display_list(true);
+        draw(&mut renderer);
+        renderer.take_display_list().unwrap()
+    }
+
+    #[test]
+    fn records_draw_commands_in_order() {
+        let list = record(|renderer| {
+            renderer.push_layer(
+                Bounds::new(Position::default(), Size::unit(100.0)),
+                0.5,
+                BlendMode::SourceOver,
+            );
+            renderer.push_clip(Bounds::new(Position::default(), Size::unit(50.0)), 8.0);
+            renderer.draw_rect(Rect::new().size(20.0, 20.0).color(Color::RED));
+            renderer.draw_text(Text::new("Hello"));
+            renderer.pop_clip();
+            renderer.pop_layer();
+        });
+
+        assert_eq!(list.len(), 6);
+        assert!(matches!(
+            list.commands()[0],
+            DrawCommand::PushLayer { opacity: 0.5, .. }
+        ));
+        assert!(matches!(
+            list.commands()[1],
+            DrawCommand::PushClip { corner_radius, .. } if corner_radius == CornerRadius::all(8.0)
+        ));
+        assert_eq!(list.rects().count(), 1);
+        let run = list.text_runs().next().unwrap();
+        assert_eq!(run.content, "Hello");
+        assert!(run.size.width > 0.0);
+        assert_eq!(list.commands()[4], DrawCommand::PopClip);
+        assert_eq!(list.commands()[5], DrawCommand::PopLayer);
+    }
+
+    #[test]
+    fn commands_have_resolved_transforms() {
+        let list = record(|renderer| {
+            renderer.push_transform(Affine::translate(10.0, 0.0));
+            renderer.push_transform(Affine::scale(2.0, 2.0));
+            renderer.draw_rect(Rect::new().size(20.0, 20.0));
+            renderer.pop_transform();
+            renderer.pop_transform();
+        });
+
+        let bounds = list.commands()[0].bounds().unwrap();
+        assert_eq!(bounds.x, [10.0, 50.0]);
+        assert_eq!(bounds.y, [0.0, 40.0]);
+    }
+
+    #[test]
+    fn damage_pass_is_not_recorded() {
+        let mut renderer = Renderer::new();
+        renderer.record_display_list(true);
+        renderer.record_damage(|renderer| {
+            renderer.draw_rect(Rect::new().size(20.0, 20.0));
+        });
+        assert!(renderer.display_list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn replay_draws_the_same_frame() {
+        let draw = |renderer: &mut Renderer| {
+            renderer.push_transform(Affine::rotate(20.0).around(Position::unit(50.0)));
+            renderer.push_clip(Bounds::new(Position::unit(10.0), Size::unit(80.0)), 12.0);
+            renderer.draw_rect(Rect::new().size(100.0, 100.0).color(Color::BLUE));
+            renderer.pop_clip();
+            renderer.pop_transform();
+            renderer.push_layer(
+                Bounds::new(Position::default(), Size::unit(100.0)),
+                0.5,
+                BlendMode::SourceOver,
+            );
+            renderer.draw_rect(Rect::new().size(40.0, 40.0).color(Color::RED));
+            renderer.pop_layer();
+        };
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.record_display_list(true);
+        draw(&mut renderer);
+        let list = renderer.take_display_list().unwrap();
+
+        let mut replayed = Renderer::new();
+        replayed.resize(100, 100);
+        list.replay(&mut replayed);
+        assert_eq!(renderer.pixmap().data(), replayed.pixmap().data());
+    }
+
+    #[test]
+    fn diff_display_lists() {
+        let draw = |color| {
+            record(|renderer| {
+                renderer.draw_rect(Rect::new().size(20.0, 20.0));
+                renderer.draw_rect(Rect::new().size(20.0, 20.0).color(color));
+            })
+        };
+        let list = draw(Color::RED);
+        assert!(list.diff(&draw(Color::RED)).is_empty());
+        assert_eq!(list.diff(&draw(Color::BLUE)), [1]);
+        assert_eq!(list.diff(&DisplayList::new()), [0, 1]);
+    }
+
+    #[test]
+    fn take_keeps_recording() {
+        let mut renderer = Renderer::new();
+        renderer.record_display_list(true);
+        renderer.draw_rect(Rect::new());
+        assert_eq!(renderer.take_display_list().unwrap().len(), 1);
+        renderer.draw_rect(Rect::new());
+        assert_eq!(renderer.take_display_list().unwrap().len(), 1);
+
+        renderer.record_display_list(false);
+        renderer.draw_rect(Rect::new());
+        assert!(renderer.take_display_list().is_none());
+    }
+}
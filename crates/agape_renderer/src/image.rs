@@ -1,39 +1,691 @@
-use agape_core::{Position, Size};
+use crate::cache::{CacheStats, LruCache};
+use crate::rect::rounded_rect_path;
+use agape_core::{Bounds, CornerRadius, Position, Size};
 use image::{DynamicImage, GenericImageView};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::sync::Arc;
-use tiny_skia::{IntSize, Pixmap, PixmapPaint, Transform};
+use tiny_skia::{
+    ColorU8, FillRule, FilterQuality, IntRect, IntSize, Mask, Pixmap, PixmapPaint, PixmapRef,
+    Transform,
+};
 
-#[derive(PartialEq)]
+/// The number of decoded images that are kept by default.
+pub const DEFAULT_IMAGE_CACHE_CAPACITY: usize = 64;
+
+/// How an [`Image`] is sized inside its bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash, Default)]
+pub enum ObjectFit {
+    /// Stretch the image to fill the bounds.
+    #[default]
+    Fill,
+    /// Scale the image to fit inside the bounds, keeping its aspect ratio.
+    Contain,
+    /// Scale the image to cover the bounds, keeping its aspect ratio. The
+    /// parts outside the bounds are clipped.
+    Cover,
+    /// Keep the original size of the image, the parts outside the bounds
+    /// are clipped.
+    None,
+    /// The same as [`ObjectFit::None`] or [`ObjectFit::Contain`], whichever
+    /// is smaller.
+    ScaleDown,
+}
+
+/// The insets, in image pixels, that split an image into nine slices.
+///
+/// The corners keep their size, the edges stretch along one axis and the
+/// center stretches to fill the rest. This lets bordered panels and buttons
+/// scale without distorting their borders.
+///
+/// # Example
+/// ```
+/// use agape_renderer::image::NineSlice;
+///
+/// let slice = NineSlice::all(8.0).top(12.0);
+/// assert_eq!(slice.left, 8.0);
+/// assert_eq!(slice.top, 12.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct NineSlice {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl NineSlice {
+    pub fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// Create a [`NineSlice`] with the same inset on every side.
+    pub fn all(inset: f32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+
+    pub fn top(mut self, top: f32) -> Self {
+        self.top = top;
+        self
+    }
+
+    pub fn right(mut self, right: f32) -> Self {
+        self.right = right;
+        self
+    }
+
+    pub fn bottom(mut self, bottom: f32) -> Self {
+        self.bottom = bottom;
+        self
+    }
+
+    pub fn left(mut self, left: f32) -> Self {
+        self.left = left;
+        self
+    }
+}
+
+#[derive(Clone)]
 pub struct Image {
     pub size: Size,
     pub position: Position,
+    pub fit: ObjectFit,
+    /// Where the image is placed inside its bounds when it doesn't fill them,
+    /// from `0.0` to `1.0` on each axis. Defaults to the center.
+    pub align: Position,
+    pub corner_radius: CornerRadius,
+    pub nine_slice: Option<NineSlice>,
+    /// How the image is sampled when it's scaled or transformed, images
+    /// that are only moved are always drawn as they are.
+    pub filter_quality: FilterQuality,
     data: Arc<DynamicImage>,
+    /// The hash of the image's content, see [`Image::content_key`].
+    key: u64,
+}
+
+impl std::fmt::Debug for Image {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The image data is left out since it's too large to print
+        f.debug_struct("Image")
+            .field("size", &self.size)
+            .field("position", &self.position)
+            .field("fit", &self.fit)
+            .field("align", &self.align)
+            .field("corner_radius", &self.corner_radius)
+            .field("nine_slice", &self.nine_slice)
+            .field("filter_quality", &self.filter_quality)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Images are compared by their content key instead of their pixels, so
+/// comparing large images is cheap.
+impl PartialEq for Image {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+            && self.size == other.size
+            && self.position == other.position
+            && self.fit == other.fit
+            && self.align == other.align
+            && self.corner_radius == other.corner_radius
+            && self.nine_slice == other.nine_slice
+            && self.filter_quality == other.filter_quality
+    }
 }
 
 impl Image {
+    /// Create an [`Image`], the image's pixels are hashed to find its key so
+    /// use [`Image::with_key`] if the key is already known.
     pub fn new(image: Arc<DynamicImage>) -> Self {
+        let key = Self::content_key(&image);
+        Self::with_key(image, key)
+    }
+
+    /// Create an [`Image`] with a `key` from [`Image::content_key`] that was
+    /// found before, so that the pixels aren't hashed every frame.
+    pub fn with_key(image: Arc<DynamicImage>, key: u64) -> Self {
         let size = Size::from(image.dimensions());
         Self {
             data: image,
+            key,
             size,
             position: Position::default(),
+            fit: ObjectFit::default(),
+            align: Position::unit(0.5),
+            corner_radius: CornerRadius::default(),
+            nine_slice: None,
+            filter_quality: FilterQuality::Bilinear,
         }
     }
 
-    pub fn draw(&self, pixmap: &mut Pixmap) {
+    pub fn fit(mut self, fit: ObjectFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Set where the image is placed inside its bounds, `(0.0, 0.0)` is the
+    /// top left and `(1.0, 1.0)` is the bottom right.
+    pub fn align(mut self, x: f32, y: f32) -> Self {
+        self.align = Position::new(x, y);
+        self
+    }
+
+    /// Clip the image to rounded corners.
+    pub fn corner_radius(mut self, radius: impl Into<CornerRadius>) -> Self {
+        self.corner_radius = radius.into();
+        self
+    }
+
+    /// Draw the image in nine slices, the fit and alignment are ignored
+    /// and the image always fills its bounds.
+    pub fn nine_slice(mut self, slice: NineSlice) -> Self {
+        self.nine_slice = Some(slice);
+        self
+    }
+
+    /// Set how the image is sampled when it's scaled, use
+    /// [`FilterQuality::Nearest`] to keep pixel art sharp.
+    pub fn filter_quality(mut self, quality: FilterQuality) -> Self {
+        self.filter_quality = quality;
+        self
+    }
+
+    /// Get the key of the `image`, which is a hash of its size, color type
+    /// and pixels. Images with the same content have the same key.
+    pub fn content_key(image: &DynamicImage) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        image.dimensions().hash(&mut hasher);
+        image.color().hash(&mut hasher);
+        image.as_bytes().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub(crate) fn bounds(&self) -> Bounds {
+        Bounds::new(self.position, self.size)
+    }
+
+    /// Get the bounds the image is drawn in after it's fit and aligned,
+    /// which can be larger than the image's own bounds.
+    ///
+    /// # Example
+    /// ```
+    /// use agape_core::{Position, Size};
+    /// use agape_renderer::image::{Image, ObjectFit};
+    /// use image::DynamicImage;
+    /// use std::sync::Arc;
+    ///
+    /// let mut image = Image::new(Arc::new(DynamicImage::new_rgba8(20, 10)))
+    ///     .fit(ObjectFit::Contain);
+    /// image.size = Size::unit(100.0);
+    ///
+    /// let bounds = image.fitted_bounds();
+    /// assert_eq!(bounds.y, [25.0, 75.0]);
+    /// ```
+    pub fn fitted_bounds(&self) -> Bounds {
         let (width, height) = self.data.dimensions();
-        let data = self.data.to_rgba8().into_raw();
-        let pixmap_size = IntSize::from_wh(width, height).unwrap();
-        let image_pixmap = Pixmap::from_vec(data, pixmap_size).unwrap();
-
-        let scale_x = self.size.width / image_pixmap.width() as f32;
-        let scale_y = self.size.height / image_pixmap.height() as f32;
-        let transform = Transform::from_translate(self.position.x, self.position.y)
-            .post_scale(scale_x, scale_y);
-
-        let x = self.position.x as i32;
-        let y = self.position.y as i32;
-        let paint = PixmapPaint::default();
-        pixmap.draw_pixmap(x, y, image_pixmap.as_ref(), &paint, transform, None);
+        let natural = Size::new(width as f32, height as f32);
+        if self.nine_slice.is_some() || natural.width <= 0.0 || natural.height <= 0.0 {
+            return self.bounds();
+        }
+
+        let scale_x = self.size.width / natural.width;
+        let scale_y = self.size.height / natural.height;
+        let size = match self.fit {
+            ObjectFit::Fill => return self.bounds(),
+            ObjectFit::Contain => natural * scale_x.min(scale_y),
+            ObjectFit::Cover => natural * scale_x.max(scale_y),
+            ObjectFit::None => natural,
+            ObjectFit::ScaleDown => natural * scale_x.min(scale_y).min(1.0),
+        };
+
+        let position = Position::new(
+            (self.size.width - size.width).mul_add(self.align.x, self.position.x),
+            (self.size.height - size.height).mul_add(self.align.y, self.position.y),
+        );
+        Bounds::new(position, size)
+    }
+
+    /// Hash the image, the data is hashed by its content key.
+    pub(crate) fn hash_into(&self, hasher: &mut impl Hasher) {
+        self.key.hash(hasher);
+        self.fit.hash(hasher);
+        (self.filter_quality as u8).hash(hasher);
+        for value in [self.align.x, self.align.y] {
+            value.to_bits().hash(hasher);
+        }
+        for radius in self.corner_radius.corners() {
+            radius.to_bits().hash(hasher);
+        }
+        if let Some(slice) = &self.nine_slice {
+            for inset in [slice.top, slice.right, slice.bottom, slice.left] {
+                inset.to_bits().hash(hasher);
+            }
+        }
+    }
+
+    /// Draw the image onto the `pixmap`, using the decoded image in the `cache`.
+    pub fn draw(&self, pixmap: &mut Pixmap, cache: &mut ImageCache) {
+        self.draw_clipped(pixmap, None, cache);
+    }
+
+    /// Draw the image onto the `pixmap`, only the parts inside the `mask` are drawn.
+    pub fn draw_clipped(&self, pixmap: &mut Pixmap, mask: Option<&Mask>, cache: &mut ImageCache) {
+        self.draw_transformed(pixmap, mask, Transform::identity(), cache);
+    }
+
+    /// Draw the image with a `transform` that is applied after it's placed
+    /// at its position, using the decoded image in the `cache`.
+    pub(crate) fn draw_transformed(
+        &self,
+        pixmap: &mut Pixmap,
+        mask: Option<&Mask>,
+        transform: Transform,
+        cache: &mut ImageCache,
+    ) {
+        let Some(decoded) = cache.get(self) else {
+            return;
+        };
+
+        let clip = self.clip_mask(pixmap, mask, transform);
+        let mask = clip.as_ref().or(mask);
+
+        match &self.nine_slice {
+            Some(slice) => {
+                let image = decoded.levels[0].as_ref();
+                self.draw_slices(pixmap, image, slice, mask, transform);
+            }
+            None => {
+                let bounds = self.fitted_bounds();
+                let image = decoded.level(&bounds, transform, self.filter_quality);
+                let paint = pixmap_paint(&bounds, image, transform, self.filter_quality);
+                draw_scaled(pixmap, image, &bounds, &paint, mask, transform);
+            }
+        }
+    }
+
+    /// Get the mask that clips the image to its rounded bounds, `None` if
+    /// the image doesn't need to be clipped.
+    fn clip_mask(
+        &self,
+        pixmap: &Pixmap,
+        mask: Option<&Mask>,
+        transform: Transform,
+    ) -> Option<Mask> {
+        let bounds = self.bounds();
+        let fitted = self.fitted_bounds();
+        let overflows = fitted.intersection(&bounds) != fitted;
+        if self.corner_radius.is_zero() && !overflows {
+            return None;
+        }
+
+        let Some(path) = rounded_rect_path(self.position, self.size, self.corner_radius) else {
+            // Nothing is drawn inside empty bounds
+            return Mask::new(pixmap.width(), pixmap.height());
+        };
+        match mask {
+            Some(mask) => {
+                let mut mask = mask.clone();
+                mask.intersect_path(&path, FillRule::Winding, true, transform);
+                Some(mask)
+            }
+            None => {
+                let mut mask = Mask::new(pixmap.width(), pixmap.height())?;
+                mask.fill_path(&path, FillRule::Winding, true, transform);
+                Some(mask)
+            }
+        }
+    }
+
+    /// Draw the image in nine slices that fill the bounds. The corners are
+    /// shrunk if the bounds are too small to fit them.
+    fn draw_slices(
+        &self,
+        pixmap: &mut Pixmap,
+        image: PixmapRef,
+        slice: &NineSlice,
+        mask: Option<&Mask>,
+        transform: Transform,
+    ) {
+        let width = image.width() as f32;
+        let height = image.height() as f32;
+        let left = slice.left.clamp(0.0, width).round();
+        let right = slice.right.clamp(0.0, width - left).round();
+        let top = slice.top.clamp(0.0, height).round();
+        let bottom = slice.bottom.clamp(0.0, height - top).round();
+
+        let Size {
+            width: dst_width,
+            height: dst_height,
+        } = self.size;
+        let scale = [
+            1.0,
+            dst_width / (left + right).max(1.0),
+            dst_height / (top + bottom).max(1.0),
+        ]
+        .into_iter()
+        .fold(f32::INFINITY, f32::min)
+        .max(0.0);
+
+        let src_x = [0.0, left, width - right, width];
+        let src_y = [0.0, top, height - bottom, height];
+        let Position { x, y } = self.position;
+        let dst_x = [
+            x,
+            left.mul_add(scale, x),
+            (-right).mul_add(scale, x + dst_width),
+            x + dst_width,
+        ];
+        let dst_y = [
+            y,
+            top.mul_add(scale, y),
+            (-bottom).mul_add(scale, y + dst_height),
+            y + dst_height,
+        ];
+
+        for row in 0..3 {
+            for column in 0..3 {
+                let Some(rect) = IntRect::from_ltrb(
+                    src_x[column] as i32,
+                    src_y[row] as i32,
+                    src_x[column + 1] as i32,
+                    src_y[row + 1] as i32,
+                ) else {
+                    continue;
+                };
+                let Some(piece) = image.clone_rect(rect) else {
+                    continue;
+                };
+                let bounds = Bounds {
+                    x: [dst_x[column], dst_x[column + 1]],
+                    y: [dst_y[row], dst_y[row + 1]],
+                };
+                if bounds.is_empty() {
+                    continue;
+                }
+                let piece = piece.as_ref();
+                let paint = pixmap_paint(&bounds, piece, transform, self.filter_quality);
+                draw_scaled(pixmap, piece, &bounds, &paint, mask, transform);
+            }
+        }
+    }
+}
+
+/// Draw the `image` scaled to fill the `bounds`.
+fn draw_scaled(
+    pixmap: &mut Pixmap,
+    image: PixmapRef,
+    bounds: &Bounds,
+    paint: &PixmapPaint,
+    mask: Option<&Mask>,
+    transform: Transform,
+) {
+    pixmap.draw_pixmap(
+        0,
+        0,
+        image,
+        paint,
+        image_transform(bounds, image, transform),
+        mask,
+    );
+}
+
+/// Get the transform that scales the `image` to fill the `bounds`, and then
+/// applies the `transform`.
+fn image_transform(bounds: &Bounds, image: PixmapRef, transform: Transform) -> Transform {
+    let scale_x = (bounds.x[1] - bounds.x[0]) / image.width() as f32;
+    let scale_y = (bounds.y[1] - bounds.y[0]) / image.height() as f32;
+    // Scale before translating so that the image is drawn inside its bounds
+    Transform::from_scale(scale_x, scale_y)
+        .post_translate(bounds.x[0], bounds.y[0])
+        .post_concat(transform)
+}
+
+/// Get the paint used to draw the `image` in the `bounds`, images that are
+/// only moved are drawn as they are and anything else uses the `quality`.
+fn pixmap_paint(
+    bounds: &Bounds,
+    image: PixmapRef,
+    transform: Transform,
+    quality: FilterQuality,
+) -> PixmapPaint {
+    let mut paint = crate::paint::pixmap_paint(image_transform(bounds, image, transform));
+    if paint.quality != FilterQuality::Nearest {
+        paint.quality = quality;
+    }
+    paint
+}
+
+/// An image that has been converted to premultiplied pixmaps, the first
+/// level is the full image and each level after it is half the size of the
+/// one before.
+#[derive(Debug)]
+pub(crate) struct DecodedImage {
+    levels: Vec<Pixmap>,
+}
+
+impl DecodedImage {
+    /// Decode the `image`, an empty image has no levels.
+    fn new(image: &DynamicImage) -> Self {
+        let levels = Self::decode(image).map_or_else(Vec::new, |pixmap| {
+            let mut levels = vec![pixmap];
+            while let Some(level) = levels.last().and_then(|level| downsample(level.as_ref())) {
+                levels.push(level);
+            }
+            levels
+        });
+        Self { levels }
+    }
+
+    fn decode(image: &DynamicImage) -> Option<Pixmap> {
+        let (width, height) = image.dimensions();
+        let mut data = image.to_rgba8().into_raw();
+        // Pixmaps are premultiplied, images aren't
+        for pixel in data.chunks_exact_mut(4) {
+            let color = ColorU8::from_rgba(pixel[0], pixel[1], pixel[2], pixel[3]).premultiply();
+            pixel.copy_from_slice(&[color.red(), color.green(), color.blue(), color.alpha()]);
+        }
+        Pixmap::from_vec(data, IntSize::from_wh(width, height)?)
+    }
+
+    /// Get the smallest level that is at least as large as the image is
+    /// drawn, so large images that are drawn small aren't aliased. Images
+    /// drawn with [`FilterQuality::Nearest`] always use the full image.
+    fn level(
+        &self,
+        bounds: &Bounds,
+        transform: Transform,
+        quality: FilterQuality,
+    ) -> PixmapRef<'_> {
+        let full = self.levels[0].as_ref();
+        if quality == FilterQuality::Nearest {
+            return full;
+        }
+        let scale_x =
+            (bounds.x[1] - bounds.x[0]) / full.width() as f32 * transform.sx.hypot(transform.ky);
+        let scale_y =
+            (bounds.y[1] - bounds.y[0]) / full.height() as f32 * transform.kx.hypot(transform.sy);
+        let scale = scale_x.max(scale_y);
+        if !scale.is_finite() || scale <= 0.0 {
+            return full;
+        }
+        let level = (1.0 / scale).log2().floor().max(0.0) as usize;
+        self.levels[level.min(self.levels.len() - 1)].as_ref()
+    }
+}
+
+/// Get a copy of the `pixmap` that is half the size, each pixel is the
+/// average of four pixels. Returns `None` if the pixmap can't be made smaller.
+fn downsample(pixmap: PixmapRef) -> Option<Pixmap> {
+    let (width, height) = (pixmap.width(), pixmap.height());
+    if width <= 1 && height <= 1 {
+        return None;
+    }
+    let mut level = Pixmap::new((width / 2).max(1), (height / 2).max(1))?;
+    let source = pixmap.data();
+    let level_width = level.width();
+    for (i, pixel) in level.data_mut().chunks_exact_mut(4).enumerate() {
+        let x = (i as u32 % level_width) * 2;
+        let y = (i as u32 / level_width) * 2;
+        let mut sum = [0u32; 4];
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let index = (((y + dy).min(height - 1) * width + (x + dx).min(width - 1)) * 4) as usize;
+            for channel in 0..4 {
+                sum[channel] += source[index + channel] as u32;
+            }
+        }
+        for channel in 0..4 {
+            pixel[channel] = ((sum[channel] + 2) / 4) as u8;
+        }
+    }
+    Some(level)
+}
+
+/// Keeps images decoded into premultiplied pixmaps between frames, with
+/// smaller copies for drawing them at smaller sizes. Images are looked up
+/// by their [content key](Image::content_key), so images with the same
+/// pixels are only decoded once even if they're loaded again.
+#[derive(Debug)]
+pub struct ImageCache {
+    images: LruCache<u64, DecodedImage>,
+}
+
+impl Default for ImageCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_IMAGE_CACHE_CAPACITY)
+    }
+}
+
+impl ImageCache {
+    /// Create an [`ImageCache`] that keeps up to `capacity` images.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            images: LruCache::new(capacity),
+        }
+    }
+
+    /// Get the decoded `image`, decoding it if it isn't in the cache.
+    /// Returns `None` if the image is empty.
+    pub(crate) fn get(&mut self, image: &Image) -> Option<&DecodedImage> {
+        let decoded = self
+            .images
+            .get_or_insert_with(image.key, || DecodedImage::new(&image.data));
+        (!decoded.levels.is_empty()).then_some(decoded)
+    }
+
+    /// Get the number of decoded images.
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the hit and miss stats of the cache.
+    pub fn stats(&self) -> CacheStats {
+        self.images.stats()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use image::{ImageBuffer, Rgba};
+
+    fn image(width: u32, height: u32, color: [u8; 4]) -> Arc<DynamicImage> {
+        let buf = ImageBuffer::from_pixel(width, height, Rgba(color));
+        Arc::new(DynamicImage::ImageRgba8(buf))
+    }
+
+    #[test]
+    fn transparent_images_are_premultiplied() {
+        let mut pixmap = Pixmap::new(10, 10).unwrap();
+        pixmap.fill(tiny_skia::Color::WHITE);
+        let mut image = Image::new(image(1, 1, [255, 0, 0, 128]));
+        image.size = Size::unit(10.0);
+        image.draw(&mut pixmap, &mut ImageCache::default());
+
+        let pixel = pixmap.pixel(5, 5).unwrap();
+        assert_eq!(pixel.red(), 255);
+        assert!((126..=128).contains(&pixel.green()));
+    }
+
+    #[test]
+    fn decoded_levels_halve_in_size() {
+        let decoded = DecodedImage::new(&image(16, 4, [0, 0, 0, 255]));
+        let sizes: Vec<_> = decoded
+            .levels
+            .iter()
+            .map(|level| (level.width(), level.height()))
+            .collect();
+        assert_eq!(sizes, [(16, 4), (8, 2), (4, 1), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn small_draws_use_smaller_levels() {
+        let decoded = DecodedImage::new(&image(64, 64, [0, 0, 0, 255]));
+        let level = |size: f32, quality| {
+            let bounds = Bounds::new(Position::default(), Size::unit(size));
+            decoded
+                .level(&bounds, Transform::identity(), quality)
+                .width()
+        };
+        assert_eq!(level(64.0, FilterQuality::Bilinear), 64);
+        assert_eq!(level(200.0, FilterQuality::Bilinear), 64);
+        assert_eq!(level(20.0, FilterQuality::Bilinear), 32);
+        assert_eq!(level(16.0, FilterQuality::Bicubic), 16);
+        assert_eq!(level(0.5, FilterQuality::Bilinear), 1);
+        assert_eq!(level(16.0, FilterQuality::Nearest), 64);
+    }
+
+    #[test]
+    fn scaled_transforms_use_smaller_levels() {
+        let decoded = DecodedImage::new(&image(64, 64, [0, 0, 0, 255]));
+        let bounds = Bounds::new(Position::default(), Size::unit(64.0));
+        let transform = Transform::from_scale(0.25, 0.25);
+        let level = decoded.level(&bounds, transform, FilterQuality::Bilinear);
+        assert_eq!(level.width(), 16);
+    }
+
+    #[test]
+    fn images_are_decoded_once() {
+        let data = image(4, 4, [0, 0, 0, 255]);
+        let mut cache = ImageCache::default();
+        let mut pixmap = Pixmap::new(10, 10).unwrap();
+        for _ in 0..3 {
+            let image = Image::new(data.clone());
+            image.draw_transformed(&mut pixmap, None, Transform::identity(), &mut cache);
+        }
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.stats().misses, 1);
+        assert_eq!(cache.stats().hits, 2);
+    }
+
+    #[test]
+    fn images_with_the_same_content_share_a_decode() {
+        let mut cache = ImageCache::default();
+        let mut pixmap = Pixmap::new(10, 10).unwrap();
+        for _ in 0..2 {
+            // A new allocation every time, like an image that is loaded again
+            let image = Image::new(image(4, 4, [0, 0, 0, 255]));
+            image.draw(&mut pixmap, &mut cache);
+        }
+        Image::new(image(4, 4, [255, 0, 0, 255])).draw(&mut pixmap, &mut cache);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.stats().hits, 1);
+    }
+
+    #[test]
+    fn empty_images_are_not_drawn() {
+        let mut cache = ImageCache::default();
+        assert!(
+            cache
+                .get(&Image::new(image(0, 0, [0, 0, 0, 255])))
+                .is_none()
+        );
     }
 }
@@ -1,18 +1,31 @@
+pub mod cache;
+pub mod damage;
+pub mod display_list;
 pub mod image;
+mod paint;
 pub mod rect;
+mod shadow;
 pub mod svg;
 pub mod text;
 
-pub use crate::image::Image;
-use crate::rect::Rect;
-pub use crate::text::{FontQuery, Text};
-use agape_core::Size;
+pub use crate::cache::{CacheStats, TextCache};
+pub use crate::damage::Damage;
+use crate::damage::{DrawRecord, Recording, diff, hash_transform};
+pub use crate::display_list::{DisplayList, DrawCommand, TextRun};
+pub use crate::image::{Image, ImageCache, NineSlice, ObjectFit};
+use crate::rect::{Rect, rounded_rect_path};
+use crate::shadow::ShadowCache;
+pub use crate::text::{FontQuery, ShapedText, Text};
+use ::image::RgbaImage;
+use agape_core::{Affine, Bounds, CornerRadius, Position, Size};
 use cosmic_text::fontdb::Database;
 pub use cosmic_text::{Family, Style, Weight};
 use cosmic_text::{FontSystem, SwashCache};
+use std::hash::Hash;
 use std::path::Path;
 pub use svg::Svg;
-use tiny_skia::Pixmap;
+pub use tiny_skia::{BlendMode, FilterQuality};
+use tiny_skia::{FillRule, IntSize, Mask, Pixmap, PixmapPaint, Transform};
 
 // TODO: mention that only ttf/otf fonts are supported
 
@@ -21,7 +34,116 @@ pub struct Renderer {
     font_system: FontSystem,
     /// The [`SwashCache`] stores rasterised glyphs.
     swash_cache: SwashCache,
+    /// Shaped and rasterised text that is kept between frames.
+    text_cache: TextCache,
+    /// Blurred box shadows that are kept between frames.
+    shadow_cache: ShadowCache,
+    /// Decoded images that are kept between frames.
+    image_cache: ImageCache,
     pixmap: Pixmap,
+    /// The clip masks, each mask is the intersection of itself and the
+    /// masks below it.
+    clips: Vec<Mask>,
+    /// The offscreen layers, drawing goes to the last layer if there is one.
+    layers: Vec<Layer>,
+    /// The transforms, each transform is combined with the transforms
+    /// below it.
+    transforms: Vec<Affine>,
+    /// The draw calls being recorded by [`Renderer::record_damage`], nothing
+    /// is drawn while recording.
+    recording: Option<Recording>,
+    /// The draw calls of the last recorded frame, `None` if the whole
+    /// pixmap needs to be drawn.
+    last_frame: Option<Vec<DrawRecord>>,
+    damage: Damage,
+    /// The draw commands recorded since the last time the display list
+    /// was taken, `None` if they aren't being recorded.
+    display_list: Option<DisplayList>,
+}
+
+/// An offscreen pixmap that is composited onto the layer below it when
+/// it's popped. A layer only covers the bounds it was pushed with.
+struct Layer {
+    pixmap: Pixmap,
+    /// Where the top left corner of the layer is on the renderer's pixmap.
+    origin: (i32, i32),
+    opacity: f32,
+    blend_mode: BlendMode,
+    /// The clip that was in place when the layer was pushed, cropped to the layer.
+    clip: Option<Mask>,
+    /// The number of clips when the layer was pushed, the clips pushed after
+    /// it are the size of the layer.
+    clip_depth: usize,
+}
+
+/// The pixmap that is being drawn onto, with its clip and where it is on
+/// the renderer's pixmap.
+struct Canvas<'a> {
+    pixmap: &'a mut Pixmap,
+    clip: Option<&'a Mask>,
+    origin: (i32, i32),
+}
+
+impl Canvas<'_> {
+    /// Convert a `transform` into one that draws onto the canvas.
+    fn transform(&self, transform: &Affine) -> Transform {
+        skia_transform(transform).post_translate(-self.origin.0 as f32, -self.origin.1 as f32)
+    }
+}
+
+/// Convert an [`Affine`] to a `tiny_skia` transform.
+fn skia_transform(transform: &Affine) -> Transform {
+    let Affine {
+        sx,
+        ky,
+        kx,
+        sy,
+        tx,
+        ty,
+    } = *transform;
+    Transform::from_row(sx, ky, kx, sy, tx, ty)
+}
+
+/// Get the [`Canvas`] that is currently being drawn onto, which is the last
+/// layer if there is one.
+fn canvas<'a>(pixmap: &'a mut Pixmap, layers: &'a mut [Layer], clips: &'a [Mask]) -> Canvas<'a> {
+    let Some(layer) = layers.last_mut() else {
+        return Canvas {
+            pixmap,
+            clip: clips.last(),
+            origin: (0, 0),
+        };
+    };
+    let clip = match clips.len() > layer.clip_depth {
+        true => clips.last(),
+        false => layer.clip.as_ref(),
+    };
+    Canvas {
+        pixmap: &mut layer.pixmap,
+        clip,
+        origin: layer.origin,
+    }
+}
+
+/// Copy the part of the `mask` that's under a pixmap of the `size`, placed at `origin`.
+fn crop_mask(mask: &Mask, origin: (i32, i32), size: IntSize) -> Option<Mask> {
+    let mut cropped = Mask::new(size.width(), size.height())?;
+    let (width, height) = (mask.width() as i32, mask.height() as i32);
+    let row_width = size.width() as usize;
+    for y in 0..size.height() as i32 {
+        let source_y = y + origin.1;
+        if !(0..height).contains(&source_y) {
+            continue;
+        }
+        let row = &mut cropped.data_mut()[y as usize * row_width..][..row_width];
+        for (x, value) in row.iter_mut().enumerate() {
+            let source_x = x as i32 + origin.0;
+            if (0..width).contains(&source_x) {
+                *value = mask.data()[(source_y * width + source_x) as usize];
+            }
+        }
+    }
+    Some(cropped)
 }
 
 impl Default for Renderer {
@@ -41,7 +163,17 @@ impl Renderer {
         Self {
             font_system,
             swash_cache,
+            text_cache: TextCache::default(),
+            shadow_cache: ShadowCache::default(),
+            image_cache: ImageCache::default(),
             pixmap,
+            clips: Vec::new(),
+            layers: Vec::new(),
+            transforms: Vec::new(),
+            recording: None,
+            last_frame: None,
+            damage: Damage::new(),
+            display_list: None,
         }
     }
 
@@ -64,14 +196,271 @@ impl Renderer {
         self.font_system.db_mut().load_fonts_dir(path)
     }
 
-    /// Resize the `Pixmap`. Does nothing if the `width` or `height` is 0.
+    /// Resize the `Pixmap`, this removes all the clips, layers and transforms
+    /// and damages the whole pixmap. Does nothing if the `width` or `height` is 0.
     pub fn resize(&mut self, width: u32, height: u32) {
         if width > 0 && height > 0 {
             self.pixmap = Pixmap::new(width, height).unwrap();
+            self.clips.clear();
+            self.layers.clear();
+            self.transforms.clear();
+            self.invalidate();
+        }
+    }
+
+    /// Get the size of the `Pixmap`.
+    pub fn size(&self) -> Size {
+        Size::new(self.pixmap.width() as f32, self.pixmap.height() as f32)
+    }
+
+    /// Record what `draw` would draw, without drawing it, and compare it with
+    /// the last recorded frame to find the parts of the pixmap that changed.
+    ///
+    /// Draw calls are compared by their bounds and everything that changes
+    /// how they look, so the same frame gives no damage. The whole pixmap is
+    /// damaged the first time, after a resize or after [`Renderer::invalidate`].
+    /// Anything drawn with [`Renderer::pixmap_mut`] isn't tracked.
+    ///
+    /// `draw` is called once more than it would be without damage tracking, but
+    /// draw calls are only measured and hashed so this is much cheaper than
+    /// drawing, see the `damage` benchmark.
+    pub fn record_damage(&mut self, draw: impl FnOnce(&mut Self)) -> &Damage {
+        self.recording = Some(Recording::default());
+        draw(self);
+        let recording = self.recording.take().unwrap_or_default();
+
+        let size = self.size();
+        self.damage = match &self.last_frame {
+            Some(last_frame) => diff(last_frame, &recording.records, size),
+            None => {
+                let mut damage = Damage::new();
+                damage.add(Bounds::new(Position::default(), size));
+                damage
+            }
+        };
+        self.last_frame = Some(recording.records);
+        &self.damage
+    }
+
+    /// Get the [`Damage`] found by the last [`Renderer::record_damage`], a
+    /// backend only needs to upload these regions of the pixmap.
+    pub fn damage(&self) -> &Damage {
+        &self.damage
+    }
+
+    /// Damage the whole pixmap the next time damage is recorded.
+    pub fn invalidate(&mut self) {
+        self.last_frame = None;
+    }
+
+    /// Clip drawing to the damaged regions, until [`Renderer::pop_clip`] is
+    /// called, so that only the parts that changed are drawn again.
+    pub fn push_damage_clip(&mut self) {
+        let regions = self.damage.regions().to_vec();
+        self.push_regions_clip(&regions);
+    }
+
+    /// Clip drawing to the union of the `regions`.
+    pub(crate) fn push_regions_clip(&mut self, regions: &[Bounds]) {
+        if let Some(list) = &mut self.display_list {
+            list.push(DrawCommand::PushDamageClip {
+                regions: regions.to_vec(),
+            });
+        }
+        let canvas = canvas(&mut self.pixmap, &mut self.layers, &self.clips);
+        let transform = canvas.transform(&Affine::identity());
+        let mut mask = Mask::new(canvas.pixmap.width(), canvas.pixmap.height()).unwrap();
+        for region in regions {
+            let position = Position::new(region.x[0], region.y[0]);
+            let size = Size::new(region.x[1] - region.x[0], region.y[1] - region.y[0]);
+            if let Some(path) = rounded_rect_path(position, size, 0.0) {
+                mask.fill_path(&path, FillRule::Winding, false, transform);
+            }
+        }
+        if let Some(clip) = canvas.clip {
+            let mut clip = clip.clone();
+            for (value, damaged) in clip.data_mut().iter_mut().zip(mask.data()) {
+                *value = (*value).min(*damaged);
+            }
+            mask = clip;
+        }
+        self.clips.push(mask);
+    }
+
+    /// Clip everything drawn after this to the `bounds` with rounded corners of
+    /// `radius`, until [`Renderer::pop_clip`] is called. Clips can be nested, in
+    /// which case drawing is clipped to all of them.
+    pub fn push_clip(&mut self, bounds: Bounds, radius: impl Into<CornerRadius>) {
+        let radius = radius.into();
+        let transform = self.transform();
+        if let Some(recording) = &mut self.recording {
+            recording.push_clip(transform.map_bounds(&bounds), radius);
+            return;
+        }
+        self.record_command(|| DrawCommand::PushClip {
+            bounds,
+            corner_radius: radius,
+            transform,
+        });
+        let canvas = canvas(&mut self.pixmap, &mut self.layers, &self.clips);
+        let transform = canvas.transform(&transform);
+        let (width, height) = (canvas.pixmap.width(), canvas.pixmap.height());
+
+        let position = Position::new(bounds.x[0], bounds.y[0]);
+        let size = Size::new(bounds.x[1] - bounds.x[0], bounds.y[1] - bounds.y[0]);
+        let path = rounded_rect_path(position, size, radius);
+
+        let mask = match (canvas.clip, path) {
+            (Some(clip), Some(path)) => {
+                let mut mask = clip.clone();
+                mask.intersect_path(&path, FillRule::Winding, true, transform);
+                mask
+            }
+            (None, Some(path)) => {
+                let mut mask = Mask::new(width, height).unwrap();
+                mask.fill_path(&path, FillRule::Winding, true, transform);
+                mask
+            }
+            // Nothing is drawn inside empty bounds
+            (_, None) => Mask::new(width, height).unwrap(),
+        };
+        self.clips.push(mask);
+    }
+
+    /// Remove the last clip added with [`Renderer::push_clip`].
+    pub fn pop_clip(&mut self) {
+        if let Some(recording) = &mut self.recording {
+            recording.pop();
+            return;
+        }
+        self.record_command(|| DrawCommand::PopClip);
+        self.clips.pop();
+    }
+
+    /// Get the number of clips that are currently pushed.
+    pub fn clip_depth(&self) -> usize {
+        self.clips.len()
+    }
+
+    /// Draw everything after this onto an offscreen layer, until
+    /// [`Renderer::pop_layer`] is called. The layer is then drawn onto the layer
+    /// below it with the `opacity` and `blend_mode`, so a group of overlapping
+    /// shapes fades as one.
+    ///
+    /// The layer only covers the `bounds`, anything drawn outside them is lost.
+    pub fn push_layer(&mut self, bounds: Bounds, opacity: f32, blend_mode: BlendMode) {
+        let transform = self.transform();
+        if let Some(recording) = &mut self.recording {
+            let bounds = transform.map_bounds(&bounds);
+            recording.push_state(|hasher| {
+                opacity.to_bits().hash(hasher);
+                (blend_mode as u8).hash(hasher);
+                for value in [bounds.x, bounds.y].concat() {
+                    value.to_bits().hash(hasher);
+                }
+            });
+            return;
+        }
+        self.record_command(|| DrawCommand::PushLayer {
+            bounds,
+            opacity,
+            blend_mode,
+            transform,
+        });
+
+        let clip_depth = self.clips.len();
+        let canvas = canvas(&mut self.pixmap, &mut self.layers, &self.clips);
+        // Only the part of the bounds that's on the canvas below can be seen
+        let bounds = transform.map_bounds(&bounds);
+        let left = (bounds.x[0].floor() as i32).max(canvas.origin.0);
+        let top = (bounds.y[0].floor() as i32).max(canvas.origin.1);
+        let right = (bounds.x[1].ceil() as i32).min(canvas.origin.0 + canvas.pixmap.width() as i32);
+        let bottom =
+            (bounds.y[1].ceil() as i32).min(canvas.origin.1 + canvas.pixmap.height() as i32);
+
+        // Nothing drawn onto an empty layer is visible, so it's drawn onto a
+        // single pixel that's thrown away
+        let (origin, size, opacity) =
+            match IntSize::from_wh((right - left).max(0) as u32, (bottom - top).max(0) as u32) {
+                Some(size) => ((left, top), size, opacity.clamp(0.0, 1.0)),
+                None => ((0, 0), IntSize::from_wh(1, 1).unwrap(), 0.0),
+            };
+        let offset = (origin.0 - canvas.origin.0, origin.1 - canvas.origin.1);
+        let clip = canvas.clip.and_then(|clip| crop_mask(clip, offset, size));
+        self.layers.push(Layer {
+            pixmap: Pixmap::new(size.width(), size.height()).unwrap(),
+            origin,
+            opacity,
+            blend_mode,
+            clip,
+            clip_depth,
+        });
+    }
+
+    /// Composite the last layer added with [`Renderer::push_layer`] onto the
+    /// layer below it, clipped to the current clip.
+    pub fn pop_layer(&mut self) {
+        if let Some(recording) = &mut self.recording {
+            recording.pop();
+            return;
+        }
+        self.record_command(|| DrawCommand::PopLayer);
+
+        let Some(layer) = self.layers.pop() else {
+            return;
+        };
+        if layer.opacity <= 0.0 {
+            return;
         }
+
+        let paint = PixmapPaint {
+            opacity: layer.opacity,
+            blend_mode: layer.blend_mode,
+            ..Default::default()
+        };
+        let canvas = canvas(&mut self.pixmap, &mut self.layers, &self.clips);
+        canvas.pixmap.draw_pixmap(
+            layer.origin.0 - canvas.origin.0,
+            layer.origin.1 - canvas.origin.1,
+            layer.pixmap.as_ref(),
+            &paint,
+            Transform::identity(),
+            canvas.clip,
+        );
+    }
+
+    /// Transform everything drawn after this, until [`Renderer::pop_transform`]
+    /// is called. Transforms can be nested, in which case the `transform` is
+    /// applied before the transforms that are already pushed. Clips pushed
+    /// after this are transformed too.
+    pub fn push_transform(&mut self, transform: Affine) {
+        let transform = transform.then(self.transform());
+        if let Some(recording) = &mut self.recording {
+            recording.push_state(|hasher| hash_transform(hasher, &transform));
+        }
+        self.transforms.push(transform);
+    }
+
+    /// Remove the last transform added with [`Renderer::push_transform`].
+    pub fn pop_transform(&mut self) {
+        if let Some(recording) = &mut self.recording {
+            recording.pop();
+        }
+        self.transforms.pop();
     }
 
-    /// Get a reference to the `Pixmap`.
+    /// Get the transform that is currently applied to everything drawn.
+    pub fn transform(&self) -> Affine {
+        self.transforms.last().copied().unwrap_or_default()
+    }
+
+    /// Get the number of layers that are currently pushed.
+    pub fn layer_depth(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Get a reference to the `Pixmap`, layers that haven't been popped
+    /// aren't drawn onto it yet.
     pub fn pixmap(&self) -> &Pixmap {
         &self.pixmap
     }
@@ -81,39 +470,410 @@ impl Renderer {
         &mut self.pixmap
     }
 
+    /// Copy the `Pixmap` into an [`RgbaImage`], the pixmap is premultiplied
+    /// but the image isn't.
+    pub fn to_image(&self) -> RgbaImage {
+        let mut data = Vec::with_capacity(self.pixmap.data().len());
+        for pixel in self.pixmap.pixels() {
+            let color = pixel.demultiply();
+            data.extend([color.red(), color.green(), color.blue(), color.alpha()]);
+        }
+        RgbaImage::from_raw(self.pixmap.width(), self.pixmap.height(), data)
+            .expect("Pixmap data should fit the image")
+    }
+
+    /// Save the `Pixmap` as a png file.
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), ::image::ImageError> {
+        self.to_image()
+            .save_with_format(path, ::image::ImageFormat::Png)
+    }
+
     /// Draw an svg onto the pixmap.
     pub fn draw_svg(&mut self, svg: Svg) {
-        svg.draw(&mut self.pixmap);
+        let transform = self.transform();
+        if let Some(recording) = &mut self.recording {
+            recording.record(transform.map_bounds(&svg.bounds()), |hasher| {
+                svg.hash_into(hasher)
+            });
+            return;
+        }
+        let canvas = canvas(&mut self.pixmap, &mut self.layers, &self.clips);
+        let skia_transform = canvas.transform(&transform);
+        svg.draw_transformed(canvas.pixmap, canvas.clip, skia_transform);
+        self.record_command(|| DrawCommand::Svg { svg, transform });
     }
 
     /// Draw an image onto the pixmap.
     pub fn draw_image(&mut self, image: Image) {
-        image.draw(&mut self.pixmap);
+        let transform = self.transform();
+        if let Some(recording) = &mut self.recording {
+            recording.record(transform.map_bounds(&image.bounds()), |hasher| {
+                image.hash_into(hasher)
+            });
+            return;
+        }
+        let canvas = canvas(&mut self.pixmap, &mut self.layers, &self.clips);
+        let skia_transform = canvas.transform(&transform);
+        image.draw_transformed(
+            canvas.pixmap,
+            canvas.clip,
+            skia_transform,
+            &mut self.image_cache,
+        );
+        self.record_command(|| DrawCommand::Image { image, transform });
     }
 
     /// Draw a rectangle onto the `Pixmap`.
     pub fn draw_rect(&mut self, rect: Rect) {
-        rect.draw(&mut self.pixmap);
+        let transform = self.transform();
+        if let Some(recording) = &mut self.recording {
+            recording.record(transform.map_bounds(&rect.bounds()), |hasher| {
+                rect.hash_into(hasher)
+            });
+            return;
+        }
+        let canvas = canvas(&mut self.pixmap, &mut self.layers, &self.clips);
+        let skia_transform = canvas.transform(&transform);
+        rect.draw(
+            canvas.pixmap,
+            canvas.clip,
+            skia_transform,
+            &mut self.shadow_cache,
+        );
+        self.record_command(|| DrawCommand::Rect { rect, transform });
     }
 
-    /// Draw text onto the `Pixmap`.
+    /// Draw text onto the `Pixmap`, text that has been drawn before is
+    /// drawn from the [`TextCache`].
     pub fn draw_text(&mut self, text: Text) {
-        text.draw_text(
-            &mut self.pixmap,
-            &mut self.font_system,
-            &mut self.swash_cache,
-        )
+        let transform = self.transform();
+        if let Some(recording) = &mut self.recording {
+            let size = self
+                .text_cache
+                .shape(&text, &mut self.font_system)
+                .size(text.max_width);
+            let bounds = transform.map_bounds(&Bounds::new(text.position, size));
+            recording.record(bounds, |hasher| text.hash_into(hasher));
+            return;
+        }
+        let (size, glyphs) =
+            self.text_cache
+                .rasterise_sized(&text, &mut self.font_system, &mut self.swash_cache);
+        if let Some(glyphs) = glyphs {
+            let canvas = canvas(&mut self.pixmap, &mut self.layers, &self.clips);
+            let skia_transform = canvas.transform(&transform);
+            text.draw_rasterised(canvas.pixmap, glyphs, canvas.clip, skia_transform);
+        }
+        self.record_command(|| DrawCommand::Text {
+            run: TextRun::new(&text, size),
+            transform,
+        });
     }
 
     /// Get the text size.
     pub fn text_size(&mut self, text: Text) -> Size {
-        text.size(&mut self.font_system)
+        self.text_cache
+            .shape(&text, &mut self.font_system)
+            .size(text.max_width)
+    }
+
+    /// Shape the text so that it can be measured at any width.
+    pub fn shape_text(&mut self, text: Text) -> ShapedText {
+        self.text_cache.shape(&text, &mut self.font_system).clone()
+    }
+
+    /// Get the [`TextCache`], which has the hit and miss stats.
+    pub fn text_cache(&self) -> &TextCache {
+        &self.text_cache
+    }
+
+    /// Get a `&mut` to the [`TextCache`], to change its capacity or clear it.
+    pub fn text_cache_mut(&mut self) -> &mut TextCache {
+        &mut self.text_cache
+    }
+
+    /// Get the [`ImageCache`], which has the hit and miss stats.
+    pub fn image_cache(&self) -> &ImageCache {
+        &self.image_cache
+    }
+
+    /// Record every draw command into a [`DisplayList`] as it's drawn, until
+    /// this is called with `false`. Draw calls made while recording damage
+    /// aren't added since they aren't drawn.
+    pub fn record_display_list(&mut self, enabled: bool) {
+        match enabled {
+            true => {
+                self.display_list.get_or_insert_with(DisplayList::new);
+            }
+            false => self.display_list = None,
+        }
+    }
+
+    /// Get the draw commands recorded so far, `None` if the display list
+    /// isn't being recorded.
+    pub fn display_list(&self) -> Option<&DisplayList> {
+        self.display_list.as_ref()
+    }
+
+    /// Take the draw commands recorded so far and start a new display list,
+    /// `None` if the display list isn't being recorded.
+    pub fn take_display_list(&mut self) -> Option<DisplayList> {
+        self.display_list.as_mut().map(std::mem::take)
+    }
+
+    /// Add a command to the display list if it's being recorded.
+    fn record_command(&mut self, command: impl FnOnce() -> DrawCommand) {
+        if let Some(list) = &mut self.display_list {
+            list.push(command());
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use agape_core::Color;
+
+    #[test]
+    fn image_is_demultiplied() {
+        let mut renderer = Renderer::new();
+        renderer.resize(20, 10);
+        renderer.draw_rect(
+            Rect::new()
+                .size(10.0, 10.0)
+                .color(Color::rgba(255, 0, 0, 50)),
+        );
+
+        let image = renderer.to_image();
+        assert_eq!(image.dimensions(), (20, 10));
+        let [r, g, b, a] = image.get_pixel(5, 5).0;
+        assert_eq!((r, g, b), (255, 0, 0));
+        assert!((126..=128).contains(&a));
+        assert_eq!(image.get_pixel(15, 5).0, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn save_png() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("frame.png");
+        let mut renderer = Renderer::new();
+        renderer.resize(8, 8);
+        renderer.draw_rect(Rect::new().size(8.0, 8.0).color(Color::BLACK));
+        renderer.save_png(&path).unwrap();
+
+        let image = ::image::open(&path).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (8, 8));
+        assert_eq!(image.get_pixel(4, 4).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn clip_drawing() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.push_clip(Bounds::new(Position::unit(0.0), Size::unit(50.0)), 0.0);
+        renderer.draw_rect(Rect::new().size(100.0, 100.0).color(0));
+        renderer.pop_clip();
+
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(25, 25).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(75, 25).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(25, 75).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn nested_clips_intersect() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.push_clip(Bounds::new(Position::unit(0.0), Size::unit(60.0)), 0.0);
+        renderer.push_clip(Bounds::new(Position::unit(40.0), Size::unit(60.0)), 0.0);
+        assert_eq!(renderer.clip_depth(), 2);
+        renderer.draw_rect(Rect::new().size(100.0, 100.0).color(0));
+        renderer.pop_clip();
+        renderer.pop_clip();
+        assert_eq!(renderer.clip_depth(), 0);
+
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(50, 50).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(20, 20).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(80, 80).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn rounded_clip() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.push_clip(Bounds::new(Position::unit(0.0), Size::unit(100.0)), 30.0);
+        renderer.draw_rect(Rect::new().size(100.0, 100.0).color(0));
+
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(1, 1).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(50, 50).unwrap().alpha(), 255);
+    }
+
+    #[test]
+    fn layer_opacity() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.pixmap_mut().fill(tiny_skia::Color::WHITE);
+        renderer.push_layer(
+            Bounds::new(Position::default(), Size::unit(100.0)),
+            0.5,
+            BlendMode::SourceOver,
+        );
+        renderer.draw_rect(Rect::new().size(50.0, 50.0).color(0));
+        // Overlapping shapes in a layer don't get darker
+        renderer.draw_rect(Rect::new().size(50.0, 50.0).color(0));
+        assert_eq!(renderer.pixmap().pixel(25, 25).unwrap().red(), 255);
+        renderer.pop_layer();
+
+        let pixmap = renderer.pixmap();
+        let red = pixmap.pixel(25, 25).unwrap().red();
+        assert!((127..=128).contains(&red));
+        assert_eq!(pixmap.pixel(75, 75).unwrap().red(), 255);
+        assert_eq!(renderer.layer_depth(), 0);
+    }
+
+    #[test]
+    fn layer_blend_mode() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.draw_rect(Rect::new().size(100.0, 100.0).color((255, 0, 0)));
+        renderer.push_layer(
+            Bounds::new(Position::default(), Size::unit(100.0)),
+            1.0,
+            BlendMode::Multiply,
+        );
+        renderer.draw_rect(Rect::new().size(100.0, 100.0).color((0, 0, 255)));
+        renderer.pop_layer();
+
+        let pixel = renderer.pixmap().pixel(50, 50).unwrap();
+        assert_eq!((pixel.red(), pixel.green(), pixel.blue()), (0, 0, 0));
+    }
+
+    #[test]
+    fn nested_layers() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.pixmap_mut().fill(tiny_skia::Color::WHITE);
+        renderer.push_layer(
+            Bounds::new(Position::default(), Size::unit(100.0)),
+            0.5,
+            BlendMode::SourceOver,
+        );
+        renderer.push_layer(
+            Bounds::new(Position::default(), Size::unit(100.0)),
+            0.5,
+            BlendMode::SourceOver,
+        );
+        renderer.draw_rect(Rect::new().size(100.0, 100.0).color(0));
+        renderer.pop_layer();
+        renderer.pop_layer();
+
+        let red = renderer.pixmap().pixel(50, 50).unwrap().red();
+        assert!((190..=192).contains(&red));
+    }
+
+    #[test]
+    fn clipped_layer() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.push_clip(Bounds::new(Position::unit(0.0), Size::unit(50.0)), 0.0);
+        renderer.push_layer(
+            Bounds::new(Position::default(), Size::unit(100.0)),
+            1.0,
+            BlendMode::SourceOver,
+        );
+        renderer.draw_rect(Rect::new().size(100.0, 100.0).color(0));
+        renderer.pop_layer();
+        renderer.pop_clip();
+
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(25, 25).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(75, 75).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn layers_only_cover_their_bounds() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.push_layer(
+            Bounds::new(Position::unit(20.0), Size::unit(40.0)),
+            1.0,
+            BlendMode::SourceOver,
+        );
+        renderer.draw_rect(Rect::new().size(100.0, 100.0).color(0));
+        renderer.pop_layer();
+
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(30, 30).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(10, 10).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(70, 70).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn transformed_layer_keeps_the_clip() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.push_clip(Bounds::new(Position::default(), Size::unit(50.0)), 0.0);
+        renderer.push_transform(Affine::translate(25.0, 25.0));
+        renderer.push_layer(
+            Bounds::new(Position::default(), Size::unit(50.0)),
+            1.0,
+            BlendMode::SourceOver,
+        );
+        renderer.draw_rect(Rect::new().size(50.0, 50.0).color(0));
+        renderer.pop_layer();
+        renderer.pop_transform();
+        renderer.pop_clip();
+
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(30, 30).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(10, 10).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(60, 60).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn clips_inside_layers() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.push_layer(
+            Bounds::new(Position::unit(10.0), Size::unit(80.0)),
+            1.0,
+            BlendMode::SourceOver,
+        );
+        renderer.push_clip(Bounds::new(Position::unit(20.0), Size::unit(20.0)), 0.0);
+        renderer.push_layer(
+            Bounds::new(Position::default(), Size::unit(100.0)),
+            1.0,
+            BlendMode::SourceOver,
+        );
+        renderer.draw_rect(Rect::new().size(100.0, 100.0).color(0));
+        renderer.pop_layer();
+        renderer.pop_clip();
+        renderer.draw_rect(Rect::new().size(5.0, 5.0).position(80.0, 80.0).color(0));
+        renderer.pop_layer();
+
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(30, 30).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(15, 15).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(50, 50).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(82, 82).unwrap().alpha(), 255);
+    }
+
+    #[test]
+    fn empty_layers_draw_nothing() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.push_layer(
+            Bounds::new(Position::unit(200.0), Size::unit(10.0)),
+            1.0,
+            BlendMode::SourceOver,
+        );
+        renderer.draw_rect(Rect::new().size(100.0, 100.0).color(0));
+        renderer.pop_layer();
+        assert!(renderer.pixmap().pixels().iter().all(|p| p.alpha() == 0));
+        assert_eq!(renderer.layer_depth(), 0);
+    }
 
     #[test]
     fn text_size() {
@@ -123,4 +883,243 @@ mod test {
         assert!(size.height >= 16.0);
         assert!(size.width >= 16.0);
     }
+
+    #[test]
+    fn cached_text_is_drawn() {
+        let mut renderer = Renderer::new();
+        renderer.resize(200, 100);
+        let text = Text::new("Hello world").font_size(24.0);
+        renderer.draw_text(text.clone());
+        let first = renderer.pixmap().clone();
+        renderer.pixmap_mut().fill(tiny_skia::Color::TRANSPARENT);
+        renderer.draw_text(text.clone());
+        assert_eq!(renderer.pixmap(), &first);
+
+        renderer.text_size(text.clone());
+        renderer.shape_text(text);
+        let cache = renderer.text_cache();
+        assert_eq!(cache.rasterised_stats().hits, 1);
+        assert_eq!(cache.shaped_stats().hits, 1);
+        assert!(first.pixels().iter().any(|pixel| pixel.alpha() > 0));
+    }
+
+    #[test]
+    fn text_runs_are_not_shaped_again() {
+        let mut renderer = Renderer::new();
+        renderer.resize(200, 100);
+        renderer.record_display_list(true);
+        let text = Text::new("Hello world").font_size(24.0);
+        renderer.draw_text(text.clone());
+
+        assert_eq!(renderer.text_cache().shaped_stats(), CacheStats::default());
+        let list = renderer.take_display_list().unwrap();
+        let run = list.text_runs().next().unwrap();
+        assert_eq!(run.size, renderer.text_size(text));
+    }
+
+    #[test]
+    fn first_frame_is_damaged() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        let damage = renderer.record_damage(|renderer| {
+            renderer.draw_rect(Rect::new().size(10.0, 10.0));
+        });
+        assert_eq!(
+            damage.regions(),
+            &[Bounds::new(Position::default(), Size::unit(100.0))]
+        );
+    }
+
+    #[test]
+    fn recording_doesnt_draw() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.record_damage(|renderer| {
+            renderer.push_layer(
+                Bounds::new(Position::default(), Size::unit(100.0)),
+                0.5,
+                BlendMode::SourceOver,
+            );
+            renderer.draw_rect(Rect::new().size(100.0, 100.0).color(0));
+            renderer.draw_text(Text::new("Hello"));
+            renderer.pop_layer();
+        });
+        assert!(renderer.pixmap().pixels().iter().all(|p| p.alpha() == 0));
+        assert_eq!(renderer.layer_depth(), 0);
+    }
+
+    #[test]
+    fn only_changed_draw_calls_are_damaged() {
+        let mut renderer = Renderer::new();
+        renderer.resize(200, 100);
+        let frame = |color: u8| {
+            move |renderer: &mut Renderer| {
+                renderer.draw_rect(Rect::new().size(50.0, 50.0));
+                renderer.draw_rect(
+                    Rect::new()
+                        .size(50.0, 50.0)
+                        .position(100.0, 0.0)
+                        .color(color),
+                );
+            }
+        };
+        renderer.record_damage(frame(0));
+        assert!(renderer.record_damage(frame(0)).is_empty());
+
+        let damage = renderer.record_damage(frame(255));
+        let bounds = Bounds::new(Position::new(99.0, 0.0), Size::new(52.0, 51.0));
+        assert_eq!(damage.regions(), &[bounds]);
+
+        renderer.resize(200, 100);
+        assert_eq!(renderer.record_damage(frame(255)).regions().len(), 1);
+        assert_eq!(renderer.damage().bounds().unwrap().x, [0.0, 200.0]);
+    }
+
+    #[test]
+    fn images_are_damaged_by_content() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        // Every frame loads its own copy of the image
+        let frame = |red: u8| {
+            move |renderer: &mut Renderer| {
+                let buf = ::image::RgbaImage::from_pixel(4, 4, ::image::Rgba([red, 0, 0, 255]));
+                let data = std::sync::Arc::new(::image::DynamicImage::ImageRgba8(buf));
+                renderer.draw_image(Image::new(data));
+            }
+        };
+        renderer.record_damage(frame(0));
+        assert!(renderer.record_damage(frame(0)).is_empty());
+        assert!(!renderer.record_damage(frame(255)).is_empty());
+    }
+
+    #[test]
+    fn svgs_are_damaged_by_content() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        let frame = |color: &'static str| {
+            move |renderer: &mut Renderer| {
+                let data = format!(
+                    "<svg width='10' height='10' xmlns='http://www.w3.org/2000/svg'>\
+                    <rect width='10' height='10' fill='{color}'/></svg>"
+                );
+                let tree = usvg::Tree::from_str(&data, &usvg::Options::default()).unwrap();
+                renderer.draw_svg(Svg::new(std::sync::Arc::new(tree)));
+            }
+        };
+        renderer.record_damage(frame("red"));
+        assert!(renderer.record_damage(frame("red")).is_empty());
+        assert!(!renderer.record_damage(frame("blue")).is_empty());
+    }
+
+    #[test]
+    fn images_and_svgs_are_equal_by_content() {
+        let image = |red: u8| {
+            let buf = ::image::RgbaImage::from_pixel(4, 4, ::image::Rgba([red, 0, 0, 255]));
+            Image::new(std::sync::Arc::new(::image::DynamicImage::ImageRgba8(buf)))
+        };
+        let svg = |color: &str| {
+            let data = format!(
+                "<svg width='10' height='10' xmlns='http://www.w3.org/2000/svg'>\
+                <rect width='10' height='10' fill='{color}'/></svg>"
+            );
+            let tree = usvg::Tree::from_str(&data, &usvg::Options::default()).unwrap();
+            Svg::new(std::sync::Arc::new(tree))
+        };
+
+        assert_eq!(image(0), image(0));
+        assert_ne!(image(0), image(255));
+        assert_ne!(image(0), image(0).fit(ObjectFit::Cover));
+        assert_eq!(svg("red"), svg("red"));
+        assert_ne!(svg("red"), svg("blue"));
+    }
+
+    #[test]
+    fn damage_clip() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.record_damage(|renderer| renderer.draw_rect(Rect::new().size(10.0, 10.0)));
+        renderer.record_damage(|renderer| {
+            renderer.draw_rect(Rect::new().size(10.0, 10.0).position(50.0, 50.0))
+        });
+
+        renderer.push_damage_clip();
+        renderer.draw_rect(Rect::new().size(100.0, 100.0).color(0));
+        renderer.pop_clip();
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(5, 5).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(55, 55).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(30, 30).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(80, 80).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn transform_drawing() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.push_transform(Affine::translate(50.0, 0.0));
+        renderer.push_transform(Affine::scale(2.0, 2.0));
+        renderer.draw_rect(Rect::new().size(10.0, 10.0).color(0));
+        renderer.pop_transform();
+        renderer.pop_transform();
+        assert_eq!(renderer.transform(), Affine::identity());
+
+        // Scaled first, then moved
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(55, 15).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(5, 5).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(71, 5).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn rotated_rect() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        let center = Position::unit(50.0);
+        renderer.push_transform(Affine::rotate(45.0).around(center));
+        renderer.draw_rect(Rect::new().size(40.0, 40.0).position(30.0, 30.0).color(0));
+        renderer.pop_transform();
+
+        // The corners of the square are at the middle of the edges of the diamond
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(50, 25).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(33, 33).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(50, 50).unwrap().alpha(), 255);
+    }
+
+    #[test]
+    fn clips_are_transformed() {
+        let mut renderer = Renderer::new();
+        renderer.resize(100, 100);
+        renderer.push_transform(Affine::translate(50.0, 50.0));
+        renderer.push_clip(Bounds::new(Position::default(), Size::unit(20.0)), 0.0);
+        renderer.pop_transform();
+        renderer.draw_rect(Rect::new().size(100.0, 100.0).color(0));
+        renderer.pop_clip();
+
+        let pixmap = renderer.pixmap();
+        assert_eq!(pixmap.pixel(60, 60).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(10, 10).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn transformed_draw_calls_are_damaged() {
+        let mut renderer = Renderer::new();
+        renderer.resize(200, 200);
+        let frame = |angle: f32| {
+            move |renderer: &mut Renderer| {
+                let center = Position::unit(100.0);
+                renderer.push_transform(Affine::rotate(angle).around(center));
+                renderer.draw_rect(Rect::new().size(20.0, 20.0).position(90.0, 90.0));
+                renderer.pop_transform();
+            }
+        };
+        renderer.record_damage(frame(0.0));
+        assert!(renderer.record_damage(frame(0.0)).is_empty());
+        // The bounds are the same but the rect is drawn differently
+        assert!(!renderer.record_damage(frame(90.0)).is_empty());
+
+        let damage = renderer.record_damage(frame(45.0)).bounds().unwrap();
+        // The rotated square is wider than the square
+        assert!(damage.x[0] <= 86.0 && damage.x[1] >= 114.0);
+    }
 }
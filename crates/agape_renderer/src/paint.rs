@@ -0,0 +1,122 @@
+use agape_core::{Color, Fill, Gradient, GradientKind, Position, Rgba, Size, SpreadMode, map};
+use std::hash::{Hash, Hasher};
+use tiny_skia::{
+    FilterQuality, GradientStop, LinearGradient, Paint, PixmapPaint, Point, RadialGradient, Shader,
+    Transform,
+};
+
+/// Convert a [`Color`] to a `tiny_skia` color, the alpha goes from 0 to 100.
+pub(crate) fn skia_color(color: &Color<Rgba>) -> tiny_skia::Color {
+    let (r, g, b, a) = color.inner();
+    // TODO: change alpha to f32
+    let a = map(a as f32, [0.0, 100.0], [0.0, 255.0]) as u8;
+    tiny_skia::Color::from_rgba8(r, g, b, a)
+}
+
+/// Get the paint used to draw a pixmap with a `transform`, pixmaps that are
+/// only moved are drawn as they are and anything else is filtered.
+pub(crate) fn pixmap_paint(transform: Transform) -> PixmapPaint {
+    let quality = match transform.is_identity() || transform.is_translate() {
+        true => FilterQuality::Nearest,
+        false => FilterQuality::Bilinear,
+    };
+    PixmapPaint {
+        quality,
+        ..Default::default()
+    }
+}
+
+/// Get the [`Paint`] that fills a shape at `position` with a `size`. Returns
+/// `None` if nothing would be drawn, such as a gradient with no stops.
+pub(crate) fn fill_paint(fill: &Fill, position: Position, size: Size) -> Option<Paint<'static>> {
+    let shader = match fill {
+        Fill::Color(color) => Shader::SolidColor(skia_color(color)),
+        Fill::Gradient(gradient) => gradient_shader(gradient, position, size)?,
+    };
+
+    Some(Paint {
+        shader,
+        anti_alias: true,
+        ..Default::default()
+    })
+}
+
+fn gradient_shader(gradient: &Gradient, position: Position, size: Size) -> Option<Shader<'static>> {
+    let stops: Vec<GradientStop> = gradient
+        .stops
+        .iter()
+        .map(|stop| GradientStop::new(stop.offset, skia_color(&stop.color)))
+        .collect();
+    let mode = match gradient.spread {
+        SpreadMode::Pad => tiny_skia::SpreadMode::Pad,
+        SpreadMode::Repeat => tiny_skia::SpreadMode::Repeat,
+        SpreadMode::Reflect => tiny_skia::SpreadMode::Reflect,
+    };
+    let Size { width, height } = size;
+
+    match gradient.kind {
+        GradientKind::Linear { angle } => {
+            // The gradient line goes through the center and is long enough
+            // that the corners get the first and last colors, like css.
+            let (sin, cos) = angle.to_radians().sin_cos();
+            let length = (width * sin).abs() + (height * cos).abs();
+            let center = Point::from_xy(
+                width.mul_add(0.5, position.x),
+                height.mul_add(0.5, position.y),
+            );
+            let (dx, dy) = (sin * length / 2.0, -cos * length / 2.0);
+            let start = Point::from_xy(center.x - dx, center.y - dy);
+            let end = Point::from_xy(center.x + dx, center.y + dy);
+            LinearGradient::new(start, end, stops, mode, Transform::identity())
+        }
+        GradientKind::Radial { center, radius } => {
+            let cx = center.x * width;
+            let cy = center.y * height;
+            // The distance to the farthest corner
+            let dx = cx.max(width - cx);
+            let dy = cy.max(height - cy);
+            let radius = radius * dx.hypot(dy);
+            let center = Point::from_xy(position.x + cx, position.y + cy);
+            RadialGradient::new(center, center, radius, stops, mode, Transform::identity())
+        }
+    }
+}
+
+/// Hash everything that changes how the [`Fill`] is drawn.
+pub(crate) fn hash_fill(fill: &Fill, hasher: &mut impl Hasher) {
+    match fill {
+        Fill::Color(color) => color.hash(hasher),
+        Fill::Gradient(gradient) => {
+            match gradient.kind {
+                GradientKind::Linear { angle } => angle.to_bits().hash(hasher),
+                GradientKind::Radial { center, radius } => {
+                    for value in [center.x, center.y, radius] {
+                        value.to_bits().hash(hasher);
+                    }
+                }
+            }
+            for stop in &gradient.stops {
+                stop.offset.to_bits().hash(hasher);
+                stop.color.hash(hasher);
+            }
+            (gradient.spread as u8).hash(hasher);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn empty_gradient_has_no_paint() {
+        let fill = Fill::Gradient(Gradient::linear(0.0));
+        assert!(fill_paint(&fill, Position::default(), Size::unit(10.0)).is_none());
+    }
+
+    #[test]
+    fn alpha_is_mapped() {
+        let color = skia_color(&Color::rgba(0, 0, 0, 50));
+        assert!((color.alpha() - 0.5).abs() < 0.01);
+    }
+}
@@ -1,14 +1,25 @@
-use agape_core::{Border, Color, IntoColor, Position, Rgba, Size, map};
-use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Stroke, Transform};
+use crate::paint::{fill_paint, hash_fill, pixmap_paint, skia_color};
+use crate::shadow::{ShadowCache, shadow_extent};
+use agape_core::{
+    Border, BorderSide, BorderStyle, Bounds, BoxShadow, CornerRadius, Fill, IntoColor, Position,
+    Rgba, Size,
+};
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+use std::hash::{Hash, Hasher};
+use tiny_skia::{
+    FillRule, LineCap, Mask, Paint, Path, PathBuilder, Pixmap, Stroke, StrokeDash, Transform,
+};
 
 // TODO: add builder
 #[derive(Debug, Clone, PartialOrd, PartialEq, Default)]
 pub struct Rect {
     pub size: Size,
     pub position: Position,
-    pub color: Color<Rgba>,
-    pub corner_radius: u32,
+    pub fill: Fill,
+    pub corner_radius: CornerRadius,
     pub border: Option<Border>,
+    /// The shadows, the first shadow is drawn on top.
+    pub shadows: Vec<BoxShadow>,
 }
 
 impl Rect {
@@ -28,8 +39,15 @@ impl Rect {
         self
     }
 
+    /// Fill the rect with a solid color.
     pub fn color(mut self, color: impl IntoColor<Rgba>) -> Self {
-        self.color = color.into_color();
+        self.fill = Fill::Color(color.into_color());
+        self
+    }
+
+    /// Fill the rect with a color or a [`Gradient`](agape_core::Gradient).
+    pub fn fill(mut self, fill: impl Into<Fill>) -> Self {
+        self.fill = fill.into();
         self
     }
 
@@ -38,77 +56,314 @@ impl Rect {
         self
     }
 
-    pub fn corner_radius(mut self, radius: u32) -> Self {
-        self.corner_radius = radius;
+    /// Set the radius of the corners, use a [`CornerRadius`] to round each
+    /// corner differently.
+    pub fn corner_radius(mut self, radius: impl Into<CornerRadius>) -> Self {
+        self.corner_radius = radius.into();
         self
     }
 
-    pub(crate) fn draw(&self, pixmap: &mut Pixmap) {
+    /// Add a [`BoxShadow`].
+    pub fn shadow(mut self, shadow: BoxShadow) -> Self {
+        self.shadows.push(shadow);
+        self
+    }
+
+    /// Get the bounds that the rect draws to, including the border and shadows.
+    pub fn bounds(&self) -> Bounds {
+        let inset = self
+            .border
+            .as_ref()
+            .map_or(0.0, |border| border.max_width() / 2.0);
+        let mut bounds = Bounds::new(self.position, self.size);
+        bounds.x = [bounds.x[0] - inset, bounds.x[1] + inset];
+        bounds.y = [bounds.y[0] - inset, bounds.y[1] + inset];
+
+        for shadow in &self.shadows {
+            if let Some((offset, size)) = shadow_extent(self.size, shadow) {
+                let position =
+                    Position::new(self.position.x + offset.x, self.position.y + offset.y);
+                bounds = bounds.union(&Bounds::new(position, size));
+            }
+        }
+        bounds
+    }
+
+    /// Hash everything that changes how the rect is drawn.
+    pub(crate) fn hash_into(&self, hasher: &mut impl Hasher) {
+        hash_fill(&self.fill, hasher);
+        for radius in self.corner_radius.corners() {
+            radius.to_bits().hash(hasher);
+        }
+        if let Some(border) = &self.border {
+            for side in border.sides() {
+                side.width.to_bits().hash(hasher);
+                side.color.hash(hasher);
+            }
+            border.style.hash(hasher);
+        }
+        for shadow in &self.shadows {
+            for value in [shadow.offset.x, shadow.offset.y, shadow.blur, shadow.spread] {
+                value.to_bits().hash(hasher);
+            }
+            shadow.color.hash(hasher);
+            shadow.inset.hash(hasher);
+        }
+    }
+
+    /// Draw the rect with a `transform`, using the blurred shadows in the
+    /// `shadows` cache.
+    pub(crate) fn draw(
+        &self,
+        pixmap: &mut Pixmap,
+        mask: Option<&Mask>,
+        transform: Transform,
+        shadows: &mut ShadowCache,
+    ) {
         if self.size.width == 0.0 || self.size.height == 0.0 {
             return;
         }
-        let (r, g, b, a) = self.color.inner();
-
-        // Map the alpha since it's clipped to 100
-        // TODO: change alpha to f32
-        let a = map(a as f32, [0.0, 100.0], [0.0, 255.0]) as u8;
-        let mut paint = Paint::default();
-        paint.set_color_rgba8(r, g, b, a);
-
-        let Position { x, y } = self.position;
-        let Size { width, height } = self.size;
-        // TODO: get max radius
-        let radius = self.corner_radius as f32;
-
-        // Construct a rounded rect going clockwise
-        let mut pb = PathBuilder::new();
-        pb.move_to(x + radius, y);
-        // Top edge
-        pb.line_to(x + width - radius, y);
-        // Top right corner
-        pb.quad_to(x + width, y, x + width, y + radius);
-        // Right edge
-        pb.line_to(x + width, y + height - radius);
-        // Bottom right corner
-        pb.quad_to(x + width, y + height, x + width - radius, y + height);
-        // Bottom edge
-        pb.line_to(x + radius, y + height);
-        // Bottom left corner
-        pb.quad_to(x, y + height, x, y + height - radius);
-        // Left edge
-        pb.line_to(x, y + radius);
-        // Top left corner
-        pb.quad_to(x, y, x + radius, y);
-
-        let path = pb.finish().unwrap();
-        let rect = tiny_skia::Rect::from_xywh(x, y, width, height).unwrap();
-        pixmap.fill_path(
-            &path,
-            &paint,
-            FillRule::Winding,
-            Transform::identity(),
-            None,
-        );
+
+        let outer = self.shadows.iter().rev().filter(|shadow| !shadow.inset);
+        for shadow in outer {
+            self.draw_shadow(pixmap, mask, transform, shadows, shadow);
+        }
+        let Some(path) = rounded_rect_path(self.position, self.size, self.corner_radius) else {
+            return;
+        };
+        if let Some(paint) = fill_paint(&self.fill, self.position, self.size) {
+            pixmap.fill_path(&path, &paint, FillRule::Winding, transform, mask);
+        }
+
+        let inset = self.shadows.iter().rev().filter(|shadow| shadow.inset);
+        for shadow in inset {
+            self.draw_shadow(pixmap, mask, transform, shadows, shadow);
+        }
 
         if let Some(border) = &self.border {
-            // TODO turn this into a function
-            let (r, g, b, a) = border.color.inner();
-            let a = map(a as f32, [0.0, 100.0], [0.0, 255.0]) as u8;
+            self.draw_border(pixmap, mask, transform, border, &path);
+        }
+    }
 
-            let mut border_paint = Paint::default();
-            border_paint.set_color_rgba8(r, g, b, a);
-            let mut path_builder = PathBuilder::new();
-            path_builder.push_rect(rect);
-            let path = path_builder.finish().unwrap();
+    /// Draw the border along the outline, each side is drawn separately if
+    /// they aren't all the same.
+    fn draw_border(
+        &self,
+        pixmap: &mut Pixmap,
+        mask: Option<&Mask>,
+        transform: Transform,
+        border: &Border,
+        outline: &Path,
+    ) {
+        if border.is_uniform() {
+            stroke_side(pixmap, mask, transform, outline, &border.top, border.style);
+            return;
+        }
 
-            let stroke = Stroke {
-                width: border.width,
-                ..Default::default()
-            };
+        let radius = self.corner_radius.clamp(self.size);
+        let widths = border.sides().map(|side| side.width);
+        for (index, side) in border.sides().into_iter().enumerate() {
+            if let Some(path) = side_path(self.position, self.size, radius, widths, index) {
+                stroke_side(pixmap, mask, transform, &path, side, border.style);
+            }
+        }
+    }
 
-            pixmap.stroke_path(&path, &border_paint, &stroke, Transform::identity(), None);
+    fn draw_shadow(
+        &self,
+        pixmap: &mut Pixmap,
+        mask: Option<&Mask>,
+        transform: Transform,
+        shadows: &mut ShadowCache,
+        shadow: &BoxShadow,
+    ) {
+        let Some(rendered) = shadows.get(self.size, self.corner_radius, shadow) else {
+            return;
+        };
+        let x = self.position.x + rendered.origin.x;
+        let y = self.position.y + rendered.origin.y;
+        pixmap.draw_pixmap(
+            x.round() as i32,
+            y.round() as i32,
+            rendered.pixmap.as_ref(),
+            &pixmap_paint(transform),
+            transform,
+            mask,
+        );
+    }
+}
+
+/// Stroke a side of a border, or the whole border if all the sides are the same.
+fn stroke_side(
+    pixmap: &mut Pixmap,
+    mask: Option<&Mask>,
+    transform: Transform,
+    path: &Path,
+    side: &BorderSide,
+    style: BorderStyle,
+) {
+    if side.width <= 0.0 {
+        return;
+    }
+    let mut paint = Paint::default();
+    paint.set_color(skia_color(&side.color));
+    paint.anti_alias = true;
+
+    let width = side.width;
+    let (dash, line_cap) = match style {
+        BorderStyle::Solid => (None, LineCap::Butt),
+        BorderStyle::Dashed => (
+            StrokeDash::new(vec![width * 3.0, width * 2.0], 0.0),
+            LineCap::Butt,
+        ),
+        // Zero length dashes with round caps are drawn as dots
+        BorderStyle::Dotted => (StrokeDash::new(vec![0.0, width * 2.0], 0.0), LineCap::Round),
+    };
+    let stroke = Stroke {
+        width,
+        line_cap,
+        dash,
+        ..Default::default()
+    };
+    pixmap.stroke_path(path, &paint, &stroke, transform, mask);
+}
+
+/// The center of each corner's arc, going clockwise from the top left, with the
+/// angle that the arc starts at.
+fn corner_arcs(position: Position, size: Size, radius: CornerRadius) -> [(f32, f32, f32, f32); 4] {
+    let Position { x, y } = position;
+    let Size { width, height } = size;
+    let [top_left, top_right, bottom_right, bottom_left] = radius.corners();
+    [
+        (x + top_left, y + top_left, top_left, PI),
+        (
+            x + width - top_right,
+            y + top_right,
+            top_right,
+            PI + FRAC_PI_2,
+        ),
+        (
+            x + width - bottom_right,
+            y + height - bottom_right,
+            bottom_right,
+            0.0,
+        ),
+        (
+            x + bottom_left,
+            y + height - bottom_left,
+            bottom_left,
+            FRAC_PI_2,
+        ),
+    ]
+}
+
+/// Get the point at an `angle` on a circle, angles go clockwise since y points down.
+fn arc_point(cx: f32, cy: f32, radius: f32, angle: f32) -> (f32, f32) {
+    let (sin, cos) = angle.sin_cos();
+    (radius.mul_add(cos, cx), radius.mul_add(sin, cy))
+}
+
+/// Add a circular arc from the current point, which should be at the `start`
+/// angle, to the `end` angle. The arc is approximated with a cubic, which is
+/// accurate enough for arcs up to a quarter circle.
+fn arc_to(pb: &mut PathBuilder, (cx, cy, radius): (f32, f32, f32), start: f32, end: f32) {
+    if radius <= 0.0 {
+        return;
+    }
+    let k = 4.0 / 3.0 * ((end - start) / 4.0).tan() * radius;
+    let (sin0, cos0) = start.sin_cos();
+    let (sin1, cos1) = end.sin_cos();
+    let (x0, y0) = arc_point(cx, cy, radius, start);
+    let (x1, y1) = arc_point(cx, cy, radius, end);
+    pb.cubic_to(
+        k.mul_add(-sin0, x0),
+        k.mul_add(cos0, y0),
+        k.mul_add(sin1, x1),
+        k.mul_add(-cos1, y1),
+        x1,
+        y1,
+    );
+}
+
+/// Build the path of a rectangle with rounded corners, returns `None` if the
+/// rectangle is empty. The radii are limited to half the size.
+pub(crate) fn rounded_rect_path(
+    position: Position,
+    size: Size,
+    radius: impl Into<CornerRadius>,
+) -> Option<Path> {
+    if size.width <= 0.0 || size.height <= 0.0 {
+        return None;
+    }
+    let radius = radius.into().clamp(size);
+
+    // Construct a rounded rect going clockwise
+    let mut pb = PathBuilder::new();
+    for (index, (cx, cy, radius, start)) in
+        corner_arcs(position, size, radius).into_iter().enumerate()
+    {
+        let (x, y) = arc_point(cx, cy, radius, start);
+        match index {
+            0 => pb.move_to(x, y),
+            _ => pb.line_to(x, y),
         }
+        arc_to(&mut pb, (cx, cy, radius), start, start + FRAC_PI_2);
+    }
+    pb.close();
+    pb.finish()
+}
+
+/// Build the path of one side of a border, going clockwise from the top. Rounded
+/// corners are split in half between the sides. Square corners are drawn by the
+/// top and bottom sides, so the sides don't overlap.
+fn side_path(
+    position: Position,
+    size: Size,
+    radius: CornerRadius,
+    widths: [f32; 4],
+    side: usize,
+) -> Option<Path> {
+    if size.width <= 0.0 || size.height <= 0.0 {
+        return None;
+    }
+    let arcs = corner_arcs(position, size, radius);
+    let (first, second) = (arcs[side], arcs[(side + 1) % 4]);
+    let horizontal = side.is_multiple_of(2);
+    // How far the side is moved past a square corner, outwards for the top and
+    // bottom and inwards for the left and right.
+    let extend = |corner_side: usize| match horizontal {
+        true => widths[corner_side] / 2.0,
+        false => -widths[corner_side] / 2.0,
+    };
+    // The direction that the side goes in
+    let (dx, dy): (f32, f32) = [(1.0, 0.0), (0.0, 1.0), (-1.0, 0.0), (0.0, -1.0)][side];
+
+    let mut pb = PathBuilder::new();
+    let (cx, cy, radius, start) = first;
+    if radius > 0.0 {
+        let (x, y) = arc_point(cx, cy, radius, start + FRAC_PI_4);
+        pb.move_to(x, y);
+        arc_to(
+            &mut pb,
+            (cx, cy, radius),
+            start + FRAC_PI_4,
+            start + FRAC_PI_2,
+        );
+    } else {
+        let amount = extend((side + 3) % 4);
+        pb.move_to(dx.mul_add(-amount, cx), dy.mul_add(-amount, cy));
+    }
+
+    let (cx, cy, radius, start) = second;
+    if radius > 0.0 {
+        let (x, y) = arc_point(cx, cy, radius, start);
+        pb.line_to(x, y);
+        arc_to(&mut pb, (cx, cy, radius), start, start + FRAC_PI_4);
+    } else {
+        let amount = extend((side + 1) % 4);
+        pb.line_to(dx.mul_add(amount, cx), dy.mul_add(amount, cy));
     }
+    pb.finish()
 }
 
 #[cfg(test)]
@@ -120,7 +375,12 @@ mod test {
         let mut pixmap = Pixmap::new(100, 100).unwrap();
         pixmap.fill(tiny_skia::Color::WHITE);
         let rect = Rect::new().size(50.0, 20.0).color((12, 144, 240));
-        rect.draw(&mut pixmap);
+        rect.draw(
+            &mut pixmap,
+            None,
+            Transform::identity(),
+            &mut ShadowCache::default(),
+        );
         for x in 0..100 {
             for y in 0..100 {
                 let pixel = pixmap.pixel(x, y).unwrap();
@@ -142,11 +402,223 @@ mod test {
         let mut pixmap = Pixmap::new(100, 100).unwrap();
         pixmap.fill(tiny_skia::Color::WHITE);
         let rect = Rect::new().size(100.0, 100.0).color(150);
-        rect.draw(&mut pixmap);
+        rect.draw(
+            &mut pixmap,
+            None,
+            Transform::identity(),
+            &mut ShadowCache::default(),
+        );
         for pixel in pixmap.pixels() {
             assert_eq!(pixel.red(), 150);
             assert_eq!(pixel.green(), 150);
             assert_eq!(pixel.blue(), 150);
         }
     }
+
+    #[test]
+    fn linear_gradient() {
+        let mut pixmap = Pixmap::new(100, 100).unwrap();
+        let gradient = agape_core::Gradient::linear(90.0)
+            .stop(0.0, (255, 0, 0))
+            .stop(1.0, (0, 0, 255));
+        let rect = Rect::new().size(100.0, 100.0).fill(gradient);
+        rect.draw(
+            &mut pixmap,
+            None,
+            Transform::identity(),
+            &mut ShadowCache::default(),
+        );
+
+        let left = pixmap.pixel(0, 50).unwrap();
+        let right = pixmap.pixel(99, 50).unwrap();
+        assert!(left.red() > 250 && left.blue() < 5);
+        assert!(right.blue() > 250 && right.red() < 5);
+        // The gradient goes from left to right, so every row is the same
+        assert_eq!(pixmap.pixel(30, 0), pixmap.pixel(30, 99));
+    }
+
+    #[test]
+    fn radial_gradient() {
+        let mut pixmap = Pixmap::new(100, 100).unwrap();
+        let gradient = agape_core::Gradient::radial()
+            .stop(0.0, (255, 255, 255))
+            .stop(1.0, (0, 0, 0));
+        let rect = Rect::new().size(100.0, 100.0).fill(gradient);
+        rect.draw(
+            &mut pixmap,
+            None,
+            Transform::identity(),
+            &mut ShadowCache::default(),
+        );
+
+        assert!(pixmap.pixel(50, 50).unwrap().red() > 250);
+        assert!(pixmap.pixel(0, 0).unwrap().red() < 5);
+        assert_eq!(pixmap.pixel(20, 50), pixmap.pixel(50, 20));
+    }
+
+    #[test]
+    fn repeated_gradient() {
+        let mut pixmap = Pixmap::new(100, 100).unwrap();
+        let gradient = agape_core::Gradient::radial()
+            .radius(0.5)
+            .stop(0.0, (0, 0, 0))
+            .stop(1.0, (255, 255, 255))
+            .spread(agape_core::SpreadMode::Repeat);
+        let rect = Rect::new().size(100.0, 100.0).fill(gradient);
+        rect.draw(
+            &mut pixmap,
+            None,
+            Transform::identity(),
+            &mut ShadowCache::default(),
+        );
+
+        // The radius is about 35, so the gradient starts again past it
+        assert!(pixmap.pixel(50, 50).unwrap().red() < 10);
+        assert!(pixmap.pixel(84, 50).unwrap().red() > 240);
+        assert!(pixmap.pixel(87, 50).unwrap().red() < 40);
+    }
+
+    #[test]
+    fn shadow_follows_corner_radius() {
+        let mut pixmap = Pixmap::new(100, 100).unwrap();
+        let shadow = BoxShadow::new().offset(10.0, 10.0).color((0, 0, 0));
+        let rect = Rect::new()
+            .size(50.0, 50.0)
+            .corner_radius(20)
+            .color((255, 255, 255))
+            .shadow(shadow);
+        rect.draw(
+            &mut pixmap,
+            None,
+            Transform::identity(),
+            &mut ShadowCache::default(),
+        );
+
+        // Drawn below and to the right of the box
+        assert_eq!(pixmap.pixel(55, 30).unwrap().red(), 0);
+        assert_eq!(pixmap.pixel(55, 30).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(30, 30).unwrap().red(), 255);
+        // The corner of the shadow is rounded
+        assert_eq!(pixmap.pixel(58, 58).unwrap().alpha(), 0);
+        assert!(rect.bounds().x[1] >= 60.0);
+    }
+
+    #[test]
+    fn corners_are_circular() {
+        let mut pixmap = Pixmap::new(100, 100).unwrap();
+        let rect = Rect::new()
+            .position(10.0, 10.0)
+            .size(80.0, 80.0)
+            .corner_radius(40)
+            .color((0, 0, 0));
+        rect.draw(
+            &mut pixmap,
+            None,
+            Transform::identity(),
+            &mut ShadowCache::default(),
+        );
+
+        // Just outside and inside a circle with a radius of 40
+        assert_eq!(pixmap.pixel(20, 20).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(23, 23).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(11, 50).unwrap().alpha(), 255);
+    }
+
+    #[test]
+    fn each_corner_has_a_radius() {
+        let mut pixmap = Pixmap::new(100, 100).unwrap();
+        let rect = Rect::new()
+            .size(100.0, 100.0)
+            .corner_radius(CornerRadius::new(40.0, 0.0, 20.0, 0.0))
+            .color((0, 0, 0));
+        rect.draw(
+            &mut pixmap,
+            None,
+            Transform::identity(),
+            &mut ShadowCache::default(),
+        );
+
+        assert_eq!(pixmap.pixel(1, 1).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(99, 0).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(98, 98).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(0, 99).unwrap().alpha(), 255);
+    }
+
+    #[test]
+    fn border_follows_rounded_corners() {
+        let mut pixmap = Pixmap::new(100, 100).unwrap();
+        let rect = Rect::new()
+            .position(10.0, 10.0)
+            .size(80.0, 80.0)
+            .corner_radius(20)
+            .color((255, 255, 255))
+            .border(Border::all(2.0, (0, 0, 0)));
+        rect.draw(
+            &mut pixmap,
+            None,
+            Transform::identity(),
+            &mut ShadowCache::default(),
+        );
+
+        // The border doesn't stick out of the corners
+        for (x, y) in [(10, 10), (89, 10), (89, 89), (10, 89)] {
+            assert_eq!(pixmap.pixel(x, y).unwrap().alpha(), 0);
+        }
+        let edge = pixmap.pixel(50, 10).unwrap();
+        assert_eq!((edge.red(), edge.alpha()), (0, 255));
+        let middle = pixmap.pixel(50, 50).unwrap();
+        assert_eq!(middle.red(), 255);
+    }
+
+    #[test]
+    fn border_sides() {
+        let mut pixmap = Pixmap::new(100, 100).unwrap();
+        let border = Border::new()
+            .top(BorderSide::new(4.0, (255, 0, 0)))
+            .left(BorderSide::new(2.0, (0, 0, 255)));
+        let rect = Rect::new()
+            .position(10.0, 10.0)
+            .size(80.0, 80.0)
+            .border(border);
+        rect.draw(
+            &mut pixmap,
+            None,
+            Transform::identity(),
+            &mut ShadowCache::default(),
+        );
+
+        let top = pixmap.pixel(50, 10).unwrap();
+        assert_eq!((top.red(), top.blue(), top.alpha()), (255, 0, 255));
+        let left = pixmap.pixel(10, 50).unwrap();
+        assert_eq!((left.red(), left.blue(), left.alpha()), (0, 255, 255));
+        // The top side covers the square corner
+        let corner = pixmap.pixel(9, 9).unwrap();
+        assert_eq!((corner.red(), corner.alpha()), (255, 255));
+        // The right and bottom sides have no width
+        assert_eq!(pixmap.pixel(89, 50).unwrap().alpha(), 0);
+        assert_eq!(pixmap.pixel(50, 89).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn dashed_and_dotted_borders() {
+        for style in [BorderStyle::Dashed, BorderStyle::Dotted] {
+            let mut pixmap = Pixmap::new(100, 100).unwrap();
+            let rect = Rect::new()
+                .position(10.0, 10.0)
+                .size(80.0, 80.0)
+                .border(Border::all(4.0, (0, 0, 0)).style(style));
+            rect.draw(
+                &mut pixmap,
+                None,
+                Transform::identity(),
+                &mut ShadowCache::default(),
+            );
+
+            let alpha: Vec<u8> = (10..90)
+                .map(|x| pixmap.pixel(x, 10).unwrap().alpha())
+                .collect();
+            assert!(alpha.contains(&255), "{style:?}");
+            assert!(alpha.contains(&0), "{style:?}");
+        }
+    }
 }
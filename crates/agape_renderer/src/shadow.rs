@@ -0,0 +1,315 @@
+use crate::cache::LruCache;
+use crate::paint::skia_color;
+use crate::rect::rounded_rect_path;
+use agape_core::{BoxShadow, Color, CornerRadius, Position, Rgba, Size};
+use tiny_skia::{BlendMode, FillRule, Mask, Paint, Pixmap, Transform};
+
+/// The number of blurred shadows that are kept.
+const SHADOW_CACHE_CAPACITY: usize = 256;
+
+/// A blurred shadow, the `origin` is where it's drawn relative to the box.
+#[derive(Debug)]
+pub(crate) struct RenderedShadow {
+    pub pixmap: Pixmap,
+    pub origin: Position,
+}
+
+/// Everything that changes how a shadow looks, apart from where it's drawn.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ShadowKey {
+    size: [u32; 2],
+    radius: [u32; 4],
+    offset: [u32; 2],
+    blur: u32,
+    spread: u32,
+    color: Color<Rgba>,
+    inset: bool,
+}
+
+impl ShadowKey {
+    fn new(size: Size, radius: CornerRadius, shadow: &BoxShadow) -> Self {
+        Self {
+            size: [size.width.to_bits(), size.height.to_bits()],
+            radius: radius.corners().map(f32::to_bits),
+            offset: [shadow.offset.x.to_bits(), shadow.offset.y.to_bits()],
+            blur: shadow.blur.to_bits(),
+            spread: shadow.spread.to_bits(),
+            color: shadow.color.clone(),
+            inset: shadow.inset,
+        }
+    }
+}
+
+/// Keeps blurred shadows between frames, boxes with the same size and
+/// shadow share the same blurred pixmap.
+#[derive(Debug)]
+pub(crate) struct ShadowCache {
+    shadows: LruCache<ShadowKey, Option<RenderedShadow>>,
+}
+
+impl Default for ShadowCache {
+    fn default() -> Self {
+        Self {
+            shadows: LruCache::new(SHADOW_CACHE_CAPACITY),
+        }
+    }
+}
+
+impl ShadowCache {
+    /// Get the shadow of a box with a `size` and corner `radius`, blurring it
+    /// if it isn't in the cache. Returns `None` if the shadow is empty.
+    pub fn get(
+        &mut self,
+        size: Size,
+        radius: CornerRadius,
+        shadow: &BoxShadow,
+    ) -> Option<&RenderedShadow> {
+        let key = ShadowKey::new(size, radius, shadow);
+        self.shadows
+            .get_or_insert_with(key, || match shadow.inset {
+                true => render_inset(size, radius, shadow),
+                false => render_outer(size, radius, shadow),
+            })
+            .as_ref()
+    }
+
+    #[cfg(test)]
+    fn stats(&self) -> crate::cache::CacheStats {
+        self.shadows.stats()
+    }
+}
+
+/// Get the extra space around the shadow that the blur fades out in.
+fn blur_padding(blur: f32) -> f32 {
+    (blur.max(0.0) * 1.5).ceil()
+}
+
+/// Get the position and size that an outer shadow draws to, relative to the
+/// box. Inset shadows are drawn inside the box so they return `None`.
+pub(crate) fn shadow_extent(size: Size, shadow: &BoxShadow) -> Option<(Position, Size)> {
+    if shadow.inset {
+        return None;
+    }
+    let padding = blur_padding(shadow.blur) + shadow.spread;
+    let position = Position::new(shadow.offset.x - padding, shadow.offset.y - padding);
+    let size = Size::new(
+        padding.mul_add(2.0, size.width),
+        padding.mul_add(2.0, size.height),
+    );
+    Some((position, size))
+}
+
+/// Draw a shadow behind a box, the shadow isn't drawn under the box itself.
+fn render_outer(size: Size, radius: CornerRadius, shadow: &BoxShadow) -> Option<RenderedShadow> {
+    let (origin, pixmap_size) = shadow_extent(size, shadow)?;
+    let padding = blur_padding(shadow.blur);
+    let spread = shadow.spread;
+    let shape = Size::new(
+        spread.mul_add(2.0, size.width),
+        spread.mul_add(2.0, size.height),
+    );
+
+    let mut pixmap = Pixmap::new(
+        pixmap_size.width.ceil() as u32,
+        pixmap_size.height.ceil() as u32,
+    )?;
+    let path = rounded_rect_path(Position::unit(padding), shape, radius.expand(spread))?;
+    let mut paint = Paint::default();
+    paint.set_color(skia_color(&shadow.color));
+    pixmap.fill_path(
+        &path,
+        &paint,
+        FillRule::Winding,
+        Transform::identity(),
+        None,
+    );
+    gaussian_blur(&mut pixmap, shadow.blur / 2.0);
+
+    // Cut out the box so that the shadow doesn't show through it
+    if let Some(path) = rounded_rect_path(Position::new(-origin.x, -origin.y), size, radius) {
+        paint.blend_mode = BlendMode::Clear;
+        pixmap.fill_path(
+            &path,
+            &paint,
+            FillRule::Winding,
+            Transform::identity(),
+            None,
+        );
+    }
+
+    Some(RenderedShadow { pixmap, origin })
+}
+
+/// Draw a shadow inside a box, around a hole that is moved by the offset
+/// and shrunk by the spread.
+fn render_inset(size: Size, radius: CornerRadius, shadow: &BoxShadow) -> Option<RenderedShadow> {
+    let padding = blur_padding(shadow.blur);
+    let mut pixmap = Pixmap::new(
+        padding.mul_add(2.0, size.width).ceil() as u32,
+        padding.mul_add(2.0, size.height).ceil() as u32,
+    )?;
+    pixmap.fill(skia_color(&shadow.color));
+
+    let spread = shadow.spread;
+    let hole = Size::new(
+        spread.mul_add(-2.0, size.width),
+        spread.mul_add(-2.0, size.height),
+    );
+    let position = Position::new(
+        padding + shadow.offset.x + spread,
+        padding + shadow.offset.y + spread,
+    );
+    if let Some(path) = rounded_rect_path(position, hole, radius.expand(-spread)) {
+        let paint = Paint {
+            blend_mode: BlendMode::Clear,
+            ..Default::default()
+        };
+        pixmap.fill_path(
+            &path,
+            &paint,
+            FillRule::Winding,
+            Transform::identity(),
+            None,
+        );
+    }
+    gaussian_blur(&mut pixmap, shadow.blur / 2.0);
+
+    // Only keep the parts inside the box
+    let mut mask = Mask::new(pixmap.width(), pixmap.height())?;
+    let path = rounded_rect_path(Position::unit(padding), size, radius)?;
+    mask.fill_path(&path, FillRule::Winding, true, Transform::identity());
+    pixmap.apply_mask(&mask);
+
+    Some(RenderedShadow {
+        pixmap,
+        origin: Position::unit(-padding),
+    })
+}
+
+/// Blur the pixmap with a gaussian blur, pixels outside the pixmap
+/// are transparent.
+fn gaussian_blur(pixmap: &mut Pixmap, sigma: f32) {
+    if sigma <= 0.0 {
+        return;
+    }
+
+    let radius = (sigma * 3.0).ceil() as isize;
+    let kernel: Vec<f32> = (-radius..=radius)
+        .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = kernel.iter().sum();
+    let kernel: Vec<f32> = kernel.iter().map(|weight| weight / total).collect();
+
+    let width = pixmap.width() as isize;
+    let height = pixmap.height() as isize;
+    let data = pixmap.data_mut();
+    let index = |x: isize, y: isize| (y * width + x) as usize * 4;
+
+    // Blur the rows then the columns, the channels are premultiplied so
+    // they can be blurred separately.
+    let mut rows = vec![0.0f32; data.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0.0f32; 4];
+            for (i, weight) in kernel.iter().enumerate() {
+                let sx = x + i as isize - radius;
+                if sx < 0 || sx >= width {
+                    continue;
+                }
+                let pixel = &data[index(sx, y)..index(sx, y) + 4];
+                for (sum, value) in sum.iter_mut().zip(pixel) {
+                    *sum = (*value as f32).mul_add(*weight, *sum);
+                }
+            }
+            rows[index(x, y)..index(x, y) + 4].copy_from_slice(&sum);
+        }
+    }
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0.0f32; 4];
+            for (i, weight) in kernel.iter().enumerate() {
+                let sy = y + i as isize - radius;
+                if sy < 0 || sy >= height {
+                    continue;
+                }
+                let pixel = &rows[index(x, sy)..index(x, sy) + 4];
+                for (sum, value) in sum.iter_mut().zip(pixel) {
+                    *sum = value.mul_add(*weight, *sum);
+                }
+            }
+            // Rounding can't make a channel bigger than the alpha
+            let alpha = sum[3].round().clamp(0.0, 255.0) as u8;
+            let pixel = &mut data[index(x, y)..index(x, y) + 4];
+            for (value, sum) in pixel.iter_mut().zip(&sum[..3]) {
+                *value = (sum.round().clamp(0.0, 255.0) as u8).min(alpha);
+            }
+            pixel[3] = alpha;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn blur_spreads_out() {
+        let mut pixmap = Pixmap::new(21, 1).unwrap();
+        pixmap.data_mut()[40..44].copy_from_slice(&[255, 255, 255, 255]);
+        gaussian_blur(&mut pixmap, 2.0);
+
+        let alpha: Vec<u8> = pixmap.pixels().iter().map(|p| p.alpha()).collect();
+        assert!(alpha[10] < 255 && alpha[10] > 0);
+        assert!(alpha[8] > 0);
+        assert_eq!(alpha[9], alpha[11]);
+        assert!(alpha[9] < alpha[10]);
+        assert_eq!(alpha[0], 0);
+    }
+
+    #[test]
+    fn outer_shadow_is_cut_out() {
+        let shadow = BoxShadow::new().blur(10.0).color((0, 0, 0));
+        let rendered = render_outer(Size::unit(50.0), CornerRadius::default(), &shadow).unwrap();
+        assert_eq!(rendered.origin, Position::unit(-15.0));
+        assert_eq!(rendered.pixmap.width(), 80);
+
+        let pixmap = &rendered.pixmap;
+        // Under the box
+        assert_eq!(pixmap.pixel(40, 40).unwrap().alpha(), 0);
+        // At the edge of the box the shadow is about half as dark
+        let edge = pixmap.pixel(14, 40).unwrap().alpha();
+        assert!((100..150).contains(&edge), "{edge}");
+        assert!(pixmap.pixel(2, 40).unwrap().alpha() < 10);
+    }
+
+    #[test]
+    fn inset_shadow_is_inside_the_box() {
+        let shadow = BoxShadow::new()
+            .blur(4.0)
+            .spread(5.0)
+            .color((0, 0, 0))
+            .inset();
+        let rendered = render_inset(Size::unit(50.0), CornerRadius::default(), &shadow).unwrap();
+        let pixmap = &rendered.pixmap;
+        let padding = -rendered.origin.x as u32;
+
+        // Outside the box
+        assert_eq!(pixmap.pixel(padding - 1, 30).unwrap().alpha(), 0);
+        // The edge of the box is dark and the middle is clear
+        assert!(pixmap.pixel(padding, 30).unwrap().alpha() > 200);
+        assert_eq!(pixmap.pixel(padding + 25, padding + 25).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn shadows_are_cached_by_size() {
+        let mut cache = ShadowCache::default();
+        let shadow = BoxShadow::new().blur(4.0);
+        cache.get(Size::unit(50.0), CornerRadius::default(), &shadow);
+        cache.get(Size::unit(50.0), CornerRadius::default(), &shadow);
+        cache.get(Size::unit(60.0), CornerRadius::default(), &shadow);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+    }
+}
@@ -1,31 +1,113 @@
-use agape_core::{Position, Size};
+use agape_core::{Bounds, Position, Size};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::sync::Arc;
-use tiny_skia::{Pixmap, Transform};
-use usvg::Tree;
+use tiny_skia::{Mask, Pixmap, PixmapPaint, Transform};
+use usvg::{Tree, WriteOptions};
 
+#[derive(Clone)]
 pub struct Svg {
     tree: Arc<Tree>,
+    /// The hash of the svg's content, see [`Svg::content_key`].
+    key: u64,
     pub size: Size,
     pub position: Position,
 }
 
+/// Svgs are compared by their content key, so svgs that are parsed from the
+/// same source are equal.
+impl PartialEq for Svg {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.size == other.size && self.position == other.position
+    }
+}
+
+impl std::fmt::Debug for Svg {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Svg")
+            .field("size", &self.size)
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
 impl Svg {
+    /// Create an [`Svg`], the tree is written out and hashed to find its key
+    /// so use [`Svg::with_key`] if the key is already known.
     pub fn new(tree: Arc<Tree>) -> Self {
+        let key = Self::content_key(&tree);
+        Self::with_key(tree, key)
+    }
+
+    /// Create an [`Svg`] with a `key` from [`Svg::content_key`] that was found
+    /// before, so that the tree isn't hashed every frame.
+    pub fn with_key(tree: Arc<Tree>, key: u64) -> Self {
         Self {
             size: Size::from(tree.size()),
             position: Position::default(),
             tree,
+            key,
         }
     }
 
+    /// Get the key of the svg `tree`, which is a hash of the svg it writes
+    /// out. Trees with the same content have the same key.
+    pub fn content_key(tree: &Tree) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        tree.to_string(&WriteOptions::default()).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub(crate) fn bounds(&self) -> Bounds {
+        Bounds::new(self.position, self.size)
+    }
+
+    /// Hash the svg tree by its content key.
+    pub(crate) fn hash_into(&self, hasher: &mut impl Hasher) {
+        self.key.hash(hasher);
+    }
+
     pub fn draw(&self, pixmap: &mut Pixmap) {
+        self.draw_clipped(pixmap, None);
+    }
+
+    /// Draw the svg onto the `pixmap`, only the parts inside the `mask` are drawn.
+    pub fn draw_clipped(&self, pixmap: &mut Pixmap, mask: Option<&Mask>) {
+        self.draw_transformed(pixmap, mask, Transform::identity());
+    }
+
+    /// Draw the svg with a `transform` that is applied after it's placed
+    /// at its position.
+    pub(crate) fn draw_transformed(
+        &self,
+        pixmap: &mut Pixmap,
+        mask: Option<&Mask>,
+        transform: Transform,
+    ) {
         let svg_width = self.tree.size().width();
         let svg_height = self.tree.size().height();
         let scale_x = self.size.width / svg_width;
         let scale_y = self.size.height / svg_height;
         let transform = Transform::from_scale(scale_x, scale_y)
-            .post_translate(self.position.x, self.position.y);
+            .post_translate(self.position.x, self.position.y)
+            .post_concat(transform);
+
+        let Some(mask) = mask else {
+            resvg::render(&self.tree, transform, &mut pixmap.as_mut());
+            return;
+        };
 
-        resvg::render(&self.tree, transform, &mut pixmap.as_mut());
+        // resvg can't draw with a mask, so draw onto a separate pixmap first
+        let Some(mut layer) = Pixmap::new(pixmap.width(), pixmap.height()) else {
+            return;
+        };
+        resvg::render(&self.tree, transform, &mut layer.as_mut());
+        pixmap.draw_pixmap(
+            0,
+            0,
+            layer.as_ref(),
+            &PixmapPaint::default(),
+            Transform::identity(),
+            Some(mask),
+        );
     }
 }
@@ -1,8 +1,13 @@
+use crate::paint::pixmap_paint;
 use agape_core::{Color, IntoColor, Position, Rgba, Size};
 use cosmic_text::fontdb::Query;
-use cosmic_text::{Attrs, Buffer, Family, FontSystem, Metrics, Shaping, Style, SwashCache, Weight};
+use cosmic_text::{
+    Attrs, Buffer, Family, FontSystem, Metrics, ShapeLine, Shaping, Style, SwashCache, Weight, Wrap,
+};
 use image::RgbaImage;
-use tiny_skia::{IntSize, Pixmap, PixmapPaint, Transform};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use tiny_skia::{IntSize, Mask, Pixmap, Transform};
 
 // TODO: add line height
 #[derive(Clone, PartialEq, Debug)]
@@ -11,6 +16,9 @@ pub struct Text<'a> {
     pub font_size: f32,
     /// The line height is a multiple of the font size.
     pub line_height: f32,
+    /// The width the text wraps at, the text is on one line per paragraph if
+    /// there's no max width.
+    pub max_width: Option<f32>,
     pub position: Position,
     pub font: FontQuery<'a>,
     pub color: Color,
@@ -30,6 +38,7 @@ impl<'a> Text<'a> {
             content: content.to_owned(),
             font_size: 16.0,
             line_height: 1.25,
+            max_width: None,
             font: FontQuery::default(),
             position: Position::default(),
             color: Color::BLACK,
@@ -54,6 +63,12 @@ impl<'a> Text<'a> {
         self
     }
 
+    /// Set the width the text wraps at.
+    pub fn max_width(mut self, width: f32) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
     /// Set the font weight
     pub fn weight(mut self, weight: Weight) -> Self {
         self.font.weight = weight;
@@ -79,26 +94,46 @@ impl<'a> Text<'a> {
     }
 
     pub fn size(&self, font_system: &mut FontSystem) -> Size {
-        // FIXME: add line height
         let metrics = self.metrics();
         let attrs = self.attrs(font_system);
         let mut buffer = Buffer::new(font_system, metrics);
         let mut buffer = buffer.borrow_with(font_system);
 
+        buffer.set_size(self.max_width, None);
         buffer.set_text(&self.content, &attrs, Shaping::Advanced);
         buffer.shape_until_scroll(true);
 
-        let mut width = 0.0;
+        let mut width = 0.0f32;
         let mut height = 0.0;
 
         for run in buffer.layout_runs() {
-            width += run.line_w;
+            width = width.max(run.line_w);
             height += run.line_height;
         }
 
         Size::new(width, height)
     }
 
+    /// Shape the text so that it can be measured at different widths without
+    /// the [`FontSystem`].
+    pub fn shape(&self, font_system: &mut FontSystem) -> ShapedText {
+        let metrics = self.metrics();
+        let attrs = self.attrs(font_system);
+        let mut buffer = Buffer::new(font_system, metrics);
+        let mut buffer = buffer.borrow_with(font_system);
+
+        buffer.set_text(&self.content, &attrs, Shaping::Advanced);
+        buffer.shape_until_scroll(true);
+
+        let lines = buffer
+            .lines
+            .iter()
+            .filter_map(|line| line.shape_opt().cloned())
+            .collect();
+
+        ShapedText { lines, metrics }
+    }
+
     fn query_font(&self, font_system: &mut FontSystem) -> Option<Family<'a>> {
         let query: Query = Query {
             families: self.font.families.as_ref(),
@@ -138,31 +173,82 @@ impl<'a> Text<'a> {
             .weight(self.font.weight)
     }
 
+    /// Hash everything that changes how the text is drawn, apart from
+    /// the position.
+    pub(crate) fn hash_into(&self, hasher: &mut impl Hasher) {
+        self.content.hash(hasher);
+        self.font_size.to_bits().hash(hasher);
+        self.line_height.to_bits().hash(hasher);
+        self.max_width.map(f32::to_bits).hash(hasher);
+        self.font.families.hash(hasher);
+        self.font.weight.hash(hasher);
+        self.font.style.hash(hasher);
+        self.color.hash(hasher);
+    }
+
     pub fn draw_text(
         &self,
         pixmap: &mut Pixmap,
         font_system: &mut FontSystem,
         cache: &mut SwashCache,
     ) {
-        // TODO: cache text that has the same content and font size
+        self.draw_text_clipped(pixmap, font_system, cache, None);
+    }
+
+    /// Draw the text onto the `pixmap`, only the parts inside the `mask` are drawn.
+    pub fn draw_text_clipped(
+        &self,
+        pixmap: &mut Pixmap,
+        font_system: &mut FontSystem,
+        cache: &mut SwashCache,
+        mask: Option<&Mask>,
+    ) {
+        if let Some(glyphs) = self.rasterise(font_system, cache) {
+            self.draw_rasterised(pixmap, &glyphs, mask, Transform::identity());
+        }
+    }
+
+    /// Rasterise the text onto a pixmap that is the size of the text, returns
+    /// `None` if the text has no size.
+    pub fn rasterise(
+        &self,
+        font_system: &mut FontSystem,
+        cache: &mut SwashCache,
+    ) -> Option<Pixmap> {
+        self.rasterise_sized(font_system, cache).1
+    }
+
+    /// Rasterise the text, along with the size of the text that was shaped
+    /// to rasterise it.
+    pub(crate) fn rasterise_sized(
+        &self,
+        font_system: &mut FontSystem,
+        cache: &mut SwashCache,
+    ) -> (Size, Option<Pixmap>) {
         // TODO: check families that don't exist
         let metrics = self.metrics();
         let attrs = self.attrs(font_system);
-        let size = self.size(font_system);
 
         let mut buffer = Buffer::new(font_system, metrics);
         let mut buffer = buffer.borrow_with(font_system);
+        buffer.set_size(self.max_width, None);
         buffer.set_text(&self.content, &attrs, Shaping::Advanced);
         buffer.shape_until_scroll(true);
 
+        let mut width = 0.0f32;
+        let mut height = 0.0;
+        for run in buffer.layout_runs() {
+            width = width.max(run.line_w);
+            height += run.line_height;
+        }
+
         // TODO: add clippy lint for conversion
         let (r, g, b, a) = self.color.inner();
         let text_color = cosmic_text::Color::rgba(r, g, b, a);
-        let width = size.width.ceil() as u32;
-        let height = size.height.ceil() as u32;
-
-        let mut image = RgbaImage::new(width, height);
-        let size = IntSize::from_wh(image.width(), image.height()).unwrap();
+        let Some(size) = IntSize::from_wh(width.ceil() as u32, height.ceil() as u32) else {
+            return (Size::new(width, height), None);
+        };
+        let mut image = RgbaImage::new(size.width(), size.height());
 
         buffer.draw(cache, text_color, |x, y, _, _, color| {
             let [r, g, b, a] = color.as_rgba();
@@ -173,19 +259,85 @@ impl<'a> Text<'a> {
             }
         });
 
-        let glyph_pixmap = Pixmap::from_vec(image.to_vec(), size).unwrap();
+        let glyphs = Pixmap::from_vec(image.into_raw(), size);
+        (Size::new(width, height), glyphs)
+    }
+
+    /// Draw text that was rasterised with [`Text::rasterise`] at the text's position,
+    /// with a `transform`.
+    pub(crate) fn draw_rasterised(
+        &self,
+        pixmap: &mut Pixmap,
+        glyphs: &Pixmap,
+        mask: Option<&Mask>,
+        transform: Transform,
+    ) {
         let Position { x, y } = self.position;
         pixmap.draw_pixmap(
             x as i32,
             y as i32,
-            glyph_pixmap.as_ref(),
-            &PixmapPaint::default(),
-            Transform::identity(),
-            None,
+            glyphs.as_ref(),
+            &pixmap_paint(transform),
+            transform,
+            mask,
         );
     }
 }
 
+/// Text that has already been shaped, used to measure the size of text
+/// when it wraps at different widths.
+#[derive(Debug, Clone)]
+pub struct ShapedText {
+    /// The lines are shared so that cached text is cheap to clone.
+    lines: Arc<[ShapeLine]>,
+    metrics: Metrics,
+}
+
+impl ShapedText {
+    /// Get the size of the text when it wraps at `max_width`, this is the
+    /// same size that [`Text::size`] would give.
+    pub fn size(&self, max_width: Option<f32>) -> Size {
+        let mut width = 0.0f32;
+        let mut height = 0.0;
+
+        for line in self.lines.iter() {
+            let layout_lines = line.layout(
+                self.metrics.font_size,
+                max_width,
+                Wrap::WordOrGlyph,
+                None,
+                None,
+            );
+            for layout_line in layout_lines {
+                width = width.max(layout_line.w);
+                height += layout_line
+                    .line_height_opt
+                    .unwrap_or(self.metrics.line_height);
+            }
+        }
+
+        Size::new(width, height)
+    }
+
+    /// Get the distance from the top of the text to the baseline of the first line.
+    pub fn baseline(&self) -> f32 {
+        let Some(layout_line) = self.lines.first().and_then(|line| {
+            line.layout(self.metrics.font_size, None, Wrap::None, None, None)
+                .into_iter()
+                .next()
+        }) else {
+            return 0.0;
+        };
+
+        // The glyphs are centered in the line, the same as when the text is drawn
+        let line_height = layout_line
+            .line_height_opt
+            .unwrap_or(self.metrics.line_height);
+        let glyph_height = layout_line.max_ascent + layout_line.max_descent;
+        (line_height - glyph_height) / 2.0 + layout_line.max_ascent
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct FontQuery<'a> {
     /// A prioritized list of font family names or generic family names.
@@ -226,6 +378,48 @@ mod test {
         assert_eq!(metrics.line_height, 12.0);
     }
 
+    #[test]
+    fn wrap_text() {
+        let mut font_system = FontSystem::new();
+        let text = Text::new("Hello world, this is a long line of text").font_size(16.0);
+        let size = text.size(&mut font_system);
+
+        let wrapped = text.clone().max_width(size.width / 2.0);
+        let wrapped_size = wrapped.size(&mut font_system);
+        assert!(wrapped_size.width <= size.width / 2.0);
+        assert!(wrapped_size.height > size.height);
+    }
+
+    #[test]
+    fn shaped_text_matches_text_size() {
+        let mut font_system = FontSystem::new();
+        let text = Text::new("Hello world, this is a long line of text\nAnd another line");
+        let shaped = text.shape(&mut font_system);
+
+        let size = text.size(&mut font_system);
+        assert_eq!(shaped.size(None), size);
+        // Text shouldn't wrap at its own width
+        assert_eq!(shaped.size(Some(size.width)), size);
+        let wrapped = text.clone().max_width(100.0);
+        assert_eq!(shaped.size(Some(100.0)), wrapped.size(&mut font_system));
+    }
+
+    #[test]
+    fn baseline_matches_layout_runs() {
+        let mut font_system = FontSystem::new();
+        let text = Text::new("Hello world").font_size(24.0);
+        let shaped = text.shape(&mut font_system);
+
+        let mut buffer = Buffer::new(&mut font_system, text.metrics());
+        let attrs = text.attrs(&mut font_system);
+        buffer.set_text(&mut font_system, "Hello world", &attrs, Shaping::Advanced);
+        buffer.shape_until_scroll(&mut font_system, true);
+        let line_y = buffer.layout_runs().next().unwrap().line_y;
+
+        assert_eq!(shaped.baseline(), line_y);
+        assert!(shaped.baseline() < shaped.size(None).height);
+    }
+
     #[test]
     fn get_matched_font() {
         let mut font_system = FontSystem::new();
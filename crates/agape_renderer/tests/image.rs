@@ -1,6 +1,7 @@
-use agape_core::Size;
-use agape_renderer::image::Image;
-use image::ImageBuffer;
+use agape_core::{Bounds, Position, Size};
+use agape_renderer::FilterQuality;
+use agape_renderer::image::{Image, ImageCache, NineSlice, ObjectFit};
+use image::{DynamicImage, ImageBuffer, Rgba};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tempfile::TempDir;
@@ -35,10 +36,165 @@ fn render_output() {
     let (_temp, path) = save_image(300, 500);
     let data = image::open(path).unwrap();
     let image = Image::new(Arc::new(data));
-    image.draw(&mut pixmap);
+    image.draw(&mut pixmap, &mut ImageCache::default());
     for pixel in pixmap.pixels() {
         assert_eq!(pixel.red(), 0);
         assert_eq!(pixel.green(), 0);
         assert_eq!(pixel.blue(), 0);
     }
 }
+
+#[test]
+fn scaled_image_is_drawn_in_its_bounds() {
+    let mut pixmap = Pixmap::new(200, 200).unwrap();
+    let (_temp, path) = save_image(10, 10);
+    let data = image::open(path).unwrap();
+    let mut image = Image::new(Arc::new(data));
+    image.position = Position::new(30.0, 30.0);
+    image.size = Size::new(20.0, 20.0);
+    image.draw(&mut pixmap, &mut ImageCache::default());
+
+    assert_eq!(pixmap.pixel(31, 31).unwrap().alpha(), 255);
+    assert_eq!(pixmap.pixel(48, 48).unwrap().alpha(), 255);
+    assert_eq!(pixmap.pixel(52, 52).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(28, 28).unwrap().alpha(), 0);
+}
+
+/// Create an opaque image with the `color` of each pixel.
+fn image_from_fn(
+    width: u32,
+    height: u32,
+    color: impl Fn(u32, u32) -> [u8; 3],
+) -> Arc<DynamicImage> {
+    let buf = ImageBuffer::from_fn(width, height, |x, y| {
+        let [r, g, b] = color(x, y);
+        Rgba([r, g, b, 255])
+    });
+    Arc::new(DynamicImage::ImageRgba8(buf))
+}
+
+#[test]
+fn contain_keeps_aspect_ratio() {
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let mut image = Image::new(image_from_fn(20, 10, |_, _| [0, 0, 0])).fit(ObjectFit::Contain);
+    image.size = Size::unit(100.0);
+    image.draw(&mut pixmap, &mut ImageCache::default());
+
+    assert_eq!(pixmap.pixel(50, 20).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(50, 30).unwrap().alpha(), 255);
+    assert_eq!(pixmap.pixel(50, 70).unwrap().alpha(), 255);
+    assert_eq!(pixmap.pixel(50, 80).unwrap().alpha(), 0);
+}
+
+#[test]
+fn cover_is_clipped_to_bounds() {
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let mut image = Image::new(image_from_fn(20, 10, |_, _| [0, 0, 0])).fit(ObjectFit::Cover);
+    image.position = Position::new(20.0, 20.0);
+    image.size = Size::unit(40.0);
+    image.draw(&mut pixmap, &mut ImageCache::default());
+
+    assert_eq!(pixmap.pixel(21, 21).unwrap().alpha(), 255);
+    assert_eq!(pixmap.pixel(58, 58).unwrap().alpha(), 255);
+    assert_eq!(pixmap.pixel(10, 40).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(70, 40).unwrap().alpha(), 0);
+}
+
+#[test]
+fn aligned_image() {
+    let mut image = Image::new(image_from_fn(10, 10, |_, _| [0, 0, 0]))
+        .fit(ObjectFit::None)
+        .align(1.0, 0.0);
+    image.size = Size::new(100.0, 50.0);
+
+    let bounds = image.fitted_bounds();
+    assert_eq!(bounds.x, [90.0, 100.0]);
+    assert_eq!(bounds.y, [0.0, 10.0]);
+}
+
+#[test]
+fn scale_down_never_grows() {
+    let mut image = Image::new(image_from_fn(10, 10, |_, _| [0, 0, 0])).fit(ObjectFit::ScaleDown);
+    image.size = Size::unit(100.0);
+    assert_eq!(
+        image.fitted_bounds(),
+        Bounds::new(Position::unit(45.0), Size::unit(10.0))
+    );
+
+    image.size = Size::new(5.0, 100.0);
+    assert_eq!(image.fitted_bounds().x, [0.0, 5.0]);
+}
+
+#[test]
+fn rounded_corners_are_clipped() {
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let mut image = Image::new(image_from_fn(10, 10, |_, _| [0, 0, 0])).corner_radius(20.0);
+    image.size = Size::unit(100.0);
+    image.draw(&mut pixmap, &mut ImageCache::default());
+
+    assert_eq!(pixmap.pixel(1, 1).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(98, 98).unwrap().alpha(), 0);
+    assert_eq!(pixmap.pixel(50, 1).unwrap().alpha(), 255);
+    assert_eq!(pixmap.pixel(50, 50).unwrap().alpha(), 255);
+}
+
+#[test]
+fn nine_slice_keeps_corners() {
+    // A 12x12 image with a 4 pixel red border around a blue center
+    let data = image_from_fn(12, 12, |x, y| {
+        if (4..8).contains(&x) && (4..8).contains(&y) {
+            [0, 0, 255]
+        } else {
+            [255, 0, 0]
+        }
+    });
+    let mut pixmap = Pixmap::new(100, 100).unwrap();
+    let mut image = Image::new(data).nine_slice(NineSlice::all(4.0));
+    image.size = Size::new(100.0, 60.0);
+    image.draw(&mut pixmap, &mut ImageCache::default());
+
+    let color = |x, y| {
+        let pixel = pixmap.pixel(x, y).unwrap();
+        (pixel.red(), pixel.blue())
+    };
+    // The border stays 4 pixels wide
+    assert_eq!(color(3, 30), (255, 0));
+    assert_eq!(color(5, 30), (0, 255));
+    assert_eq!(color(50, 3), (255, 0));
+    assert_eq!(color(50, 5), (0, 255));
+    assert_eq!(color(96, 56), (255, 0));
+    assert_eq!(color(94, 54), (0, 255));
+    assert_eq!(pixmap.pixel(50, 70).unwrap().alpha(), 0);
+}
+
+#[test]
+fn nine_slice_corners_shrink_in_small_bounds() {
+    let data = image_from_fn(12, 12, |x, _| if x < 4 { [255, 0, 0] } else { [0, 0, 255] });
+    let mut pixmap = Pixmap::new(20, 20).unwrap();
+    let mut image = Image::new(data).nine_slice(NineSlice::new(0.0, 4.0, 0.0, 4.0));
+    image.size = Size::new(4.0, 10.0);
+    image.draw(&mut pixmap, &mut ImageCache::default());
+
+    assert_eq!(pixmap.pixel(0, 5).unwrap().red(), 255);
+    assert_eq!(pixmap.pixel(3, 5).unwrap().blue(), 255);
+    assert_eq!(pixmap.pixel(5, 5).unwrap().alpha(), 0);
+}
+
+#[test]
+fn nearest_filter_quality() {
+    let data = image_from_fn(
+        2,
+        1,
+        |x, _| if x == 0 { [0, 0, 0] } else { [255, 255, 255] },
+    );
+    let draw = |quality| {
+        let mut pixmap = Pixmap::new(100, 10).unwrap();
+        let mut image = Image::new(data.clone()).filter_quality(quality);
+        image.size = Size::new(100.0, 10.0);
+        image.draw(&mut pixmap, &mut ImageCache::default());
+        pixmap.pixel(45, 5).unwrap().red()
+    };
+
+    assert_eq!(draw(FilterQuality::Nearest), 0);
+    assert!(draw(FilterQuality::Bilinear) > 0);
+}
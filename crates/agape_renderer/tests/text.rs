@@ -24,7 +24,11 @@ fn render_output() {
         .weight(Weight(400));
     renderer.pixmap_mut().fill(tiny_skia::Color::WHITE);
     renderer.draw_text(text);
-    renderer.pixmap_mut().save_png("temp-output.png").unwrap();
+    let temp = tempfile::TempDir::new().unwrap();
+    renderer
+        .pixmap_mut()
+        .save_png(temp.path().join("output.png"))
+        .unwrap();
 }
 
 #[test]